@@ -1,12 +1,49 @@
 use chrono::{DateTime, Local, TimeDelta};
-use cubesim::{Cube, Move, MoveVariant};
 #[cfg(not(target_arch = "wasm32"))]
 use discord_rich_presence::{activity::{self, Assets}, DiscordIpc};
 use rand::Rng;
 use std::collections::HashMap;
 
-use self::scramble::{Cubes, Scrambler};
+use self::competition::{Competition, Format};
+use self::event::{Event, Variant, EVENTS, RELAY_PUZZLES};
+use self::filter::{Penalty, SolveFilter};
+use self::fmc::Fmc;
+use self::history::{History, Operation};
+use self::job::Job;
+use self::keys::{Action, ACTIONS};
+use self::kociemba::SolverOptions;
+use self::library::{AlgCase, AlgSet, Library};
+use self::multi_bld::MultiBld;
+use self::notation::{Metric, Slice};
+use self::relay::RelayPart;
+use self::scramble::{Cubes, Scrambler, Subset};
+use self::settings::{BoxGroup, Period, PlotView, Settings};
+use self::sheet::ScrambleSheet;
+use self::steps::{Step, StepSolutions};
+use self::trainer::{CaseSet, Trainer};
+mod cache;
+mod competition;
+mod cubie;
+mod event;
+mod filter;
+mod fmc;
+mod history;
+mod job;
+mod keys;
+mod kociemba;
+mod library;
+mod multi_bld;
+mod notation;
+mod reduction;
+mod relay;
+mod save;
 mod scramble;
+mod settings;
+mod sheet;
+mod stats;
+mod steps;
+mod trainer;
+mod two_by_two;
 
 // Wasm detection
 
@@ -20,44 +57,8 @@ fn is_wasm() -> bool {
     false
 }
 
-fn move_string(movement: cubesim::Move) -> String {
-    match movement {
-        Move::U(variant) => format_move("U", variant),
-        Move::F(variant) => format_move("F", variant),
-        Move::L(variant) => format_move("L", variant),
-        Move::R(variant) => format_move("R", variant),
-        Move::D(variant) => format_move("D", variant),
-        Move::B(variant) => format_move("B", variant),
-        Move::X(variant) => format_move("X", variant),
-        Move::Y(variant) => format_move("Y", variant),
-        Move::Z(variant) => format_move("Z", variant),
-        Move::Rw(val, variant) => format_move_with_val("Rw", val, variant),
-        Move::Fw(val, variant) => format_move_with_val("Fw", val, variant),
-        Move::Lw(val, variant) => format_move_with_val("Lw", val, variant),
-        Move::Dw(val, variant) => format_move_with_val("Dw", val, variant),
-        Move::Uw(val, variant) => format_move_with_val("Uw", val, variant),
-        Move::Bw(val, variant) => format_move_with_val("Bw", val, variant),
-    }
-}
-
-fn format_move(move_str: &str, variant: cubesim::MoveVariant) -> String {
-    match variant {
-        MoveVariant::Double => format!("{}2", move_str),
-        MoveVariant::Standard => move_str.to_string(),
-        MoveVariant::Inverse => format!("{}'", move_str),
-    }
-}
-
-fn format_move_with_val(move_str: &str, val: i32, variant: cubesim::MoveVariant) -> String {
-    if val == 1 {
-        format_move(move_str, variant)
-    } else {
-        format_move(format!("{}{}w", val, move_str).as_str(), variant)
-    }
-}
-
-
 #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
 pub struct SolveStats {
     time: String,
     scramble: String,
@@ -66,6 +67,10 @@ pub struct SolveStats {
     plus2: bool,
     dnf: bool,
     cube_type: Cubes,
+    variant: Variant, // How the puzzle was solved, making up the event with it
+    tags: Vec<String>,
+    memo: Option<f64>, // Seconds spent memorising, for blindfolded solves
+    relay: Vec<RelayPart>, // Scramble and split of each puzzle, for relay solves
 }
 
 impl Default for SolveStats {
@@ -78,40 +83,148 @@ impl Default for SolveStats {
             plus2: false,
             dnf: false,
             cube_type: Cubes::ThreeByThree,
+            variant: Variant::Standard,
+            tags: vec![],
+            memo: None,
+            relay: vec![],
         }
     }
 }
 
-fn solve(scramble: String) -> String {
-    let moves = cubesim::parse_scramble(scramble);
-    let cube = cubesim::FaceletCube::new(3);
-    let cube = cube.apply_moves(&moves);
-    let solution = match cubesim::solve(&cube) {
-        Some(data) => data,
-        None => return "Bad Scramble".to_string()
+impl SolveStats {
+    /// The event the solve was done in.
+    fn event(&self) -> Event {
+        Event::new(self.cube_type, self.variant)
+    }
+}
+
+/// Shown before solving where generating the solver's tables stops the app responding.
+const TABLES_WARNING: &str = "The solver's tables haven't been generated yet. This takes a while, and the page won't respond until it's done.";
+
+/// Solves a scramble, optimally on a 2x2 and with the two-phase solver on a 3x3,
+/// writing out each solution on its own line.
+fn solve(moves: Vec<cubesim::Move>, cube: Cubes, options: SolverOptions) -> String {
+    let solutions: Result<Vec<String>, String> = match cube {
+        Cubes::OneByOne => Err(format!("The solver doesn't support the {}", cube)),
+        Cubes::TwoByTwo => match two_by_two::from_moves(&moves) {
+            Some(cube) => Ok(two_by_two::solve(&cube).into_iter().take(options.count).map(|solution| notation::turns_string(&solution)).collect()),
+            None => Err("Bad Scramble".to_string()),
+        },
+        Cubes::ThreeByThree => match cubie::from_moves(&moves) {
+            Some(cube) => kociemba::solve(&cube, options).map(|solutions| solutions.iter().map(|solution| notation::turns_string(solution)).collect()),
+            None => Err("Bad Scramble".to_string()),
+        },
+        // Reduction only finds one solution
+        Cubes::FourByFour | Cubes::FiveByFive => reduction::solve(&moves, cube.size() as usize, options).map(|solution| vec![notation::turns_string(&solution)]),
+    };
+    match solutions {
+        Ok(solutions) => solutions
+            .iter()
+            .map(|solution| format!("{} ({})", solution, solution.split_whitespace().count()))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        Err(error) => error,
+    }
+}
+
+/// Draws a text field for a scramble, underlining the move that can't be read and saying why below it.
+fn scramble_edit(ui: &mut egui::Ui, text: &mut String, cube: Cubes) -> egui::Response {
+    let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+        let normal = egui::TextFormat {
+            font_id: egui::TextStyle::Body.resolve(ui.style()),
+            color: ui.visuals().text_color(),
+            ..Default::default()
+        };
+        let mut job = egui::text::LayoutJob::default();
+        match notation::parse_moves(text, cube.size()) {
+            Ok(_) => job.append(text, 0.0, normal),
+            Err(error) => {
+                let wrong = egui::TextFormat {
+                    color: ui.visuals().error_fg_color,
+                    underline: egui::Stroke::new(1.0, ui.visuals().error_fg_color),
+                    ..normal.clone()
+                };
+                job.append(&text[..error.span.start], 0.0, normal.clone());
+                job.append(&text[error.span.clone()], 0.0, wrong);
+                job.append(&text[error.span.end..], 0.0, normal);
+            }
+        }
+        job.wrap.max_width = wrap_width;
+        ui.fonts(|fonts| fonts.layout_job(job))
     };
-    let mut result = String::new();
-    for movement in solution {
-        result.push_str(format!("{} ", move_string(movement).to_string()).as_str());
+    let response = ui.add(egui::TextEdit::singleline(text).layouter(&mut layouter));
+    if let Err(error) = notation::parse_moves(text, cube.size()) {
+        ui.colored_label(ui.visuals().error_fg_color, error.to_string());
     }
-    result
+    response
+}
 
+/// Draws a last layer from `trainer::last_layer`, coloured as a cube held with yellow on top and green in front.
+///
+/// OLL diagrams only colour the top stickers, since that's all OLL cares about.
+fn last_layer_diagram(ui: &mut egui::Ui, grid: &[[Option<cubesim::Face>; 5]; 5], oll: bool) {
+    let size = 20.0;
+    let (response, painter) = ui.allocate_painter(egui::vec2(size * 5.0, size * 5.0), egui::Sense::hover());
+    for (i, row) in grid.iter().enumerate() {
+        for (j, sticker) in row.iter().enumerate() {
+            let Some(face) = *sticker else {
+                continue;
+            };
+            let colour = match face {
+                cubesim::Face::U => egui::Color32::from_rgb(255, 213, 0),
+                _ if oll => egui::Color32::GRAY,
+                cubesim::Face::D => egui::Color32::WHITE,
+                cubesim::Face::F => egui::Color32::from_rgb(0, 155, 72),
+                cubesim::Face::B => egui::Color32::from_rgb(0, 70, 173),
+                cubesim::Face::R => egui::Color32::from_rgb(255, 88, 0),
+                cubesim::Face::L => egui::Color32::from_rgb(183, 18, 52),
+                cubesim::Face::X => egui::Color32::DARK_GRAY,
+            };
+            let min = response.rect.min + egui::vec2(j as f32 * size, i as f32 * size);
+            let rect = egui::Rect::from_min_size(min, egui::vec2(size, size)).shrink(1.0);
+            painter.rect_filled(rect, 2.0, colour);
+        }
+    }
+}
+
+/// Draws the net of a scrambled puzzle, like on printed scramble sheets.
+fn net_diagram(ui: &mut egui::Ui, puzzle: Cubes, scramble: &str) {
+    let (face, gap) = (24.0, 2.0);
+    let (response, painter) = ui.allocate_painter(egui::vec2(face * 4.0 + gap * 3.0, face * 3.0 + gap * 2.0), egui::Sense::hover());
+    let Some(stickers) = sheet::stickers(puzzle, scramble) else {
+        return;
+    };
+    let size = puzzle.size() as usize;
+    let sticker = face / size as f32;
+    for (i, colour) in stickers.iter().enumerate() {
+        let (x, y) = sheet::sticker_position(i, size, face, gap);
+        let [r, g, b] = sheet::colour(*colour);
+        let rect = egui::Rect::from_min_size(response.rect.min + egui::vec2(x, y), egui::vec2(sticker, sticker)).shrink(0.5);
+        painter.rect_filled(rect, 0.0, egui::Color32::from_rgb(r, g, b));
+    }
 }
 
 // State
 
+/// Data belonging to a single session, the rest lives in `Settings`.
 #[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+#[serde(default)]
 pub struct State {
     // Time-related fields
     time: String,     // Time as seen in timer
     starttime: DateTime<Local>, // Start time of the timer
     timeron: bool,    // Indicates whether the timer is currently running
     debounce: DateTime<Local>, // Used for debouncing timer inputs
+    inspecting: bool, // Indicates whether inspection is currently running
+    inspection_start: DateTime<Local>, // Start time of the inspection
+    inspection_time: f64, // Seconds used for inspecting the last solve
+    memo: Option<f64>, // Memorisation time of the running blindfolded solve, once it's over
+    splits: Vec<f64>, // Times the puzzles of the running relay were finished at
 
     // Scramble-related fields
     scramble: String, // Scramble for the puzzle
     scramble_text: String, // Text representation of the scramble
-    c_scramble: String, // Compressed scramble
+    relay: Vec<RelayPart>, // Scramble of each puzzle, when the session is a relay
 
     // Averages and statistics
     ao5: String,      // Average of 5 solves
@@ -144,89 +257,57 @@ pub struct State {
     show_solve: bool,        // Indicates whether solve details are being displayed
     solve_info: bool,        // Indicates whether solve information is shown
     solve_info_copy: String, // Copy of solve information
-
-    // Settings and UI-related fields
-    settings_open: bool,      // Indicates whether settings are open
-    importing: bool,          // Indicates whether data is being imported
-    imported_data: String,    // Imported data
-    imported_fail: String,    // Message for failed imports
-    prec: usize,              // Precision for numeric display
-    ao5_prec: usize,          // Precision for ao5 display
-    solves_prec: usize,       // Precision for solves display
-    old_ao5_prec: usize,      // Old precision for ao5
-    old_solves_prec: usize,   // Old precision for solves
-    old_prec: usize,          // Old precision
     fmt_solves: Vec<String>,  // Formatted solves for display
-    background: [u8; 3],      // Background color
-    window: [u8; 3],          // Window color
-    button: [u8; 3],          // Button color
-    outline: [u8; 3],         // Outline color
-    titlebar: [u8; 3],        // Title bar color
-    text: [u8; 3],            // Text color
-    outline_w: f32,           // Outline width
-    widget: [u8; 3],          // Widget color
-    show_solve_info: bool,    // Indicates whether solve info is being shown
-    show_tools: bool,         // Indicates whether tools are being shown
-    current_tool: String,     // Current selected tool
     plottable: Vec<[f64; 2]>, // Vector of plottable data
-    plot_aspect_ratio: f32,   // Aspect ratio for plots
-    stats_open: bool,         // Whether to have Stats menu open
-    show_left_bar: bool,      // Whether to show the left (main) bar of the screen
-    footer: String,           // Custom footer text (for youtubers etc)
-    show_footer: bool,
-
-    // Puzzle-related fields
-    name: String,             // Name of the puzzle
-    cube_type: Cubes,         // Type of the cube
-    cube_type_old: Cubes,     // Old type of the cube
-    show_scramble: bool,      // Indicates whether scramble is being shown
-    solution: String,         // Solution for the puzzle
-    c_solution: String,       // Compressed solution
-
-    // Initiation fields
-    download: bool,           // Whether the Download the Desktop App prompt has been closed
-    used: bool,               // Whether the About Cubism menu has been shown
+    plot_dnfs: Vec<[f64; 2]>, // Points marking DNFs on the time plot
+    plot_ao5: Vec<[f64; 2]>,  // Rolling ao5 for the time plot
+    plot_ao12: Vec<[f64; 2]>, // Rolling ao12 for the time plot
+    plot_ao100: Vec<[f64; 2]>, // Rolling ao100 for the time plot
+    #[serde(skip)]
+    history: History,         // Undo/redo stacks for the solves
+    tag_input: String,        // Tag being typed in Solve Info
+
+    // Session fields
+    name: String,             // Name of the session
+    event: Option<Event>,     // Event of the session, overriding the default in settings
+    event_old: Event,         // Old event of the session
+    subset: Subset,           // Part of the cube scrambled, for 3x3 practice
+    inspection: Option<bool>, // Inspection on/off, overriding the default in settings
 }
 
 impl Default for State {
     fn default() -> Self {
         Self {
-            // Customizable Footer
-            footer: "Cubism by Aityz (This text is customizable)".to_string(),
-
             // Cubes Configuration
-            show_scramble: true,
-            cube_type_old: Cubes::ThreeByThree,
-            cube_type: Cubes::ThreeByThree,
+            event_old: Event::default(),
+            event: None,
+            subset: Subset::Full,
+            inspection: None,
             scramble_text: "".to_string(),
 
-            // Scramble & Solution
-            c_scramble: "".to_string(),
-            c_solution: "".to_string(),
-            solution: "".to_string(),
-
-            // Widget & Tools
-            widget: [150, 150, 150],
-            show_tools: true,
-
             // Plotting
             plottable: vec![],
-            plot_aspect_ratio: 2.0,
-
-            // First Load 
-            download: true,
-            used: false,
+            plot_dnfs: vec![],
+            plot_ao5: vec![],
+            plot_ao12: vec![],
+            plot_ao100: vec![],
 
             // Solve Info
             solve_index: 0,
             solve_info: false,
             solve_info_copy: "".to_string(),
+            show_solve: false,
 
             // Timing
             time: "0.00".to_string(),
             starttime: Local::now(),
             timeron: false,
             debounce: Local::now(),
+            inspecting: false,
+            inspection_start: Local::now(),
+            inspection_time: 0.0,
+            memo: None,
+            splits: vec![],
 
             // Averages
             ao5: "".to_string(),
@@ -253,104 +334,105 @@ impl Default for State {
             best_ao5000: "".to_string(),
             best_mo3: "".to_string(),
 
-            // Solves & Importing
+            // Solves
             solves: vec![],
-            importing: false,
-            imported_data: "".to_string(),
-            imported_fail: "".to_string(),
-
-            // Precision Settings
-            prec: 2,
-            ao5_prec: 3,
-            solves_prec: 2,
-            old_prec: 2,
-            old_ao5_prec: 3,
-            old_solves_prec: 2,
 
             // Formatting
             fmt_solves: vec![],
+            history: History::default(),
+            tag_input: "".to_string(),
 
-            // UI Colors
-            background: [255, 255, 255],
-            window: [255, 255, 255],
-            button: [255, 255, 255],
-            titlebar: [255, 255, 255],
-            outline: [0, 0, 0],
-            text: [0, 0, 0],
-
-            // Visual Options
-            outline_w: 0.5,
-            show_solve: false,
-            show_footer: true,
-            show_solve_info: true,
-            current_tool: "Select Tool".to_string(),
-
-            // Application Settings
+            // Session
             name: "Default".to_string(),
-            stats_open: false,
-            show_left_bar: true,
-            settings_open: false,
 
             // Scramble
             scramble: "".to_string(),
+            relay: vec![],
         }
     }
 }
 
-fn timestamp() -> String {
-    chrono::Local::now().timestamp().to_string()
+/// What the tools and the import window are working on, which is only kept while the app is open.
+pub struct Scratch {
+    current_tool: String,  // Current selected tool
+    solution: String,      // Solution for the puzzle
+    c_scramble: String,    // Custom scramble
+    c_solution: String,    // Solution for the custom scramble
+    algorithm: String,     // Algorithm edited in the Algorithm Tools
+    importing: bool,       // Indicates whether data is being imported
+    imported_data: String, // Imported data
+    imported_fail: String, // Message for failed imports
 }
 
-fn round(num: f64, decimals: usize) -> f64 {
-    let factor = 10.0_f64.powi(decimals as i32);
-    (num * factor).round() / factor
+impl Default for Scratch {
+    fn default() -> Self {
+        Self {
+            current_tool: "Select Tool".to_string(),
+            solution: "".to_string(),
+            c_scramble: "".to_string(),
+            c_solution: "".to_string(),
+            algorithm: "".to_string(),
+            importing: false,
+            imported_data: "".to_string(),
+            imported_fail: "".to_string(),
+        }
+    }
 }
 
-fn average(solves: &Vec<SolveStats>, number: usize, prec: usize) -> String {
-    let latest: Vec<SolveStats> = solves.get(0..=number).unwrap().to_vec();
-    let mut max_val: f64 = latest[0].time.parse::<f64>().unwrap();
-    let mut min_val: f64 = latest[0].time.parse::<f64>().unwrap();
-    let mut dnfs = 0;
+/// Length of WCA inspection, after which a +2 and then a DNF is given
+const INSPECTION_SECONDS: f64 = 15.0;
 
-    for solve in &latest {
-        if solve.dnf {
-            dnfs += 1;
-        } else {
-            let parsed = solve.time.parse::<f64>().unwrap();
-            if parsed > max_val {
-                max_val = parsed;
-            }
-            if parsed < min_val {
-                min_val = parsed;
-            }
-        }
-    }
+/// Number of weeks shown in the progress heatmap
+const HEATMAP_WEEKS: u64 = 26;
 
-    if dnfs >= 2 {
-        return "DNF".to_string();
-    }
+/// How long the undo toast stays up for
+const TOAST_SECONDS: i64 = 5;
 
-    let mut sum: f64 = 0.0;
-    let mut count = 0;
-    for solve in &latest {
-        if !solve.dnf {
-            let parsed = solve.time.parse::<f64>().unwrap();
-            if parsed != max_val && parsed != min_val {
-                sum += parsed;
-                count += 1;
-            }
-        }
+fn seconds_since(start: DateTime<Local>) -> f64 {
+    Local::now().signed_duration_since(start).num_microseconds().unwrap() as f64 / 1000000.0
+}
+
+/// Returns the (+2, DNF) penalties for an inspection of the given length
+fn inspection_penalty(seconds: f64) -> (bool, bool) {
+    if seconds > INSPECTION_SECONDS + 2.0 {
+        (false, true)
+    } else if seconds > INSPECTION_SECONDS {
+        (true, false)
+    } else {
+        (false, false)
     }
+}
 
-    if dnfs == 1 {
-        sum = sum + max_val;
-        count += 1;
+/// Text of a solve for copying, such as "12.34+2 @ R U F"
+fn copy_text(solve: &SolveStats, prec: usize) -> String {
+    let time: f64 = solve.time.parse().unwrap();
+    if solve.dnf {
+        format!("DNF [{}] @ {}", round(time, prec), solve.scramble)
+    } else if solve.plus2 {
+        format!("{}+2 @ {}", round(time - 2.0, prec), solve.scramble)
+    } else {
+        format!("{} @ {}", round(time, prec), solve.scramble)
     }
+}
+
+fn timestamp() -> String {
+    chrono::Local::now().timestamp().to_string()
+}
+
+fn round(num: f64, decimals: usize) -> f64 {
+    let factor = 10.0_f64.powi(decimals as i32);
+    (num * factor).round() / factor
+}
 
-    round(sum / count as f64, prec).to_string()
+/// Text of an average, where `None` is a DNF.
+fn format_average(average: Option<f64>, prec: usize) -> String {
+    match average {
+        Some(average) => round(average, prec).to_string(),
+        None => "DNF".to_string(),
+    }
 }
 
-fn mean(solves: &Vec<SolveStats>, number: usize, prec: usize) -> String {
+fn mean(solves: &[SolveStats], number: usize, prec: usize) -> String {
     let latest: Vec<SolveStats> = solves.get(0..=number).unwrap().to_vec();
     let mut total: f64 = 0.0;
     for solve in &latest {
@@ -359,26 +441,51 @@ fn mean(solves: &Vec<SolveStats>, number: usize, prec: usize) -> String {
     round(total / latest.len() as f64, prec).to_string()
 }
 
-#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(serde::Deserialize, serde::Serialize, Default)]
 #[serde(default)]
 pub struct Cubism {
+    settings: Settings,
     state: State,
     sessions: HashMap<String, State>,
+    library: Library, // Algorithm sets and their drill history
+    trainer: Trainer, // LL Trainer cases and their statistics
+    multi_bld: MultiBld, // Multi-BLD scrambles and attempts
+    fmc: Fmc, // FMC attempt in progress and past attempts
+    competition: Competition, // Simulated competition rounds, past and running
     #[serde(skip)]
     set_font: bool,
     #[serde(skip)]
     started: bool,
-}
-
-impl Default for Cubism {
-    fn default() -> Self {
-        Self {
-            sessions: HashMap::new(),
-            state: State::default(),
-            set_font: false,
-            started: false,
-        }
-    }
+    #[serde(skip)]
+    toast: Option<(String, DateTime<Local>)>, // Message offering to undo the last operation
+    #[serde(skip)]
+    confirm_reset: bool, // Whether the Reset App confirmation is open
+    #[serde(skip)]
+    new_pbs: Vec<String>, // Personal bests set by the last solve
+    #[serde(skip)]
+    solve_job: Option<Job<String>>, // Solver running for the Solve tool
+    #[serde(skip)]
+    c_solve_job: Option<Job<String>>, // Solver running for the Custom Solve tool
+    #[serde(skip)]
+    steps_job: Option<Job<Vec<StepSolutions>>>, // Step Solver running on the current scramble
+    #[serde(skip)]
+    scramble_job: Option<Job<String>>, // Scramble being made for the timer, when that takes a while
+    #[serde(skip)]
+    fmc_job: Option<Job<String>>, // Scramble being made for an FMC attempt
+    #[serde(skip)]
+    steps: Option<(String, Step, Result<Vec<StepSolutions>, String>)>, // Last scramble and step solved, with the solutions
+    #[serde(skip)]
+    sheet: Option<ScrambleSheet>, // Scramble sheet generated in Scramble Sheets
+    #[serde(skip)]
+    sheet_job: Option<Job<ScrambleSheet>>, // Scramble sheet being generated
+    #[serde(skip)]
+    sheet_status: Option<String>, // Whether the scramble sheet was saved, or why it wasn't
+    #[serde(skip)]
+    rebinding: Option<Action>, // Action waiting for a key to be bound to, in the settings
+    #[serde(skip)]
+    load_error: Option<(String, String)>, // Why the saved data couldn't be read, with the data itself
+    #[serde(skip)]
+    scratch: Scratch, // Input of the tools and the import window
 }
 
 impl Cubism {
@@ -391,126 +498,1601 @@ impl Cubism {
                 "https://github.com/cubetimer/Cubetimer/releases",
             );
             if ui.button("Close").clicked() {
-                self.state.download = false;
+                self.settings.download = false;
             }
         });
     }
     #[cfg(not(target_arch = "wasm32"))]
     fn downloader(&mut self, _ctx: &egui::Context) {
-        self.state.download = false;
+        self.settings.download = false;
     }
 
     pub fn reset_time(&mut self) {
         let mut time: String = "0.".to_string();
-        for _ in 0..self.state.prec {
-            time.push_str("0");
+        for _ in 0..self.settings.prec {
+            time.push('0');
         }
         self.state.time = time;
     }
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        if let Some(text) = cc.storage.and_then(|storage| storage.get_string(eframe::APP_KEY)) {
+            let mut app = match save::load(&text) {
+                Ok(app) => app,
+                // Nothing is saved over the data until the user has seen why it couldn't be read
+                Err(error) => Self {
+                    load_error: Some((error.to_string(), text)),
+                    ..Self::default()
+                },
+            };
+            app.calculate_plottable();
+            return app;
         }
 
         Default::default()
     }
     pub fn calculate_plottable(&mut self) {
-        let mut x: f64 = 0.0;
-        let mut times: Vec<[f64; 2]> = vec![];
-        let mut solves = self.state.solves.clone();
-        solves.reverse();
-        for solve in solves {
-            times.push([x, solve.time.parse().unwrap()]);
-            x += 1.0;
-        }
-        self.state.plottable = times;
+        let times = stats::chronological_times(&self.event_solves());
+        let mut plottable: Vec<[f64; 2]> = vec![];
+        let mut dnfs: Vec<[f64; 2]> = vec![];
+        for (x, time) in times.iter().enumerate() {
+            match time {
+                Some(time) => plottable.push([x as f64, *time]),
+                // DNFs are marked along the bottom instead of at their time
+                None => dnfs.push([x as f64, 0.0]),
+            }
+        }
+        let rolling = |size: usize| -> Vec<[f64; 2]> {
+            stats::rolling_average(&times, size)
+                .into_iter()
+                .filter_map(|(x, average)| Some([x as f64, average?]))
+                .collect()
+        };
+        self.state.plot_ao5 = rolling(5);
+        self.state.plot_ao12 = rolling(12);
+        self.state.plot_ao100 = rolling(100);
+        self.state.plottable = plottable;
+        self.state.plot_dnfs = dnfs;
+    }
+    /// Solves of the current session done in its event, or of every session if `all` is set.
+    fn solves_in(&self, all: bool) -> Vec<SolveStats> {
+        let mut solves = self.event_solves();
+        if all {
+            let event = self.event();
+            for session in self.sessions.values() {
+                if session.name != self.state.name {
+                    solves.extend(session.solves.iter().filter(|solve| solve.event() == event).cloned());
+                }
+            }
+        }
+        solves
     }
-    pub fn make_scramble(&self) -> String {
-        let scrambler = Scrambler::from(self.state.cube_type.clone());
-        scrambler.scramble()
+    /// Every tag used on a solve in any session, sorted.
+    fn known_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self.solves_in(true).into_iter().flat_map(|solve| solve.tags).collect();
+        tags.sort();
+        tags.dedup();
+        tags
     }
-    pub fn refresh_averages(&mut self) {
-        let len = self.state.solves.len();
-        let solves = &self.state.solves;
-        if len > 2 {
-            let mo3 = average(&solves, 2, self.state.ao5_prec);
-            if self.state.mo3 > mo3 {
-                self.state.best_mo3 = mo3.clone();
+    /// Replaces the tags of a solve, so that it can be undone.
+    fn set_tags(&mut self, index: usize, tags: Vec<String>) {
+        let operation = Operation::Tags {
+            index,
+            before: self.state.solves[index].tags.clone(),
+            after: tags,
+        };
+        self.state.history.perform(operation, &mut self.state.solves);
+    }
+    /// Draws the tags of the solve shown in Solve Info, with a way to add and remove them.
+    fn tag_editor(&mut self, ui: &mut egui::Ui) {
+        let index = self.state.solve_index;
+        let tags = self.state.solves[index].tags.clone();
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Tags: ");
+            for tag in &tags {
+                if ui.button(format!("{} x", tag)).on_hover_text("Remove tag").clicked() {
+                    self.set_tags(index, tags.iter().filter(|other| *other != tag).cloned().collect());
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            let response = ui.text_edit_singleline(&mut self.state.tag_input);
+            let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("Add Tag").clicked() || entered {
+                let tag = self.state.tag_input.trim().to_string();
+                if !tag.is_empty() && !tags.contains(&tag) {
+                    let mut new_tags = tags.clone();
+                    new_tags.push(tag);
+                    self.set_tags(index, new_tags);
+                }
+                self.state.tag_input.clear();
+            }
+        });
+        let suggestions: Vec<String> = self.known_tags().into_iter().filter(|tag| !tags.contains(tag)).collect();
+        if !suggestions.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Add: ");
+                for tag in suggestions {
+                    if ui.small_button(&tag).clicked() {
+                        let mut new_tags = tags.clone();
+                        new_tags.push(tag);
+                        self.set_tags(index, new_tags);
+                    }
+                }
+            });
+        }
+    }
+    /// Draws the Tags tool, with averages per tag and a comparison of solves with and without a tag.
+    fn tag_stats(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.settings.stats_all_sessions, "All Sessions");
+        let solves = self.solves_in(self.settings.stats_all_sessions);
+        let tags = self.known_tags();
+        if tags.is_empty() {
+            ui.label("Add tags to solves in the Solve Info window to see statistics for them.");
+            return;
+        }
+        let prec = self.settings.ao5_prec;
+        let format = |time: Option<f64>| match time {
+            Some(time) => round(time, prec).to_string(),
+            None => "-".to_string(),
+        };
+        let tagged = |tag: &str, with: bool| -> Vec<SolveStats> {
+            solves.iter().filter(|solve| solve.tags.iter().any(|other| other == tag) == with).cloned().collect()
+        };
+        egui::Grid::new("tag_stats").striped(true).show(ui, |ui| {
+            for heading in ["Tag", "Solves", "Mean", "Best", "Best Ao5", "Best Ao12"] {
+                ui.strong(heading);
+            }
+            ui.end_row();
+            for tag in &tags {
+                let solves = tagged(tag, true);
+                let summary = stats::summarize(&solves, self.settings.threshold);
+                let times = stats::chronological_times(&solves);
+                ui.label(tag);
+                ui.label(summary.count.to_string());
+                ui.label(format(summary.mean));
+                ui.label(format(summary.best));
+                ui.label(format(stats::best_average(&times, 5)));
+                ui.label(format(stats::best_average(&times, 12)));
+                ui.end_row();
+            }
+        });
+        if !tags.contains(&self.settings.compare_tag) {
+            self.settings.compare_tag = tags[0].clone();
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Compare");
+            egui::ComboBox::from_id_source("compare_tag")
+                .selected_text(self.settings.compare_tag.clone())
+                .show_ui(ui, |ui| {
+                    for tag in &tags {
+                        ui.selectable_value(&mut self.settings.compare_tag, tag.clone(), tag);
+                    }
+                });
+        });
+        let with = tagged(&self.settings.compare_tag, true);
+        let without = tagged(&self.settings.compare_tag, false);
+        let summaries = [
+            stats::summarize(&with, self.settings.threshold),
+            stats::summarize(&without, self.settings.threshold),
+        ];
+        let best_ao5 = [
+            stats::best_average(&stats::chronological_times(&with), 5),
+            stats::best_average(&stats::chronological_times(&without), 5),
+        ];
+        egui::Grid::new("tag_compare").striped(true).show(ui, |ui| {
+            ui.label("");
+            ui.strong(format!("With {}", self.settings.compare_tag));
+            ui.strong("Without");
+            ui.end_row();
+            for (name, values) in [
+                ("Solves", summaries.clone().map(|summary| summary.count.to_string())),
+                ("Mean", summaries.clone().map(|summary| format(summary.mean))),
+                ("Median", summaries.clone().map(|summary| format(summary.median))),
+                ("Standard Deviation", summaries.clone().map(|summary| format(summary.std_dev))),
+                ("Best", summaries.clone().map(|summary| format(summary.best))),
+                ("Best Ao5", best_ao5.map(format)),
+                ("DNF Rate", summaries.clone().map(|summary| format!("{}%", round(summary.dnf_rate() * 100.0, 1)))),
+            ] {
+                ui.label(name);
+                for value in values {
+                    ui.label(value);
+                }
+                ui.end_row();
+            }
+        });
+    }
+    /// Draws the solver limits, a Solve button and the solutions, for the Solve or Custom Solve tool.
+    ///
+    /// The solver runs in the background, so the result shows up on a later frame.
+    fn solver(&mut self, ui: &mut egui::Ui, custom: bool) {
+        if self.event().is_relay() {
+            ui.label("The solver doesn't support relays.");
+            return;
+        }
+        if self.cube_type() == Cubes::OneByOne {
+            ui.label("The solver doesn't support the 1x1.");
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Max Length");
+            ui.add(egui::Slider::new(&mut self.settings.solver_max_length, 16..=30));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Time");
+            ui.add(egui::Slider::new(&mut self.settings.solver_time, 0.1..=30.0).suffix("s"));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Solutions");
+            ui.add(egui::Slider::new(&mut self.settings.solver_count, 1..=10));
+        });
+        let options = SolverOptions {
+            max_length: self.settings.solver_max_length,
+            time: self.settings.solver_time,
+            count: self.settings.solver_count,
+        };
+        let scramble = if custom { self.scratch.c_scramble.clone() } else { self.state.scramble.clone() };
+        let cube = self.cube_type();
+        let moves = notation::parse_moves(&scramble, cube.size());
+        let (job, solution) = if custom {
+            (&mut self.c_solve_job, &mut self.scratch.c_solution)
+        } else {
+            (&mut self.solve_job, &mut self.scratch.solution)
+        };
+        if let Some(result) = job.as_ref().and_then(|job| job.poll()) {
+            *solution = result.unwrap_or_else(|error| error);
+            *job = None;
+        }
+        let ready = match cube {
+            Cubes::TwoByTwo => two_by_two::tables_ready(),
+            Cubes::FourByFour | Cubes::FiveByFive => kociemba::tables_ready() && reduction::tables_ready(cube.size() as usize),
+            _ => kociemba::tables_ready(),
+        };
+        if job.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                if ready {
+                    ui.label("Solving...");
+                } else {
+                    ui.label("Generating tables, this only happens once...");
+                }
+            });
+            ui.ctx().request_repaint();
+        } else {
+            match moves {
+                Ok(moves) => {
+                    if job::BLOCKS && !ready {
+                        ui.label(TABLES_WARNING);
+                    }
+                    if ui.button("Solve").clicked() {
+                        *job = Some(Job::spawn(move || solve(moves, cube, options)));
+                    }
+                }
+                // Checked before solving, so a typo is pointed out rather than solved as something else
+                Err(error) => {
+                    ui.add_enabled(false, egui::Button::new("Solve"));
+                    // The custom scramble field already shows its error
+                    if !custom {
+                        ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+                    }
+                }
+            }
+        }
+        ui.label("Solutions:");
+        for line in solution.lines() {
+            ui.horizontal(|ui| {
+                ui.label(line);
+                if ui.small_button("Copy").clicked() {
+                    let moves = line.rsplit_once(" (").map_or(line, |(moves, _)| moves);
+                    ui.output_mut(|o| o.copied_text = moves.to_string());
+                }
+            });
+        }
+    }
+    /// Draws the Algorithm Tools, rewriting the algorithm in its text field and counting its moves.
+    fn algorithm_tools(&mut self, ui: &mut egui::Ui) {
+        let cube = self.cube_type();
+        scramble_edit(ui, &mut self.scratch.algorithm, cube);
+        let Ok(turns) = notation::parse(&self.scratch.algorithm) else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            let mut result = None;
+            if ui.button("Invert").clicked() {
+                result = Some(notation::invert(&turns));
+            }
+            if ui.button("Simplify").on_hover_text("Merge and cancel moves, like R R' or R L R'").clicked() {
+                result = Some(notation::simplify(&turns));
+            }
+            if ui.button("Mirror M").on_hover_text("Mirror left to right").clicked() {
+                result = Some(notation::mirror(&turns, Slice::M));
+            }
+            if ui.button("Mirror S").on_hover_text("Mirror front to back").clicked() {
+                result = Some(notation::mirror(&turns, Slice::S));
+            }
+            if ui.button("Copy").clicked() {
+                ui.output_mut(|o| o.copied_text = notation::turns_string(&turns));
+            }
+            if let Some(result) = result {
+                self.scratch.algorithm = notation::turns_string(&result);
+            }
+        });
+        let counts: Vec<String> = [Metric::Htm, Metric::Qtm, Metric::Stm, Metric::Etm]
+            .iter()
+            .map(|&metric| format!("{}: {}", metric, notation::count(&turns, metric)))
+            .collect();
+        ui.label(counts.join("   "));
+    }
+    /// Draws the LL Trainer: the cases being trained, the last case once it's done and the statistics of each case.
+    fn trainer(&mut self, ui: &mut egui::Ui) {
+        if self.cube_type() != Cubes::ThreeByThree {
+            ui.label("The LL Trainer only works in 3x3 sessions.");
+            return;
+        }
+        let toggled = ui
+            .checkbox(&mut self.trainer.active, "Train with the timer")
+            .on_hover_text("Scrambles set up the selected cases, and times go to the case statistics instead of the session")
+            .changed();
+        if toggled && self.trainer.active {
+            self.library.drilling = false;
+        }
+        ui.checkbox(&mut self.trainer.show_case, "Show the case before solving");
+        ui.label("Apply the scramble with yellow on top and green in front.");
+        let mut selection = false;
+        for set in [CaseSet::Oll, CaseSet::Pll] {
+            let selected = &mut self.trainer.selected;
+            let ids: Vec<String> = trainer::cases(set).iter().map(|case| case.id()).collect();
+            let count = ids.iter().filter(|id| selected.contains(id)).count();
+            egui::CollapsingHeader::new(format!("{} ({}/{} selected)", set, count, ids.len())).id_source(set).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("All").clicked() {
+                        selected.retain(|id| !ids.contains(id));
+                        selected.extend(ids.iter().cloned());
+                        selection = true;
+                    }
+                    if ui.button("None").clicked() {
+                        selected.retain(|id| !ids.contains(id));
+                        selection = true;
+                    }
+                });
+                ui.horizontal_wrapped(|ui| {
+                    for case in trainer::cases(set) {
+                        let id = case.id();
+                        let mut on = selected.contains(&id);
+                        if ui.checkbox(&mut on, case.name).on_hover_text(case.algorithm).changed() {
+                            if on {
+                                selected.push(id);
+                            } else {
+                                selected.retain(|other| *other != id);
+                            }
+                            selection = true;
+                        }
+                    }
+                });
+            });
+        }
+        if toggled || (selection && self.trainer.active) {
+            self.state.scramble = self.make_scramble();
+        }
+        if self.trainer.active && self.trainer.selected.is_empty() {
+            ui.label("Select some cases to train, until then scrambles are random.");
+        }
+        let trainer = &self.trainer;
+        if let (true, Some(case)) = (trainer.show_case, trainer.case.as_ref().and_then(|id| trainer::case(id))) {
+            ui.separator();
+            ui.label(format!("Next: {}", case.id()));
+            if let Some(grid) = trainer::last_layer(&self.state.scramble) {
+                last_layer_diagram(ui, &grid, case.set == CaseSet::Oll);
+            }
+        }
+        if let Some((id, setup)) = trainer.last.clone() {
+            ui.separator();
+            if let Some(case) = trainer::case(&id) {
+                ui.label(format!("Last: {}", id));
+                if let Some(grid) = trainer::last_layer(&setup) {
+                    last_layer_diagram(ui, &grid, case.set == CaseSet::Oll);
+                }
+                ui.label(case.algorithm);
+                if ui.add_enabled(!trainer.last_missed, egui::Button::new("I didn't recognise it")).clicked() {
+                    self.trainer.miss();
+                }
+            }
+        }
+        ui.separator();
+        let trainer = &self.trainer;
+        let prec = self.settings.ao5_prec;
+        egui::Grid::new("trainer_stats").striped(true).show(ui, |ui| {
+            ui.label("Case");
+            ui.label("Attempts");
+            ui.label("Mean");
+            ui.label("Best");
+            ui.label("Recognised");
+            ui.end_row();
+            for case in [CaseSet::Oll, CaseSet::Pll].into_iter().flat_map(trainer::cases) {
+                let Some(stats) = trainer.stats.get(&case.id()) else {
+                    continue;
+                };
+                ui.label(case.id());
+                ui.label(stats.times.len().to_string());
+                ui.label(stats.average().map_or("-".to_string(), |mean| round(mean, prec).to_string()));
+                ui.label(stats.best().map_or("-".to_string(), |best| round(best, prec).to_string()));
+                ui.label(stats.recognition().map_or("-".to_string(), |share| format!("{:.0}%", share * 100.0)));
+                ui.end_row();
+            }
+        });
+    }
+    /// Draws the Algorithm Library: the sets, the case to drill next, and every case with its algorithm and notes.
+    fn algorithm_library(&mut self, ui: &mut egui::Ui) {
+        let library = &mut self.library;
+        let old_set = library.set;
+        ui.horizontal(|ui| {
+            ui.label("Set");
+            egui::ComboBox::from_id_source("library_set")
+                .selected_text(library.current().map_or("".to_string(), |set| set.name.clone()))
+                .show_ui(ui, |ui| {
+                    for (i, set) in library.sets.iter().enumerate() {
+                        ui.selectable_value(&mut library.set, i, set.name.clone());
+                    }
+                });
+            ui.add(egui::TextEdit::singleline(&mut library.new_set).hint_text("New set").desired_width(100.0));
+            if ui.add_enabled(!library.new_set.trim().is_empty(), egui::Button::new("Add Set")).clicked() {
+                library.sets.push(AlgSet {
+                    name: library.new_set.trim().to_string(),
+                    cases: vec![],
+                });
+                library.set = library.sets.len() - 1;
+                library.new_set.clear();
+            }
+            let drilled = library.current().map_or(true, |set| set.cases.iter().any(|case| !case.drills.is_empty()));
+            if ui
+                .add_enabled(!drilled && library.sets.len() > 1, egui::Button::new("Remove Set"))
+                .on_disabled_hover_text("Only sets that were never drilled can be removed")
+                .clicked()
+            {
+                library.sets.remove(library.set);
+                library.set = 0;
+            }
+        });
+        let mut regenerate = library.set != old_set && library.drilling;
+        if self.cube_type() == Cubes::ThreeByThree {
+            let toggled = ui
+                .checkbox(&mut self.library.drilling, "Drill with the timer")
+                .on_hover_text("Scrambles set up the case that is due, and times are kept with the case instead of the session")
+                .changed();
+            if toggled && self.library.drilling {
+                self.trainer.active = false;
+            }
+            regenerate |= toggled;
+        } else {
+            ui.label("Drilling with the timer only works in 3x3 sessions.");
+        }
+        // Without the timer, cases are drilled as they come due
+        let library = &mut self.library;
+        if !library.drilling {
+            library.case = library.current().and_then(|set| set.next(Local::now()));
+            library.pending = None;
+        }
+        let mut graded = false;
+        ui.separator();
+        match (library.current(), library.case) {
+            (Some(set), Some(index)) => {
+                let case = &set.cases[index];
+                ui.label(format!("Next: {}", case.name));
+                egui::CollapsingHeader::new("Algorithm").id_source("library_algorithm").show(ui, |ui| {
+                    ui.label(&case.algorithm);
+                    if !case.notes.is_empty() {
+                        ui.label(&case.notes);
+                    }
+                });
+                if let Some(time) = library.pending {
+                    ui.label(format!("Executed in {}", round(time, self.settings.prec)));
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Pass").clicked() {
+                        library.grade(true);
+                        graded = true;
+                    }
+                    if ui.button("Fail").clicked() {
+                        library.grade(false);
+                        graded = true;
+                    }
+                });
+            }
+            (Some(set), None) => match set.next_due() {
+                Some(due) if set.cases.iter().all(|case| case.due.is_some()) => {
+                    ui.label(format!("Nothing is due until {}.", due.format("%Y-%m-%d %H:%M")));
+                }
+                _ => {
+                    ui.label("Add algorithms to the cases of this set to drill them.");
+                }
+            },
+            (None, _) => {}
+        }
+        if (graded && library.drilling) || regenerate {
+            self.state.scramble = self.make_scramble();
+        }
+        ui.separator();
+        let prec = self.settings.ao5_prec;
+        let now = Local::now();
+        let library = &mut self.library;
+        let Some(set) = library.sets.get_mut(library.set) else {
+            return;
+        };
+        let mut remove = None;
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            egui::Grid::new("library_cases").striped(true).show(ui, |ui| {
+                ui.label("Case");
+                ui.label("Algorithm");
+                ui.label("Notes");
+                ui.label("Drills");
+                ui.label("Passed");
+                ui.label("Mean");
+                ui.label("Due");
+                ui.end_row();
+                for (i, case) in set.cases.iter_mut().enumerate() {
+                    ui.label(&case.name);
+                    ui.add(egui::TextEdit::singleline(&mut case.algorithm).desired_width(220.0));
+                    ui.add(egui::TextEdit::singleline(&mut case.notes).desired_width(150.0));
+                    ui.label(case.drills.len().to_string());
+                    ui.label(case.pass_rate().map_or("-".to_string(), |rate| format!("{:.0}%", rate * 100.0)));
+                    ui.label(case.mean_time().map_or("-".to_string(), |mean| round(mean, prec).to_string()));
+                    ui.label(match case.due {
+                        None => "New".to_string(),
+                        Some(due) if due <= now => "Now".to_string(),
+                        Some(due) => due.format("%Y-%m-%d %H:%M").to_string(),
+                    });
+                    if case.drills.is_empty() && ui.small_button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if let Some(i) = remove {
+            set.cases.remove(i);
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut library.new_case).hint_text("New case").desired_width(100.0));
+            if ui.add_enabled(!library.new_case.trim().is_empty(), egui::Button::new("Add Case")).clicked() {
+                set.cases.push(AlgCase {
+                    name: library.new_case.trim().to_string(),
+                    ..AlgCase::default()
+                });
+                library.new_case.clear();
+            }
+        });
+        // Indices after a removed case shift down, so the case being drilled is picked again
+        if remove.is_some() && self.library.drilling {
+            self.state.scramble = self.make_scramble();
+        }
+    }
+    /// Draws Multi-BLD: the scrambles and countdown of the attempt, and the history of attempts.
+    fn multi_bld(&mut self, ui: &mut egui::Ui) {
+        let multi = &mut self.multi_bld;
+        let idle = multi.start.is_none() && multi.time.is_none();
+        ui.horizontal(|ui| {
+            ui.label("Cubes");
+            ui.add_enabled(idle, egui::DragValue::new(&mut multi.cubes).clamp_range(multi_bld::MIN_CUBES..=100));
+            ui.label(format!("Time limit: {}", multi_bld::format_time(multi_bld::time_limit(multi.cubes))));
+            if ui.add_enabled(idle, egui::Button::new("Generate Scrambles")).clicked() {
+                multi.generate();
+            }
+        });
+        if multi.scrambles.is_empty() {
+            ui.label("Generate scrambles to start an attempt.");
+        } else {
+            // The attempt is stopped at the time limit, like at a competition
+            match multi.remaining() {
+                Some(remaining) if remaining <= 0.0 => multi.stop(),
+                Some(_) => ui.ctx().request_repaint_after(std::time::Duration::from_millis(100)),
+                None => {}
+            }
+            ui.horizontal(|ui| {
+                if let Some(remaining) = multi.remaining() {
+                    ui.heading(multi_bld::format_time(remaining.ceil()));
+                    if ui.button("Stop").clicked() {
+                        multi.stop();
+                    }
+                } else if let Some(time) = multi.time {
+                    ui.label(format!("Time: {}", multi_bld::format_time(time)));
+                    ui.label("Solved");
+                    let attempted = multi.scrambles.len();
+                    ui.add(egui::DragValue::new(&mut multi.solved).clamp_range(0..=attempted).suffix(format!("/{}", attempted)));
+                    if ui.button("Save").clicked() {
+                        multi.save();
+                    }
+                    if ui.button("Discard").clicked() {
+                        multi.time = None;
+                    }
+                } else {
+                    if ui.button("Start").clicked() {
+                        multi.start();
+                    }
+                    if ui.button("Copy Scrambles").clicked() {
+                        let text: Vec<String> = multi.scrambles.iter().enumerate().map(|(i, scramble)| format!("{}. {}", i + 1, scramble)).collect();
+                        ui.output_mut(|o| o.copied_text = text.join("\n"));
+                    }
+                }
+            });
+            egui::ScrollArea::vertical().id_source("multi_bld_scrambles").max_height(200.0).show(ui, |ui| {
+                for (i, scramble) in multi.scrambles.iter().enumerate() {
+                    ui.label(format!("{}. {}", i + 1, scramble));
+                }
+            });
+        }
+        ui.separator();
+        ui.strong("History");
+        if let Some(best) = multi.best() {
+            ui.label(format!("Best: {} ({} points)", best, best.points()));
+        }
+        let mut remove = None;
+        egui::ScrollArea::vertical().id_source("multi_bld_history").max_height(200.0).show(ui, |ui| {
+            egui::Grid::new("multi_bld_attempts").striped(true).show(ui, |ui| {
+                ui.label("Date");
+                ui.label("Result");
+                ui.label("Points");
+                ui.end_row();
+                for (i, attempt) in multi.attempts.iter().enumerate().rev() {
+                    ui.label(attempt.date.format("%Y-%m-%d %H:%M").to_string());
+                    ui.label(attempt.to_string());
+                    ui.label(attempt.points().to_string());
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if let Some(i) = remove {
+            multi.attempts.remove(i);
+        }
+    }
+    /// Draws FMC: the scramble and countdown of the attempt, the solution being written and past attempts.
+    fn fmc(&mut self, ui: &mut egui::Ui) {
+        if let Some(result) = self.fmc_job.as_ref().and_then(|job| job.poll()) {
+            match result {
+                Ok(scramble) => self.fmc.new_attempt(scramble),
+                Err(error) => self.fmc.error = Some(error),
+            }
+            self.fmc_job = None;
+        }
+        let fmc = &mut self.fmc;
+        if self.fmc_job.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Scrambling...");
+            });
+            ui.ctx().request_repaint();
+        } else if fmc.start.is_none() {
+            if ui.button("New Attempt").clicked() {
+                self.fmc_job = Some(Job::spawn(scramble::fmc_scramble));
+            }
+        } else {
+            let remaining = fmc.remaining().unwrap_or(0.0);
+            if remaining > 0.0 {
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+            }
+            ui.horizontal(|ui| {
+                ui.heading(multi_bld::format_time(remaining.ceil()));
+                if remaining <= 0.0 {
+                    ui.label("Time is up, submit your solution");
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(&fmc.scramble);
+                if ui.small_button("Copy").clicked() {
+                    ui.output_mut(|o| o.copied_text = fmc.scramble.clone());
+                }
+            });
+            // Like at a competition, the solution can't be changed once time is up
+            ui.add_enabled(remaining > 0.0, egui::TextEdit::multiline(&mut fmc.solution).hint_text("Solution").desired_rows(3));
+            match notation::parse(&fmc.solution) {
+                Ok(turns) => {
+                    ui.label(format!("{} moves", notation::count(&turns, Metric::Htm)));
+                }
+                Err(error) => {
+                    ui.label(error.to_string());
+                }
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Submit").clicked() {
+                    fmc.submit();
+                }
+                if ui.button("Give Up").clicked() {
+                    fmc.give_up();
+                }
+            });
+        }
+        if let Some(error) = &fmc.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        ui.separator();
+        ui.strong("History");
+        let format = |moves: Option<f64>| moves.map_or("-".to_string(), |moves| format!("{:.2}", moves));
+        ui.label(format!("Mo3: {}", format(fmc.mean_of_3())));
+        ui.label(format!("Best Mo3: {}", format(fmc.best_mean_of_3())));
+        ui.label(format!("Best: {}", fmc.best().map_or("-".to_string(), |moves| moves.to_string())));
+        let mut remove = None;
+        egui::ScrollArea::vertical().id_source("fmc_history").max_height(200.0).show(ui, |ui| {
+            egui::Grid::new("fmc_attempts").striped(true).show(ui, |ui| {
+                ui.label("Date");
+                ui.label("Moves");
+                ui.label("Solution");
+                ui.end_row();
+                for (i, attempt) in fmc.attempts.iter().enumerate().rev() {
+                    ui.label(attempt.date.format("%Y-%m-%d %H:%M").to_string());
+                    ui.label(attempt.to_string());
+                    ui.label(&attempt.solution).on_hover_text(&attempt.scramble);
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if let Some(i) = remove {
+            fmc.attempts.remove(i);
+        }
+    }
+    /// Draws Competition: the limits of the next round, the round being done and the results of past rounds.
+    fn competition(&mut self, ui: &mut egui::Ui) {
+        let event = self.event();
+        let competition = &mut self.competition;
+        let mut new_scramble = false;
+        if let Some(round) = competition.running() {
+            ui.label(format!("{}, {}", round.event, round.format));
+            ui.label(format!("Attempt {} of {}", round.attempts.len() + 1, round.format.attempts()));
+            if let Some(cutoff) = round.cutoff {
+                ui.label(format!("Cutoff: one of the first {} attempts under {}", round.format.cutoff_attempts(), competition::format_result(Some(cutoff))));
+            }
+            ui.label(format!("Time limit: {}", competition::format_result(Some(round.time_limit))));
+            egui::Grid::new("competition_scrambles").striped(true).show(ui, |ui| {
+                for (i, scramble) in round.scrambles.iter().enumerate() {
+                    ui.label(format!("{}", i + 1));
+                    ui.label(round.attempts.get(i).map_or("".to_string(), |attempt| competition::format_result(attempt.result())));
+                    ui.label(scramble);
+                    ui.end_row();
+                }
+            });
+            if ui.button("Abandon Round").clicked() {
+                competition.round = None;
+                new_scramble = true;
+            }
+        } else {
+            competition.set_event(event);
+            ui.label(format!("{}, {}", event, Format::of(event)));
+            ui.horizontal(|ui| {
+                let mut has_cutoff = competition.cutoff.is_some();
+                if ui.checkbox(&mut has_cutoff, "Cutoff").changed() {
+                    competition.cutoff = if has_cutoff { Some(competition.time_limit / 2.0) } else { None };
+                }
+                if let Some(cutoff) = competition.cutoff.as_mut() {
+                    ui.add(egui::DragValue::new(cutoff).clamp_range(1.0..=3600.0).suffix(" s"));
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Time Limit");
+                ui.add(egui::DragValue::new(&mut competition.time_limit).clamp_range(1.0..=3600.0).suffix(" s"));
+            });
+            if let Some(round) = &competition.round {
+                if !round.made_cutoff() {
+                    ui.label("The last round missed the cutoff");
+                }
+            }
+            if ui.button("Start Round").clicked() {
+                competition.start();
+                new_scramble = true;
+            }
+        }
+        ui.separator();
+        ui.strong("Results");
+        let mut remove = None;
+        egui::ScrollArea::vertical().id_source("competition_history").max_height(200.0).show(ui, |ui| {
+            egui::Grid::new("competition_rounds").striped(true).show(ui, |ui| {
+                ui.label("Date");
+                ui.label("Event");
+                for i in 1..=5 {
+                    ui.label(i.to_string());
+                }
+                ui.label("Average");
+                ui.label("Best");
+                ui.end_row();
+                for (i, round) in competition.rounds.iter().enumerate().rev() {
+                    ui.label(round.date.format("%Y-%m-%d %H:%M").to_string());
+                    ui.label(round.event.to_string());
+                    let mut results = round.results();
+                    results.resize(5, "".to_string());
+                    for result in results {
+                        ui.label(result);
+                    }
+                    // Best of 3 rounds have a mean, and rounds that missed the cutoff have neither
+                    ui.label(round.average().map_or("".to_string(), competition::format_result));
+                    ui.label(competition::format_result(round.best()));
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if let Some(i) = remove {
+            competition.rounds.remove(i);
+        }
+        if new_scramble {
+            self.state.scramble = self.make_scramble();
+        }
+    }
+    /// Draws Scramble Sheets: the events, rounds and seed of a sheet, a preview of it and its export.
+    fn scramble_sheets(&mut self, ui: &mut egui::Ui) {
+        let options = &mut self.settings.sheet;
+        ui.label("Events");
+        ui.horizontal_wrapped(|ui| {
+            for event in EVENTS {
+                let mut checked = options.events.contains(&event);
+                if ui.checkbox(&mut checked, event.to_string()).changed() {
+                    // Events are kept in the order of EVENTS, which is the order they're printed in
+                    options.events = EVENTS.into_iter().filter(|other| if *other == event { checked } else { options.events.contains(other) }).collect();
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Rounds");
+            ui.add(egui::DragValue::new(&mut options.rounds).clamp_range(1..=4));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Seed");
+            ui.add(egui::DragValue::new(&mut options.seed));
+            if ui.button("Random").clicked() {
+                options.seed = rand::thread_rng().gen_range(1..100000);
+            }
+        });
+        if let Some(result) = self.sheet_job.as_ref().and_then(|job| job.poll()) {
+            self.sheet = result.ok();
+            self.sheet_job = None;
+        }
+        if self.sheet_job.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Generating...");
+            });
+            ui.ctx().request_repaint();
+        } else if ui.add_enabled(!options.events.is_empty(), egui::Button::new("Generate")).clicked() {
+            let (events, rounds, seed) = (options.events.clone(), options.rounds, options.seed);
+            self.sheet_job = Some(Job::spawn(move || ScrambleSheet::generate(&events, rounds, seed)));
+            self.sheet_status = None;
+        }
+        let Some(sheet) = &self.sheet else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            if ui.button("Copy HTML").clicked() {
+                ui.output_mut(|o| o.copied_text = sheet.html());
+            }
+            if ui.button("Copy SVG").clicked() {
+                ui.output_mut(|o| o.copied_text = sheet.svg());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut options.path);
+            if ui.button("Save").clicked() {
+                let contents = if options.path.ends_with(".svg") { sheet.svg() } else { sheet.html() };
+                self.sheet_status = Some(match sheet::save(&options.path, &contents) {
+                    Ok(()) => format!("Saved to {}", options.path),
+                    Err(error) => error,
+                });
+            }
+        });
+        if let Some(status) = &self.sheet_status {
+            ui.label(status);
+        }
+        ui.separator();
+        ui.strong(format!("Seed {}", sheet.seed));
+        egui::ScrollArea::vertical().id_source("sheet_preview").max_height(400.0).show(ui, |ui| {
+            for round in &sheet.rounds {
+                ui.strong(format!("{} Round {}", round.event, round.round));
+                egui::Grid::new(format!("sheet_{}_{}", round.event, round.round)).striped(true).show(ui, |ui| {
+                    for (label, scramble) in round.labelled() {
+                        ui.label(label);
+                        ui.label(scramble);
+                        ui.horizontal(|ui| {
+                            for (puzzle, moves) in sheet::parts(round.event, scramble) {
+                                net_diagram(ui, puzzle, moves);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+            }
+        });
+    }
+    /// Draws the Step Solver, solving a step of the current scramble on every colour as it changes.
+    fn step_solver(&mut self, ui: &mut egui::Ui) {
+        if self.cube_type() != Cubes::ThreeByThree || self.event().is_relay() {
+            ui.label("The Step Solver only works on 3x3 scrambles.");
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Step");
+            egui::ComboBox::from_id_source("step")
+                .selected_text(self.settings.step.to_string())
+                .show_ui(ui, |ui| {
+                    for step in [Step::Cross, Step::XCross, Step::EOLine, Step::EOCross] {
+                        ui.selectable_value(&mut self.settings.step, step, step.to_string());
+                    }
+                });
+        });
+        let scramble = self.state.scramble.clone();
+        let step = self.settings.step;
+        if let Some(result) = self.steps_job.as_ref().and_then(|job| job.poll()) {
+            if let Some((_, _, solutions)) = self.steps.as_mut() {
+                *solutions = result;
+            }
+            self.steps_job = None;
+        }
+        let current = self.steps.as_ref().map_or(false, |(solved, solved_step, _)| *solved == scramble && *solved_step == step);
+        if !current && self.steps_job.is_none() {
+            // Solving starts by itself, except where making the tables would freeze the app
+            if job::BLOCKS && !steps::tables_ready() {
+                ui.label(TABLES_WARNING);
+                if !ui.button("Generate Tables").clicked() {
+                    return;
+                }
+            }
+            // Remember what is being solved, so the result is matched up with it
+            self.steps = Some((scramble.clone(), step, Ok(vec![])));
+            match notation::parse_moves(&scramble, 3) {
+                Ok(moves) => self.steps_job = Some(Job::spawn(move || steps::solve(&moves, step))),
+                Err(error) => self.steps = Some((scramble, step, Err(error.to_string()))),
+            }
+        }
+        if self.steps_job.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                if steps::tables_ready() {
+                    ui.label("Solving...");
+                } else {
+                    ui.label("Generating tables, this only happens once...");
+                }
+            });
+            ui.ctx().request_repaint();
+            return;
+        }
+        match self.steps.as_ref().map(|(_, _, result)| result) {
+            Some(Ok(colours)) => {
+                for colour in colours {
+                    let title = format!(
+                        "{}: {} moves ({} solutions)",
+                        colour.colour,
+                        colour.length,
+                        colour.solutions.len()
+                    );
+                    egui::CollapsingHeader::new(title).id_source(colour.colour).show(ui, |ui| {
+                        for (rotation, turns) in &colour.solutions {
+                            let solution = format!("{} {}", rotation, notation::turns_string(turns)).trim().to_string();
+                            ui.horizontal(|ui| {
+                                ui.label(&solution);
+                                if ui.small_button("Copy").clicked() {
+                                    ui.output_mut(|o| o.copied_text = solution.clone());
+                                }
+                            });
+                        }
+                    });
+                }
+            }
+            Some(Err(error)) => {
+                ui.label(error);
             }
-            self.state.mo3 = mo3;
+            None => {}
         }
-        if len > 4 {
-            let ao5 = average(&solves, 4, self.state.ao5_prec);
-            if self.state.ao5 > ao5 {
-                self.state.best_ao5 = ao5.clone();
+    }
+    /// Draws the search box and filters above the solve list, with stats of the matching solves.
+    fn solve_filter(&mut self, ui: &mut egui::Ui) {
+        let filter = &mut self.settings.filter;
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            ui.text_edit_singleline(&mut filter.search).on_hover_text("Find solves whose scramble contains this");
+        });
+        egui::CollapsingHeader::new("Filter").show(ui, |ui| {
+            // Fields that don't parse are written in the theme's error colour
+            let field = |ui: &mut egui::Ui, text: &mut String, valid: bool, hint: &str| {
+                let mut edit = egui::TextEdit::singleline(text).hint_text(hint).desired_width(80.0);
+                if !valid {
+                    edit = edit.text_color(ui.visuals().error_fg_color);
+                }
+                ui.add(edit);
+            };
+            egui::Grid::new("solve_filter").show(ui, |ui| {
+                ui.label("Dates");
+                ui.horizontal(|ui| {
+                    let valid = filter::parse_date(&filter.from).is_ok();
+                    field(ui, &mut filter.from, valid, "YYYY-MM-DD");
+                    ui.label("to");
+                    let valid = filter::parse_date(&filter.to).is_ok();
+                    field(ui, &mut filter.to, valid, "YYYY-MM-DD");
+                });
+                ui.end_row();
+                ui.label("Times");
+                ui.horizontal(|ui| {
+                    let valid = filter::parse_time(&filter.min_time).is_ok();
+                    field(ui, &mut filter.min_time, valid, "Seconds");
+                    ui.label("to");
+                    let valid = filter::parse_time(&filter.max_time).is_ok();
+                    field(ui, &mut filter.max_time, valid, "Seconds");
+                });
+                ui.end_row();
+                ui.label("Event");
+                egui::ComboBox::from_id_source("filter_event")
+                    .selected_text(match filter.event {
+                        Some(event) => event.to_string(),
+                        None => "Any".to_string(),
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut filter.event, None, "Any");
+                        for event in EVENTS {
+                            ui.selectable_value(&mut filter.event, Some(event), event.to_string());
+                        }
+                    });
+                ui.end_row();
+                ui.label("Penalty");
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut filter.penalty, Penalty::Any, "Any");
+                    ui.selectable_value(&mut filter.penalty, Penalty::NoPenalty, "OK");
+                    ui.selectable_value(&mut filter.penalty, Penalty::Plus2, "+2");
+                    ui.selectable_value(&mut filter.penalty, Penalty::Dnf, "DNF");
+                });
+                ui.end_row();
+                ui.label("Comment");
+                ui.text_edit_singleline(&mut filter.comment);
+                ui.end_row();
+                ui.label("Tag");
+                ui.text_edit_singleline(&mut filter.tag);
+                ui.end_row();
+            });
+            if ui.button("Clear Filter").clicked() {
+                *filter = SolveFilter::default();
             }
-            self.state.ao5 = ao5;
+        });
+        if self.settings.filter.is_active() {
+            let solves: Vec<SolveStats> = self.filtered_solves(false);
+            let summary = stats::summarize(&solves, self.settings.threshold);
+            let times: Vec<Option<f64>> = solves.iter().map(stats::solve_time).collect();
+            let format = |time: Option<f64>| match time {
+                Some(time) => round(time, self.settings.ao5_prec).to_string(),
+                None => "-".to_string(),
+            };
+            ui.label(format!("Showing {} of {} solves", solves.len(), self.event_solves().len()));
+            ui.label(format!(
+                "Mean: {}  Best: {}  Ao5: {}  Ao12: {}",
+                format(summary.mean),
+                format(summary.best),
+                format(times.get(0..5).and_then(stats::average_of)),
+                format(times.get(0..12).and_then(stats::average_of)),
+            ));
+        }
+    }
+    /// Solves matching the filter in the current session, or every session if `all` is set.
+    fn filtered_solves(&self, all: bool) -> Vec<SolveStats> {
+        self.solves_in(all)
+            .into_iter()
+            .filter(|solve| self.settings.filter.matches(solve))
+            .collect()
+    }
+    /// Best and worst single within the latest average of `size`, for the statistics hover text.
+    fn window_extremes(&self, size: usize) -> String {
+        let solves = self.event_solves();
+        let Some(window) = solves.get(0..size) else {
+            return "".to_string();
+        };
+        let times: Vec<Option<f64>> = window.iter().map(stats::solve_time).collect();
+        match stats::extremes(&times) {
+            Some((best, Some(worst))) => format!(
+                "Best in window: {}\nWorst in window: {}",
+                round(best, self.settings.solves_prec),
+                round(worst, self.settings.solves_prec)
+            ),
+            Some((best, None)) => format!("Best in window: {}\nWorst in window: DNF", round(best, self.settings.solves_prec)),
+            None => "Every solve in window is a DNF".to_string(),
         }
-        if len > 11 {
-            let ao12 = average(&solves, 11, self.state.ao5_prec);
-            if self.state.ao12 > ao12 {
-                self.state.best_ao12 = ao12.clone();
+    }
+    /// Progression of personal bests for an event across every session, oldest first.
+    fn records(&self, event: Event, size: usize) -> Vec<stats::Record> {
+        let mut results = stats::results(&self.state.solves, event, size);
+        for session in self.sessions.values() {
+            if session.name != self.state.name {
+                results.extend(stats::results(&session.solves, event, size));
             }
-            self.state.ao12 = ao12;
         }
-        if len > 24 {
-            let ao25 = average(&solves, 24, self.state.ao5_prec);
-            if self.state.ao25 > ao25 {
-                self.state.best_ao25 = ao25.clone();
+        stats::progression(results)
+    }
+    /// Draws the Personal Bests tool, with current bests and how they improved.
+    fn personal_bests(&mut self, ui: &mut egui::Ui) {
+        let prec = self.settings.ao5_prec;
+        let date = |record: &stats::Record| match DateTime::from_timestamp(record.timestamp, 0) {
+            Some(date) => date.with_timezone(&Local).format("%Y-%m-%d").to_string(),
+            None => "".to_string(),
+        };
+        egui::Grid::new("personal_bests").striped(true).show(ui, |ui| {
+            ui.strong("Event");
+            for size in stats::RECORD_SIZES {
+                ui.strong(stats::record_name(size));
+            }
+            ui.end_row();
+            // Relays of other puzzles are events too, so the session's is shown with the rest
+            let mut events = EVENTS.to_vec();
+            if !events.contains(&self.event()) {
+                events.push(self.event());
+            }
+            for event in events {
+                ui.label(event.to_string());
+                for size in stats::RECORD_SIZES {
+                    match self.records(event, size).last() {
+                        Some(record) => ui.label(round(record.value, prec).to_string()).on_hover_text(format!("Set on {}", date(record))),
+                        None => ui.label("-"),
+                    };
+                }
+                ui.end_row();
+            }
+        });
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} Progression: ", self.event()));
+            for size in stats::RECORD_SIZES {
+                ui.selectable_value(&mut self.settings.pb_size, size, stats::record_name(size));
             }
-            self.state.ao25 = ao25;
+        });
+        let records = self.records(self.event(), self.settings.pb_size);
+        // Each best holds until the next one, so draw them as steps
+        let days = |record: &stats::Record| record.timestamp as f64 / 86400.0;
+        let mut steps: Vec<[f64; 2]> = vec![];
+        for (i, record) in records.iter().enumerate() {
+            if i > 0 {
+                steps.push([days(record), records[i - 1].value]);
+            }
+            steps.push([days(record), record.value]);
         }
-        if len > 49 {
-            let ao50 = average(&solves, 49, self.state.ao5_prec);
-            if self.state.ao50 > ao50 {
-                self.state.best_ao50 = ao50.clone();
+        if let Some(last) = records.last() {
+            steps.push([Local::now().timestamp() as f64 / 86400.0, last.value]);
+        }
+        let points: Vec<[f64; 2]> = records.iter().map(|record| [days(record), record.value]).collect();
+        let format_day = |day: f64| match DateTime::from_timestamp((day * 86400.0) as i64, 0) {
+            Some(date) => date.with_timezone(&Local).format("%Y-%m-%d").to_string(),
+            None => "".to_string(),
+        };
+        egui_plot::Plot::new("PB Progression")
+            .view_aspect(self.settings.plot_aspect_ratio)
+            .x_axis_formatter(move |mark, _, _| format_day(mark.value))
+            .label_formatter(move |_, point| format!("{}\n{:.2}", format_day(point.x), point.y))
+            .show(ui, |plot_ui| {
+                plot_ui.line(egui_plot::Line::new(steps).name(stats::record_name(self.settings.pb_size)));
+                plot_ui.points(egui_plot::Points::new(points).radius(3.0));
+            });
+    }
+    /// Draws the Progress tool, summarising solves by day, week or month.
+    fn progress(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.settings.period, Period::Day, "Day");
+            ui.selectable_value(&mut self.settings.period, Period::Week, "Week");
+            ui.selectable_value(&mut self.settings.period, Period::Month, "Month");
+            ui.checkbox(&mut self.settings.all_sessions, "All Sessions");
+        });
+        let solves = self.solves_in(self.settings.all_sessions);
+        let prec = self.settings.ao5_prec;
+        let format = |time: Option<f64>| match time {
+            Some(time) => round(time, prec).to_string(),
+            None => "-".to_string(),
+        };
+
+        // Calendar heatmap of the last few months, one column per week
+        let mut per_day: HashMap<chrono::NaiveDate, usize> = HashMap::new();
+        for solve in &solves {
+            if let Some(date) = stats::solve_date(solve) {
+                *per_day.entry(date).or_insert(0) += 1;
             }
-            self.state.ao50 = ao50;
         }
-        if len > 99 {
-            let ao100 = average(&solves, 99, self.state.ao5_prec);
-            if self.state.ao100 > ao100 {
-                self.state.best_ao100 = ao100.clone();
+        let most = per_day.values().copied().max().unwrap_or(0).max(1);
+        let today = Local::now().date_naive();
+        let first = stats::period_start(today, Period::Week) - chrono::Days::new(7 * (HEATMAP_WEEKS - 1));
+        ui.label(format!("Practice over the last {} weeks", HEATMAP_WEEKS));
+        egui::Grid::new("heatmap").spacing([2.0, 2.0]).show(ui, |ui| {
+            for weekday in 0..7 {
+                for week in 0..HEATMAP_WEEKS {
+                    let date = first + chrono::Days::new(week * 7 + weekday);
+                    let (rect, response) = ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+                    if date > today {
+                        continue;
+                    }
+                    let count = per_day.get(&date).copied().unwrap_or(0);
+                    let strength = count as f32 / most as f32;
+                    let colour = if count == 0 {
+                        egui::Color32::from_gray(225)
+                    } else {
+                        egui::Color32::from_rgb(
+                            (200.0 - 170.0 * strength) as u8,
+                            (230.0 - 90.0 * strength) as u8,
+                            (200.0 - 170.0 * strength) as u8,
+                        )
+                    };
+                    ui.painter().rect_filled(rect, 2.0, colour);
+                    response.on_hover_text(format!("{}: {} solves", date.format("%Y-%m-%d"), count));
+                }
+                ui.end_row();
+            }
+        });
+
+        ui.separator();
+        let periods = stats::group_by_period(&solves, self.settings.period);
+        egui::scroll_area::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+            egui::Grid::new("progress").striped(true).show(ui, |ui| {
+                ui.strong("Period");
+                ui.strong("Solves");
+                ui.strong("Mean");
+                ui.strong("Best");
+                ui.strong("Best Ao5");
+                ui.end_row();
+                for period in periods.iter().rev() {
+                    ui.label(period.label(self.settings.period));
+                    ui.label(period.count.to_string());
+                    ui.label(format(period.mean));
+                    ui.label(format(period.best_single));
+                    ui.label(format(period.best_ao5));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+    /// Draws the plot chosen in the Plot Times tool.
+    fn plot_times(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.settings.plot_view, PlotView::Times, "Times");
+            ui.selectable_value(&mut self.settings.plot_view, PlotView::Histogram, "Histogram");
+            ui.selectable_value(&mut self.settings.plot_view, PlotView::BoxPlot, "Box Plot");
+        });
+        let plot = egui_plot::Plot::new("Time Distribution")
+            .view_aspect(self.settings.plot_aspect_ratio)
+            .legend(egui_plot::Legend::default());
+        match self.settings.plot_view {
+            PlotView::Times => {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.settings.plot_ao5, "Ao5");
+                    ui.checkbox(&mut self.settings.plot_ao12, "Ao12");
+                    ui.checkbox(&mut self.settings.plot_ao100, "Ao100");
+                });
+                plot.show(ui, |plot_ui| {
+                    plot_ui.line(egui_plot::Line::new(self.state.plottable.clone()).name("Times"));
+                    for (shown, line, name) in [
+                        (self.settings.plot_ao5, &self.state.plot_ao5, "Ao5"),
+                        (self.settings.plot_ao12, &self.state.plot_ao12, "Ao12"),
+                        (self.settings.plot_ao100, &self.state.plot_ao100, "Ao100"),
+                    ] {
+                        if shown {
+                            plot_ui.line(egui_plot::Line::new(line.clone()).name(name));
+                        }
+                    }
+                    if !self.state.plot_dnfs.is_empty() {
+                        plot_ui.points(
+                            egui_plot::Points::new(self.state.plot_dnfs.clone())
+                                .shape(egui_plot::MarkerShape::Cross)
+                                .color(egui::Color32::RED)
+                                .radius(4.0)
+                                .name("DNF"),
+                        );
+                    }
+                });
+            }
+            PlotView::Histogram => {
+                ui.horizontal(|ui| {
+                    ui.label("Bucket Width: ");
+                    ui.add(egui::Slider::new(&mut self.settings.bucket_width, 0.1..=10.0).suffix("s"));
+                });
+                let solves = self.event_solves();
+                let times: Vec<f64> = solves.iter().filter_map(stats::solve_time).collect();
+                let width = self.settings.bucket_width;
+                let bars: Vec<egui_plot::Bar> = stats::histogram(&times, width)
+                    .into_iter()
+                    .map(|(start, count)| egui_plot::Bar::new(start + width / 2.0, count as f64).width(width))
+                    .collect();
+                let dnfs = solves.len() - times.len();
+                plot.show(ui, |plot_ui| {
+                    plot_ui.bar_chart(egui_plot::BarChart::new(bars).name("Solves"));
+                });
+                if dnfs > 0 {
+                    ui.label(format!("{} DNFs not shown", dnfs));
+                }
+            }
+            PlotView::BoxPlot => {
+                ui.horizontal(|ui| {
+                    ui.label("Group By: ");
+                    ui.selectable_value(&mut self.settings.box_group, BoxGroup::Session, "Session");
+                    ui.selectable_value(&mut self.settings.box_group, BoxGroup::Day, "Day");
+                });
+                let event = self.event();
+                let groups = match self.settings.box_group {
+                    BoxGroup::Session => {
+                        let mut sessions: Vec<&State> = self.sessions.values().filter(|session| session.name != self.state.name).collect();
+                        sessions.push(&self.state);
+                        sessions.sort_by(|a, b| a.name.cmp(&b.name));
+                        sessions
+                            .into_iter()
+                            .map(|session| (session.name.clone(), session.solves.iter().filter(|solve| solve.event() == event).filter_map(stats::solve_time).collect::<Vec<f64>>()))
+                            .collect::<Vec<_>>()
+                    }
+                    BoxGroup::Day => {
+                        let mut days: Vec<(String, Vec<f64>)> = vec![];
+                        for solve in self.event_solves().iter().rev() {
+                            let (Some(day), Some(time)) = (stats::solve_date(solve), stats::solve_time(solve)) else {
+                                continue;
+                            };
+                            let day = day.format("%Y-%m-%d").to_string();
+                            match days.last_mut() {
+                                Some((last, times)) if *last == day => times.push(time),
+                                _ => days.push((day, vec![time])),
+                            }
+                        }
+                        days
+                    }
+                };
+                let boxes: Vec<egui_plot::BoxElem> = groups
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, (name, times))| {
+                        let spread = stats::spread(times)?;
+                        Some(
+                            egui_plot::BoxElem::new(
+                                i as f64,
+                                egui_plot::BoxSpread::new(spread.lower_whisker, spread.q1, spread.median, spread.q3, spread.upper_whisker),
+                            )
+                            .name(name),
+                        )
+                    })
+                    .collect();
+                plot.show(ui, |plot_ui| {
+                    plot_ui.box_plot(egui_plot::BoxPlot::new(boxes).name("Times"));
+                });
             }
-            self.state.ao100 = ao100;
         }
-        if len > 499 {
-            let ao500 = average(&solves, 499, self.state.ao5_prec);
-            if self.state.ao500 > ao500 {
-                self.state.best_ao500 = ao500.clone();
+    }
+    pub fn make_scramble(&mut self) -> String {
+        // A scramble still being made is replaced by this one
+        self.scramble_job = None;
+        // A case is only being trained while the scramble made for it is
+        self.trainer.case = None;
+        self.library.case = None;
+        self.state.relay.clear();
+        // A competition round hands out its own scrambles, in order
+        if let Some(scramble) = self.competition.running().and_then(|round| round.scramble()) {
+            return scramble.clone();
+        }
+        if self.event().is_relay() {
+            self.state.relay = relay::scrambles(self.event(), &mut rand::thread_rng());
+            return relay::scramble_text(&self.state.relay);
+        }
+        // The trainer sets up one of its cases instead, when it has any selected
+        if self.trainer.active && self.cube_type() == Cubes::ThreeByThree {
+            if let Some(setup) = self.trainer.next() {
+                return setup;
             }
-            self.state.ao500 = ao500;
         }
-        if len > 999 {
-            let ao1000 = average(&solves, 999, self.state.ao5_prec);
-            if self.state.ao1000 > ao1000 {
-                self.state.best_ao1000 = ao1000.clone();
+        if self.library.drilling && self.cube_type() == Cubes::ThreeByThree {
+            if let Some(setup) = self.library.next() {
+                return setup;
             }
-            self.state.ao1000 = ao1000;
         }
-        if len > 1999 {
-            let ao2000 = average(&solves, 1999, self.state.ao5_prec);
-            if self.state.ao2000 > ao2000 {
-                self.state.best_ao2000 = ao2000.clone();
+        // Subset scrambles are solved for, so they're made in the background and shown once ready
+        if self.event().has_subsets() && self.state.subset != Subset::Full {
+            let scrambler = Scrambler::from(self.event()).subset(self.state.subset);
+            self.scramble_job = Some(Job::spawn(move || scrambler.scramble()));
+            return "".to_string();
+        }
+        Scrambler::from(self.event()).scramble()
+    }
+    /// The event of the current session, falling back to the default in settings.
+    pub fn event(&self) -> Event {
+        self.state.event.unwrap_or(self.settings.event)
+    }
+    /// The puzzle of the current session's event.
+    pub fn cube_type(&self) -> Cubes {
+        self.event().puzzle
+    }
+    /// Whether the current session uses inspection, falling back to the default in settings.
+    ///
+    /// Events without inspection, like blindfolded ones, never do.
+    ///
+    /// Competition rounds always do, unless their event has none.
+    pub fn inspection(&self) -> bool {
+        if let Some(round) = self.competition.running() {
+            return round.event.inspection();
+        }
+        self.event().inspection() && self.state.inspection.unwrap_or(self.settings.inspection)
+    }
+    /// Records an attempt of the competition round being done, in place of a solve of the session.
+    fn finish_round_attempt(&mut self, rawtime: f64) {
+        let (plus2, dnf) = inspection_penalty(self.state.inspection_time);
+        self.competition.record(if plus2 { rawtime + 2.0 } else { rawtime }, plus2, dnf);
+        if let Some(attempt) = self.competition.round.as_ref().and_then(|round| round.attempts.last()) {
+            self.state.time = competition::format_result(attempt.result());
+        }
+        self.state.memo = None;
+        self.state.splits.clear();
+        self.state.scramble = self.make_scramble();
+    }
+    /// Solves of the current session done in its event, newest first, which its statistics are taken from.
+    fn event_solves(&self) -> Vec<SolveStats> {
+        let event = self.event();
+        self.state.solves.iter().filter(|solve| solve.event() == event).cloned().collect()
+    }
+    /// Whether the timer or inspection is running, in which case only the time is shown.
+    pub fn timing(&self) -> bool {
+        self.state.timeron || self.state.inspecting
+    }
+    pub fn refresh_averages(&mut self) {
+        let times = stats::chronological_times(&self.event_solves());
+        let prec = self.settings.ao5_prec;
+        // Averages without enough solves for them are left empty, which hides them
+        let latest = |size: usize| match times.len().checked_sub(size) {
+            Some(start) => format_average(stats::average_of(&times[start..]), prec),
+            None => "".to_string(),
+        };
+        // Bests are recomputed over the whole session, so that they follow deleted solves
+        let best = |size: usize| match times.len() >= size {
+            true => format_average(stats::best_average(&times, size), prec),
+            false => "".to_string(),
+        };
+        for (size, average, best_average) in [
+            (3, &mut self.state.mo3, &mut self.state.best_mo3),
+            (5, &mut self.state.ao5, &mut self.state.best_ao5),
+            (12, &mut self.state.ao12, &mut self.state.best_ao12),
+            (25, &mut self.state.ao25, &mut self.state.best_ao25),
+            (50, &mut self.state.ao50, &mut self.state.best_ao50),
+            (100, &mut self.state.ao100, &mut self.state.best_ao100),
+            (500, &mut self.state.ao500, &mut self.state.best_ao500),
+            (1000, &mut self.state.ao1000, &mut self.state.best_ao1000),
+            (2000, &mut self.state.ao2000, &mut self.state.best_ao2000),
+            (5000, &mut self.state.ao5000, &mut self.state.best_ao5000),
+        ] {
+            *average = latest(size);
+            *best_average = best(size);
+        }
+    }
+    /// Applies an operation to the solves of the current session, so that it can be undone.
+    fn perform(&mut self, operation: Operation) {
+        if operation.destructive() {
+            self.toast = Some((operation.describe(), Local::now()));
+        }
+        self.state.history.perform(operation, &mut self.state.solves);
+        self.refresh_solves();
+    }
+    /// Makes `session` the current session, keeping the current one with the others.
+    fn switch_session(&mut self, session: State) {
+        let old = std::mem::replace(&mut self.state, session);
+        self.sessions.insert(old.name.clone(), old);
+        // The toast undoes operations in the history of the session it was shown for
+        self.toast = None;
+    }
+    pub fn undo(&mut self) {
+        if let Some(operation) = self.state.history.undo(&mut self.state.solves) {
+            self.toast = Some((format!("Undid: {}", operation.describe()), Local::now()));
+            self.refresh_solves();
+        }
+    }
+    pub fn redo(&mut self) {
+        if let Some(operation) = self.state.history.redo(&mut self.state.solves) {
+            self.toast = Some((format!("Redid: {}", operation.describe()), Local::now()));
+            self.refresh_solves();
+        }
+    }
+    /// Sets the penalty of a solve, adding or removing the 2 seconds of a +2.
+    fn set_penalty(&mut self, index: usize, plus2: bool, dnf: bool) {
+        let before = self.state.solves[index].clone();
+        let mut time: f64 = before.time.parse().unwrap();
+        if before.plus2 {
+            time -= 2.0;
+        }
+        if plus2 {
+            time += 2.0;
+        }
+        let after = SolveStats {
+            time: time.to_string(),
+            plus2,
+            dnf,
+            ..before.clone()
+        };
+        if after != before {
+            self.perform(Operation::Edit { index, before, after });
+        }
+    }
+    /// Deletes the latest solve, showing the time of the one before it.
+    fn delete_latest(&mut self) {
+        self.perform(Operation::Delete {
+            index: 0,
+            solve: self.state.solves[0].clone(),
+        });
+        if self.state.solves.is_empty() {
+            self.reset_time();
+        } else {
+            self.state.time = round(self.state.solves[0].time.parse().unwrap(), self.settings.prec).to_string();
+        }
+        self.state.show_solve = false;
+    }
+    /// Does what a key is bound to in the keybindings.
+    fn run_action(&mut self, action: Action) {
+        match action {
+            Action::Ok | Action::PlusTwo | Action::Dnf | Action::Delete if self.state.solves.is_empty() => {}
+            Action::Ok => self.set_penalty(0, false, false),
+            Action::PlusTwo => self.set_penalty(0, true, false),
+            Action::Dnf => self.set_penalty(0, false, true),
+            Action::Delete => self.delete_latest(),
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::NewScramble => self.state.scramble = self.make_scramble(),
+            Action::ToggleSettings => {
+                // Closing the settings closes the import window opened from them, like the View toggle does
+                if self.settings.settings_open {
+                    self.scratch.importing = false;
+                    self.scratch.imported_fail = "".into();
+                    self.scratch.imported_data = "".into();
+                }
+                self.settings.settings_open = !self.settings.settings_open;
             }
-            self.state.ao2000 = ao2000;
+            Action::ToggleStatistics => self.settings.stats_open = !self.settings.stats_open,
+            Action::ToggleTools => self.settings.show_tools = !self.settings.show_tools,
+            Action::ToggleLeftBar => self.settings.show_left_bar = !self.settings.show_left_bar,
         }
-        if len > 4999 {
-            let ao5000 = average(&solves, 4999, self.state.ao5_prec);
-            if self.state.ao5000 > ao5000 {
-                self.state.best_ao5000 = ao5000.clone();
+    }
+    /// Draws the keybinding settings, where the next key pressed after clicking Add is bound to an action.
+    fn keybindings(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("keybindings").striped(true).show(ui, |ui| {
+            for action in ACTIONS {
+                ui.label(action.to_string());
+                if self.rebinding == Some(action) {
+                    ui.label("Press a key, or Escape to cancel");
+                } else {
+                    ui.label(self.settings.keys.describe(action));
+                }
+                if ui.small_button("Add").clicked() {
+                    self.rebinding = Some(action);
+                }
+                if ui.small_button("Clear").clicked() {
+                    self.settings.keys.set(action, vec![]);
+                }
+                ui.end_row();
             }
-            self.state.ao5000 = ao5000;
+        });
+        ui.label("Space and Escape start and cancel the timer, so they can't be bound.");
+        if ui.button("Reset Keybindings").clicked() {
+            self.settings.keys.reset();
+            self.rebinding = None;
+        }
+    }
+    /// Recomputes everything derived from the solves after they have been changed.
+    fn refresh_solves(&mut self) {
+        let solves = self.event_solves();
+        if solves.is_empty() {
+            self.state.mean.clear();
+        } else {
+            self.state.mean = mean(&solves, solves.len() - 1, self.settings.ao5_prec);
+        }
+        if !self.state.solves.is_empty() {
+            self.state.scramble_text = copy_text(&self.state.solves[0], self.settings.solves_prec);
+        } else {
+            self.state.show_solve = false;
+        }
+        if self.state.solve_index < self.state.solves.len() {
+            self.state.solve_info_copy = copy_text(&self.state.solves[self.state.solve_index], self.settings.solves_prec);
+        } else {
+            self.state.solve_info = false;
         }
+        self.refresh_averages();
+        self.redraw_solves();
+        self.calculate_plottable();
     }
     fn redraw_solves(&mut self) {
         self.state.fmt_solves = vec![];
         for solve in &self.state.solves {
-            if solve.dnf == true {
+            if solve.dnf {
                 self.state.fmt_solves.push("DNF".to_string());
             } else {
                 self.state.fmt_solves.push(
-                    round(solve.time.parse::<f64>().unwrap(), self.state.solves_prec).to_string(),
+                    round(solve.time.parse::<f64>().unwrap(), self.settings.solves_prec).to_string(),
                 );
             }
         }
@@ -546,18 +2128,20 @@ impl eframe::App for Cubism {
         true
     }
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
+        if self.load_error.is_none() {
+            eframe::set_value(storage, eframe::APP_KEY, self);
+        }
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        if self.started == false {
-            if is_wasm() == false {
+        if !self.started {
+            if !is_wasm() {
                 #[cfg(not(target_arch = "wasm32"))]
                 rpc();
             }
             self.started = true;
         }
-        if self.set_font == false {
+        if !self.set_font {
             let mut definitions = egui::FontDefinitions::default();
             definitions.font_data.insert("font".to_owned(), egui::FontData::from_static(include_bytes!("../assets/font.ttf")));
             definitions.families.get_mut(&egui::FontFamily::Proportional).unwrap()
@@ -567,67 +2151,67 @@ impl eframe::App for Cubism {
         }
         ctx.set_visuals(egui::Visuals {
             override_text_color: Some(egui::Color32::from_rgb(
-                self.state.text[0],
-                self.state.text[1],
-                self.state.text[2],
+                self.settings.text[0],
+                self.settings.text[1],
+                self.settings.text[2],
             )),
             panel_fill: egui::Color32::from_rgb(
-                self.state.background[0],
-                self.state.background[1],
-                self.state.background[2],
+                self.settings.background[0],
+                self.settings.background[1],
+                self.settings.background[2],
             ),
             window_fill: egui::Color32::from_rgb(
-                self.state.window[0],
-                self.state.window[1],
-                self.state.window[2],
+                self.settings.window[0],
+                self.settings.window[1],
+                self.settings.window[2],
             ),
             extreme_bg_color: egui::Color32::from_rgb(
-                self.state.window[0],
-                self.state.window[1],
-                self.state.window[2],
+                self.settings.window[0],
+                self.settings.window[1],
+                self.settings.window[2],
             ),
             widgets: egui::style::Widgets {
                 inactive: egui::style::WidgetVisuals {
                     weak_bg_fill: egui::Color32::from_rgb(
-                        self.state.button[0],
-                        self.state.button[1],
-                        self.state.button[2],
+                        self.settings.button[0],
+                        self.settings.button[1],
+                        self.settings.button[2],
                     ),
                     bg_stroke: egui::Stroke {
-                        width: self.state.outline_w,
+                        width: self.settings.outline_w,
                         color: egui::Color32::from_rgb(
-                            self.state.outline[0],
-                            self.state.outline[1],
-                            self.state.outline[2],
+                            self.settings.outline[0],
+                            self.settings.outline[1],
+                            self.settings.outline[2],
                         ),
                     },
                     bg_fill: egui::Color32::from_rgb(
-                        self.state.widget[0],
-                        self.state.widget[1],
-                        self.state.widget[2],
+                        self.settings.widget[0],
+                        self.settings.widget[1],
+                        self.settings.widget[2],
                     ),
                     ..egui::Visuals::light().widgets.inactive
                 },
                 open: egui::style::WidgetVisuals {
                     weak_bg_fill: egui::Color32::from_rgb(
-                        self.state.titlebar[0],
-                        self.state.titlebar[1],
-                        self.state.titlebar[2],
+                        self.settings.titlebar[0],
+                        self.settings.titlebar[1],
+                        self.settings.titlebar[2],
                     ),
                     ..egui::Visuals::light().widgets.open
                 },
                 hovered: egui::style::WidgetVisuals {
                     weak_bg_fill: egui::Color32::from_rgb(
-                        self.state.button[0],
-                        self.state.button[1],
-                        self.state.button[2],
+                        self.settings.button[0],
+                        self.settings.button[1],
+                        self.settings.button[2],
                     ),
                     bg_stroke: egui::Stroke {
-                        width: self.state.outline_w,
+                        width: self.settings.outline_w,
                         color: egui::Color32::from_rgb(
-                            self.state.outline[0],
-                            self.state.outline[1],
-                            self.state.outline[2],
+                            self.settings.outline[0],
+                            self.settings.outline[1],
+                            self.settings.outline[2],
                         ),
                     },
                     ..egui::Visuals::light().widgets.hovered
@@ -636,351 +2220,409 @@ impl eframe::App for Cubism {
             },
             ..egui::Visuals::light()
         });
-        if self.state.timeron == false {
-            if self.state.show_scramble == true {
-                if self.state.scramble.as_str() == "" {
-                    self.state.scramble = self.make_scramble();
-                }
-                egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-                    ui.with_layout(
-                        egui::Layout::centered_and_justified(egui::Direction::LeftToRight),
-                        |ui| {
-                            ui.heading(format!("{}", self.state.scramble));
-                        },
-                    );
-                });
+        if self.event() != self.state.event_old {
+            self.state.scramble = self.make_scramble();
+            self.state.event_old = self.event();
+            // Statistics only cover the solves of the session's event
+            self.refresh_solves();
+        }
+        if let Some(result) = self.scramble_job.as_ref().and_then(|job| job.poll()) {
+            self.scramble_job = None;
+            // A subset that couldn't be scrambled gets an ordinary scramble instead
+            self.state.scramble = result.unwrap_or_else(|_| Scrambler::from(self.event()).scramble());
+        }
+        if self.scramble_job.is_some() {
+            ctx.request_repaint();
+        }
+        if !self.timing() && self.settings.show_scramble {
+            if self.state.scramble.as_str() == "" && self.scramble_job.is_none() {
+                self.state.scramble = self.make_scramble();
             }
+            egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+                ui.with_layout(
+                    egui::Layout::centered_and_justified(egui::Direction::LeftToRight),
+                    |ui| {
+                        if self.scramble_job.is_some() {
+                            ui.spinner();
+                        } else {
+                            ui.heading(&self.state.scramble);
+                        }
+                    },
+                );
+            });
         }
 
-        if self.state.timeron == false {
-            if self.state.show_footer == true {
-                egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
-                    ui.with_layout(
-                        egui::Layout::centered_and_justified(egui::Direction::LeftToRight),
-                        |ui| {
-                            ui.label(format!("{}", self.state.footer));
-                        },
-                    );
-                });
-            }
+        if !self.timing() && self.settings.show_footer {
+            egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
+                ui.with_layout(
+                    egui::Layout::centered_and_justified(egui::Direction::LeftToRight),
+                    |ui| {
+                        ui.label(&self.settings.footer);
+                    },
+                );
+            });
         }
 
-        if self.state.timeron == false {
-            if self.state.show_left_bar == true {
-                egui::SidePanel::left("left_panel").show(ctx, |ui| {
-                    ui.heading("Sesssions");
-                    ui.horizontal(|ui| {
-                        ui.label("Session");
-                        egui::ComboBox::from_label("")
-                            .selected_text(self.state.name.clone())
-                            .show_ui(ui, |ui| {
-                                for (_key, value) in self.sessions.clone() {
-                                    if value.name == self.state.name {
-                                    } else {
-                                        if ui.selectable_label(false, value.name.clone()).clicked() {
-                                            // Transfering state
-                                            self.sessions.remove(&self.state.name.clone());
-                                            self.sessions.insert(self.state.name.clone(), self.state.clone());
-                                            self.state = value.clone();
-                                        }
-                                    }
+        if !self.timing() && self.settings.show_left_bar {
+            egui::SidePanel::left("left_panel").show(ctx, |ui| {
+                ui.heading("Sesssions");
+                ui.horizontal(|ui| {
+                    ui.label("Session");
+                    egui::ComboBox::from_label("")
+                        .selected_text(self.state.name.clone())
+                        .show_ui(ui, |ui| {
+                            for (_key, value) in self.sessions.clone() {
+                                if value.name != self.state.name && ui.selectable_label(false, value.name.clone()).clicked() {
+                                    self.switch_session(value.clone());
                                 }
-                            });
-                    });
+                            }
+                        });
+                });
 
-                    ui.horizontal(|ui| {
-                        ui.label("Session Name");
-                        ui.text_edit_singleline(&mut self.state.name);
-                    });
+                ui.horizontal(|ui| {
+                    ui.label("Session Name");
+                    ui.text_edit_singleline(&mut self.state.name);
+                });
 
-                    if ui.button("New Session").clicked() {
-                        let new_session_id = rand::thread_rng().gen_range(1..100000).to_string();
-                        let new_state = State {
-                            name: new_session_id,
-                            ..State::default()
-                        };
-                        if let Some(_data) = self.sessions.get(&self.state.name) {
-                            self.sessions.remove(&self.state.name);
-                            self.sessions.insert(self.state.name.clone(), self.state.clone());
-                        } else {
-                            self.sessions.insert(self.state.name.clone(), self.state.clone());
-                        }
-                        // Making a new session ig
-                        self.state = new_state;
-                    }
-                    ui.separator();
-                    ui.heading("View");
-                    ui.horizontal(|ui| {
-                        ui.label("Open Settings");
-                        if ui.radio(self.state.settings_open, "").clicked() {
-                            if self.state.settings_open == true {
-                                self.state.settings_open = false;
-                                self.state.importing = false;
-                                self.state.imported_fail = "".into();
-                                self.state.imported_data = "".into();
-                            } else {
-                                self.state.settings_open = true;
+                if ui.button("New Session").clicked() {
+                    let new_session_id = rand::thread_rng().gen_range(1..100000).to_string();
+                    let new_state = State {
+                        name: new_session_id,
+                        ..State::default()
+                    };
+                    self.switch_session(new_state);
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Session Event");
+                    let default_event = format!("Default ({})", self.settings.event);
+                    egui::ComboBox::from_id_source("session_event")
+                        .selected_text(match self.state.event {
+                            Some(event) => event.to_string(),
+                            None => default_event.clone(),
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.state.event, None, default_event);
+                            for event in EVENTS {
+                                ui.selectable_value(&mut self.state.event, Some(event), event.to_string());
                             }
-                        }
-                    });
+                        });
+                });
+                if self.event().is_relay() {
                     ui.horizontal(|ui| {
-                        ui.label("Open Statistics: ");
-                        if ui.radio(self.state.stats_open, "").clicked() {
-                            if self.state.stats_open == true {
-                                self.state.stats_open = false;
-                            } else {
-                                self.state.stats_open = true;
+                        ui.label("Relay Puzzles");
+                        let mut puzzles = self.event().puzzles();
+                        for puzzle in RELAY_PUZZLES {
+                            let mut selected = puzzles.contains(&puzzle);
+                            if ui.checkbox(&mut selected, puzzle.to_string()).changed() {
+                                if selected {
+                                    puzzles.push(puzzle);
+                                } else {
+                                    puzzles.retain(|other| *other != puzzle);
+                                }
+                                // A relay needs at least two puzzles, so the last two can't be unticked
+                                if let Some(relay) = Event::relay(&puzzles) {
+                                    self.state.event = Some(relay);
+                                }
                             }
                         }
                     });
+                    ui.checkbox(&mut self.settings.relay_splits, "Record Splits")
+                        .on_hover_text("Press Space as each puzzle is finished to time it separately");
+                }
+                if self.event().has_subsets() {
                     ui.horizontal(|ui| {
-                        ui.label("Open Solve Stats: ");
-                        if ui.radio(self.state.show_solve_info, "").clicked() {
-                            if self.state.show_solve_info == true {
-                                self.state.show_solve_info = false;
-                            } else {
-                                self.state.show_solve_info = true;
-                            }
+                        ui.label("Session Scramble");
+                        let old = self.state.subset;
+                        egui::ComboBox::from_id_source("session_subset")
+                            .selected_text(self.state.subset.to_string())
+                            .show_ui(ui, |ui| {
+                                for subset in Subset::all() {
+                                    let selected = std::mem::discriminant(&self.state.subset) == std::mem::discriminant(&subset);
+                                    if ui.selectable_label(selected, subset.to_string()).clicked() && !selected {
+                                        self.state.subset = subset;
+                                    }
+                                }
+                            });
+                        if let Subset::EasyCross(moves) = &mut self.state.subset {
+                            ui.add(egui::DragValue::new(moves).clamp_range(0..=8).suffix(" moves"))
+                                .on_hover_text("Most moves the cross may take");
                         }
-                    });
-                    ui.horizontal(|ui| {
-                        ui.label("Open Tools: ");
-                        if ui.radio(self.state.show_tools, "").clicked() {
-                            if self.state.show_tools == true {
-                                self.state.show_tools = false;
-                            } else {
-                                self.state.show_tools = true;
-                            }
+                        if self.state.subset != old {
+                            self.state.scramble = self.make_scramble();
                         }
                     });
-                    ui.horizontal(|ui| {
-                        ui.label("Show Scramble: ");
-                        if ui.radio(self.state.show_scramble, "").clicked() {
-                            if self.state.show_scramble == true {
-                                self.state.show_scramble = false;
-                            } else {
-                                self.state.show_scramble = true;
-                            }
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Session Inspection");
+                    let default_inspection = format!("Default ({})", if self.settings.inspection { "On" } else { "Off" });
+                    egui::ComboBox::from_id_source("session_inspection")
+                        .selected_text(match self.state.inspection {
+                            Some(true) => "On".to_string(),
+                            Some(false) => "Off".to_string(),
+                            None => default_inspection.clone(),
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.state.inspection, None, default_inspection);
+                            ui.selectable_value(&mut self.state.inspection, Some(true), "On");
+                            ui.selectable_value(&mut self.state.inspection, Some(false), "Off");
+                        });
+                });
+                ui.separator();
+                ui.heading("View");
+                ui.horizontal(|ui| {
+                    ui.label("Open Settings");
+                    if ui.radio(self.settings.settings_open, "").clicked() {
+                        if self.settings.settings_open {
+                            self.settings.settings_open = false;
+                            self.scratch.importing = false;
+                            self.scratch.imported_fail = "".into();
+                            self.scratch.imported_data = "".into();
+                        } else {
+                            self.settings.settings_open = true;
                         }
-                    });
-                    ui.horizontal(|ui| {
-                        ui.label("Show Footer: ");
-                        if ui.radio(self.state.show_footer, "").clicked() {
-                            if self.state.show_footer == true {
-                                self.state.show_footer = false;
-                            } else {
-                                self.state.show_footer = true;
-                            }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Open Statistics: ");
+                    if ui.radio(self.settings.stats_open, "").clicked() {
+                        self.settings.stats_open = !self.settings.stats_open;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Open Solve Stats: ");
+                    if ui.radio(self.settings.show_solve_info, "").clicked() {
+                        self.settings.show_solve_info = !self.settings.show_solve_info;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Open Tools: ");
+                    if ui.radio(self.settings.show_tools, "").clicked() {
+                        self.settings.show_tools = !self.settings.show_tools;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Show Scramble: ");
+                    if ui.radio(self.settings.show_scramble, "").clicked() {
+                        self.settings.show_scramble = !self.settings.show_scramble;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Show Footer: ");
+                    if ui.radio(self.settings.show_footer, "").clicked() {
+                        self.settings.show_footer = !self.settings.show_footer;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Show Left Bar: ");
+                    if ui.radio(self.settings.show_left_bar, "").clicked() {
+                        self.settings.show_left_bar = !self.settings.show_left_bar;
+                    }
+                });
+
+                ui.separator();
+                ui.heading("Solves");
+                self.solve_filter(ui);
+                egui::scroll_area::ScrollArea::vertical().show(ui, |ui| {
+                    for i in 0..self.state.solves.len() {
+                        if !self.settings.filter.matches(&self.state.solves[i]) {
+                            continue;
                         }
-                    });
-                    ui.horizontal(|ui| {
-                        ui.label("Show Left Bar: ");
-                        if ui.radio(self.state.show_left_bar, "").clicked() {
-                            if self.state.show_left_bar == true {
-                                self.state.show_left_bar = false;
-                            } else {
-                                self.state.show_left_bar = true;
-                            }
+                        let text: String;
+                        if self.state.solves[i].dnf {
+                            text = "Solve: DNF".to_string();
+                        } else if self.state.solves[i].comment.as_str() != "" {
+                            text = format!(
+                                "*Solve: {}",
+                                round(
+                                    self.state.solves[i].time.parse().unwrap(),
+                                    self.settings.solves_prec
+                                )
+                            )
+                        } else {
+                            text = format!(
+                                "Solve: {}",
+                                round(
+                                    self.state.solves[i].time.parse().unwrap(),
+                                    self.settings.solves_prec
+                                )
+                            );
                         }
-                    });
-
-                    ui.separator();
-                    ui.heading("Solves");
-                    egui::scroll_area::ScrollArea::vertical().show(ui, |ui| {
-                        for i in 0..self.state.solves.len() {
-                            let text: String;
-                            if self.state.solves[i].dnf == true {
-                                text = "Solve: DNF".to_string();
-                            } else {
-                                if self.state.solves[i].comment.as_str() != "" {
-                                    text = format!(
-                                        "*Solve: {}",
+                        ui.horizontal(|ui| {
+                            if ui.button(text).clicked() {
+                                self.state.solve_info = true;
+                                self.state.solve_index = i;
+                                if self.state.solves[i].plus2 {
+                                    self.state.solve_info_copy = format!(
+                                        "{}+2 @ {} {}",
                                         round(
                                             self.state.solves[i].time.parse().unwrap(),
-                                            self.state.solves_prec
-                                        )
-                                    )
+                                            self.settings.solves_prec
+                                        ),
+                                        self.state.solves[i].scramble,
+                                        self.state.solves[i].comment,
+                                    );
+                                } else if self.state.solves[i].dnf {
+                                    self.state.solve_info_copy = format!(
+                                        "DNF [{}] @ {} {}",
+                                        round(
+                                            self.state.solves[i].time.parse().unwrap(),
+                                            self.settings.solves_prec
+                                        ),
+                                        self.state.solves[i].scramble,
+                                        self.state.solves[i].comment
+                                    );
                                 } else {
-                                    text = format!(
-                                        "Solve: {}",
+                                    self.state.solve_info_copy = format!(
+                                        "{}+2 @ {} {}",
                                         round(
                                             self.state.solves[i].time.parse().unwrap(),
-                                            self.state.solves_prec
-                                        )
+                                            self.settings.solves_prec
+                                        ),
+                                        self.state.solves[i].scramble,
+                                        self.state.solves[i].comment,
                                     );
                                 }
                             }
-                            ui.horizontal(|ui| {
-                                if ui.button(text).clicked() == true {
-                                    self.state.solve_info = true;
-                                    self.state.solve_index = i;
-                                    if self.state.solves[i].plus2 == true {
-                                        self.state.solve_info_copy = format!(
-                                            "{}+2 @ {} {}",
-                                            round(
-                                                self.state.solves[i].time.parse().unwrap(),
-                                                self.state.solves_prec
-                                            ),
-                                            self.state.solves[i].scramble,
-                                            self.state.solves[i].comment,
-                                        );
-                                    } else {
-                                        if self.state.solves[i].dnf == true {
-                                            self.state.solve_info_copy = format!(
-                                                "DNF [{}] @ {} {}",
-                                                round(
-                                                    self.state.solves[i].time.parse().unwrap(),
-                                                    self.state.solves_prec
-                                                ),
-                                                self.state.solves[i].scramble,
-                                                self.state.solves[i].comment
-                                            );
-                                        } else {
-                                            self.state.solve_info_copy = format!(
-                                                "{}+2 @ {} {}",
-                                                round(
-                                                    self.state.solves[i].time.parse().unwrap(),
-                                                    self.state.solves_prec
-                                                ),
-                                                self.state.solves[i].scramble,
-                                                self.state.solves[i].comment,
-                                            );
-                                        }
-                                    }
-                                }
-                                ui.label("    ");
-                            });
-                        }
-                    });
+                            ui.label("    ");
+                        });
+                    }
                 });
-            }
+            });
         }
         egui::CentralPanel::default().show(ctx, |ui| {
-            if self.state.timeron == false {
-                if self.state.download == true {
+            if !self.timing() {
+                if self.settings.download {
                     self.downloader(ctx);
                 }
-                if self.state.settings_open == true {
+                if self.settings.settings_open {
                     egui::Window::new("Settings").show(ctx, |ui| {
                         ui.heading("Help");
-                        if ui.button("About Cubism").clicked() == true {
-                            self.state.used = false;
+                        if ui.button("About Cubism").clicked() {
+                            self.settings.used = false;
                         }
                         ui.separator();
                         ui.heading("Stats");
                         ui.horizontal(|ui| {
-                            if ui.button("Reset Session").clicked() == true {
-                                self.state = State::default();
+                            if ui.button("Reset Session").clicked() {
+                                // Keep the session itself, but clear its solves
+                                self.perform(Operation::Replace {
+                                    label: "Reset session".to_string(),
+                                    before: self.state.solves.clone(),
+                                    after: vec![],
+                                });
+                                self.reset_time();
                             }
-                            if ui.button("Reset App").clicked() == true {
-                                self.state = State::default();
-                                self.sessions = HashMap::new(); 
+                            if ui.button("Reset App").clicked() {
+                                self.confirm_reset = true;
                             }
                         });
-                        if ui.button("Import from CSTimer").clicked() == true {
-                            if self.state.importing == true {
-                                self.state.importing = false;
-                                self.state.imported_fail = "".to_string();
-                                self.state.imported_data = "".to_string();
+                        if ui.button("Import from CSTimer").clicked() {
+                            if self.scratch.importing {
+                                self.scratch.importing = false;
+                                self.scratch.imported_fail = "".to_string();
+                                self.scratch.imported_data = "".to_string();
                             } else {
-                                self.state.importing = true;
+                                self.scratch.importing = true;
                             }
                          } // TODO Fix CSTimer Import
                         ui.horizontal(|ui| {
                             ui.label("Average Precision: ");
-                            ui.add(egui::widgets::Slider::new(&mut self.state.ao5_prec, 0..=6));
-                            if self.state.ao5_prec != self.state.old_ao5_prec {
+                            ui.add(egui::widgets::Slider::new(&mut self.settings.ao5_prec, 0..=6));
+                            if self.settings.ao5_prec != self.settings.old_ao5_prec {
                                 self.refresh_averages();
-                                self.state.old_ao5_prec = self.state.ao5_prec;
+                                self.settings.old_ao5_prec = self.settings.ao5_prec;
                             }
                         });
                         ui.horizontal(|ui| {
                             ui.label("Timer Precision: ");
-                            ui.add(egui::widgets::Slider::new(&mut self.state.prec, 0..=3));
-                            if self.state.prec != self.state.old_prec {
-                                if self.state.solves.len() != 0 {
-                                    self.state.time = round(self.state.solves[0].time.parse::<f64>().unwrap(), self.state.prec).to_string();
+                            ui.add(egui::widgets::Slider::new(&mut self.settings.prec, 0..=3));
+                            if self.settings.prec != self.settings.old_prec {
+                                if !self.state.solves.is_empty() {
+                                    self.state.time = round(self.state.solves[0].time.parse::<f64>().unwrap(), self.settings.prec).to_string();
                                 }
-                                self.state.old_prec = self.state.prec;
+                                self.settings.old_prec = self.settings.prec;
                             }
                         });
                         ui.horizontal(|ui| {
                             ui.label("Solves Precision: ");
-                            ui.add(egui::widgets::Slider::new(&mut self.state.solves_prec, 0..=3));
-                            if self.state.solves_prec != self.state.old_solves_prec {
+                            ui.add(egui::widgets::Slider::new(&mut self.settings.solves_prec, 0..=3));
+                            if self.settings.solves_prec != self.settings.old_solves_prec {
                                 self.redraw_solves();
-                                self.state.old_solves_prec = self.state.solves_prec;
+                                self.settings.old_solves_prec = self.settings.solves_prec;
                             }
                         });
                         ui.horizontal(|ui| {
-                            ui.label("Cube Type: ");
-                            egui::ComboBox::from_label("").selected_text(format!("{}", self.state.cube_type)).show_ui(ui, |ui| {
-                                ui.selectable_value(&mut self.state.cube_type, Cubes::TwoByTwo, "2x2");
-                                ui.selectable_value(&mut self.state.cube_type, Cubes::ThreeByThree, "3x3");
-                                ui.selectable_value(&mut self.state.cube_type, Cubes::FourByFour, "4x4");
-                                ui.selectable_value(&mut self.state.cube_type, Cubes::FiveByFive, "5x5");
+                            ui.label("Default Event: ");
+                            egui::ComboBox::from_label("").selected_text(format!("{}", self.settings.event)).show_ui(ui, |ui| {
+                                for event in EVENTS {
+                                    ui.selectable_value(&mut self.settings.event, event, event.to_string());
+                                }
                             });
-                            if self.state.cube_type_old != self.state.cube_type {
-                                self.state.scramble = self.make_scramble();
-                                self.state.cube_type_old = self.state.cube_type;
-                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Default Inspection: ");
+                            ui.checkbox(&mut self.settings.inspection, "");
                         });
                         ui.separator();
                         ui.heading("Style");
                         ui.horizontal(|ui| {
                             ui.label("Background Colour");
-                            ui.color_edit_button_srgb(&mut self.state.background);
+                            ui.color_edit_button_srgb(&mut self.settings.background);
                         });
                         ui.horizontal(|ui| {
                             ui.label("Window Colour");
-                            ui.color_edit_button_srgb(&mut self.state.window);
+                            ui.color_edit_button_srgb(&mut self.settings.window);
                         });
                         ui.horizontal(|ui| {
                             ui.label("Button Colour");
-                            ui.color_edit_button_srgb(&mut self.state.button);
+                            ui.color_edit_button_srgb(&mut self.settings.button);
                         });
                         ui.horizontal(|ui| {
                             ui.label("Text Colour");
-                            ui.color_edit_button_srgb(&mut self.state.text);
+                            ui.color_edit_button_srgb(&mut self.settings.text);
                         });
                         ui.horizontal(|ui| {
                             ui.label("Button Outline");
-                            ui.color_edit_button_srgb(&mut self.state.outline);
+                            ui.color_edit_button_srgb(&mut self.settings.outline);
                         });
                         ui.horizontal(|ui| {
                             ui.label("Outline Width");
-                            ui.add(egui::widgets::Slider::new(&mut self.state.outline_w, 0.0..=1.5));
+                            ui.add(egui::widgets::Slider::new(&mut self.settings.outline_w, 0.0..=1.5));
                         });
                         ui.horizontal(|ui| {
                             ui.label("Title Bar Colour");
-                            ui.color_edit_button_srgb(&mut self.state.titlebar);
+                            ui.color_edit_button_srgb(&mut self.settings.titlebar);
                         });
                         ui.horizontal(|ui| {
                             ui.label("Widget Colour");
-                            ui.color_edit_button_srgb(&mut self.state.widget);
+                            ui.color_edit_button_srgb(&mut self.settings.widget);
                         });
                         ui.horizontal(|ui| {
                             ui.label("Footer Text");
-                            ui.text_edit_singleline(&mut self.state.footer);
+                            ui.text_edit_singleline(&mut self.settings.footer);
                         });
+                        ui.separator();
+                        ui.heading("Keybindings");
+                        self.keybindings(ui);
 
                     });
                 }
-                if self.state.importing == true {
+                if self.scratch.importing {
                     egui::Window::new("Import from CSTimer").show(ctx, |ui| {
                         ui.label("Open CSTimer, click export, and click export to file. Copy the contents of that file and paste it here.");
-                        ui.text_edit_singleline(&mut self.state.imported_data);
+                        ui.text_edit_singleline(&mut self.scratch.imported_data);
                         ui.horizontal(|ui| {
-                            if ui.button("Import").clicked() == true {
+                            if ui.button("Import").clicked() {
                                 let data: serde_json::Value;
-                                let result = serde_json::from_str(&self.state.imported_data);
+                                let result = serde_json::from_str(&self.scratch.imported_data);
                                 if let Err(e) = result {
                                     println!("{}", e);
-                                    self.state.imported_fail = "Invalid JSON!".to_string();
+                                    self.scratch.imported_fail = "Invalid JSON!".to_string();
                                 } else {
                                     data = result.unwrap();
                                     if let Some(session) = data["session1"].as_array() {
-                                        self.state.imported_fail = "Imported data!".to_string();
+                                        self.scratch.imported_fail = "Imported data!".to_string();
+                                        let mut solves = self.state.solves.clone();
                                         for value in session {
                                             let mut total: f64 = 0.0;
                                             for value in value[0].as_array().unwrap() {
@@ -988,353 +2630,601 @@ impl eframe::App for Cubism {
                                             }
                                             let solve = SolveStats {
                                                 time: total.to_string(),
-                                                timestamp: timestamp(),
+                                                // CSTimer stores when the solve was done in seconds
+                                                timestamp: match value[3].as_i64() {
+                                                    Some(seconds) => seconds.to_string(),
+                                                    None => timestamp(),
+                                                },
                                                 scramble: value[1].as_str().unwrap().to_string(),
                                                 comment: value[2].as_str().unwrap().to_string(),
                                                 ..SolveStats::default()
                                             };
-                                            self.state.solves.insert(0, solve);
-                                        }
-                                        self.redraw_solves();
-                                        self.refresh_averages();
-                                        if self.state.solves.len() > 2 {
-                                            self.state.mo3 = mean(&self.state.solves.get(0..=2).unwrap().to_vec(), 2, self.state.ao5_prec);
+                                            solves.insert(0, solve);
                                         }
-                                        if self.state.solves.len() != 0 {
-                                            self.state.mean = mean(&self.state.solves, self.state.solves.len() - 1, self.state.ao5_prec);
-                                        }
-                                        self.state.scramble_text = format!("{} @ {}", round(self.state.solves[0].time.parse().unwrap(), self.state.solves_prec), self.state.solves[0].scramble);
-                                        self.calculate_plottable();
+                                        self.perform(Operation::Replace {
+                                            label: format!("Imported {} solves", session.len()),
+                                            before: self.state.solves.clone(),
+                                            after: solves,
+                                        });
                                     } else {
-                                        self.state.imported_fail = "Failed to import data!".to_string();
+                                        self.scratch.imported_fail = "Failed to import data!".to_string();
                                     }
                                 }
                             }
-                            if ui.button("Close").clicked() == true{
-                                self.state.importing = false;
-                                self.state.imported_fail = "".to_string();
-                                self.state.imported_data = "".to_string();
+                            if ui.button("Close").clicked() {
+                                self.scratch.importing = false;
+                                self.scratch.imported_fail = "".to_string();
+                                self.scratch.imported_data = "".to_string();
                             }
                         });
-                        ui.label(format!("{}", self.state.imported_fail));
+                        ui.label(&self.scratch.imported_fail);
                     });
                 }
             }
-            if self.state.timeron == false {
-                if self.state.used == false {
+            if !self.timing() {
+                if !self.settings.used {
                     egui::Window::new("About Cubism").show(ctx, |ui| {
                         ui.label("Cubism is like CSTimer, but the way you start the timer is different. Press the space bar to start and stop, no need to hold. You cannot record times under 0.25 seconds!");
                         ui.hyperlink_to(
                             "Click here for logo credits.",
                             "https://www.flaticon.com/free-icons/3d-cube",
                         );
-                        if ui.button("I Understand").clicked() == true {
-                            self.state.used = true;
+                        if ui.button("I Understand").clicked() {
+                            self.settings.used = true;
                         }
                     });
                 }
-                if self.state.show_solve == true {
-                    if self.state.show_solve_info == true {
-                        egui::Window::new("Solve Stats").show(ctx, |ui| {
-                            let displayed: String;
-                            if self.state.solves[0].dnf == true {
-                                displayed = "DNF".to_string();
-                            } else {
-                                displayed = round(self.state.solves[0].time.parse().unwrap(), self.state.solves_prec).to_string();
+                if self.state.show_solve && self.settings.show_solve_info {
+                    egui::Window::new("Solve Stats").show(ctx, |ui| {
+                        let displayed = if self.state.solves[0].dnf {
+                            "DNF".to_string()
+                        } else {
+                            round(self.state.solves[0].time.parse().unwrap(), self.settings.solves_prec).to_string()
+                        };
+                        ui.heading(displayed);
+                        ui.label(&self.state.solves[0].scramble);
+                        ui.horizontal(|ui| {
+                            if ui.button("+2").clicked() {
+                                self.set_penalty(0, true, false);
+                            }
+                            if ui.button("DNF").clicked() {
+                                self.set_penalty(0, false, true);
+                            }
+                            if ui.button("OK").clicked() {
+                                self.set_penalty(0, false, false);
+                            }
+                            if ui.button("DEL").clicked() {
+                                self.delete_latest();
                             }
-                            ui.heading(format!("{}", displayed));
-                            ui.label(format!("{}", self.state.solves[0].scramble));
-                            ui.horizontal(|ui| {
-                                if ui.button("+2").clicked() {
-                                    if !self.state.solves[0].plus2 {
-                                        self.state.solves[0].plus2 = true;
-                                        self.state.solves[0].dnf = false;
-                                        self.state.solves[0].time = (self.state.solves[0].time.parse::<f64>().unwrap() + 2.0).to_string();
-                                        self.state.scramble_text = format!("{}+2 @ {}", round(self.state.solves[0].clone().time.parse::<f64>().unwrap() - 2.0, self.state.solves_prec), self.state.solves[0].scramble);
-                                        self.refresh_averages();
-                                    }
-                                }
-                                if ui.button("DNF").clicked() {
-                                    if !self.state.solves[0].dnf {
-                                        if self.state.solves[0].plus2 {
-                                            self.state.solves[0].time = (self.state.solves[0].time.parse::<f64>().unwrap() - 2.0).to_string();
-                                        }
-                                        self.state.solves[0].plus2 = false;
-                                        self.state.solves[0].dnf = true;
-                                        self.state.scramble_text = format!("DNF [{}] @ {}", round(self.state.solves[0].time.parse().unwrap(), self.state.solves_prec), self.state.solves[0].scramble);
-                                        self.refresh_averages();
-                                    }
-                                }
-                                if ui.button("OK").clicked() {
-                                    if self.state.solves[0].plus2 {
-                                        self.state.solves[0].time = (self.state.solves[0].time.parse::<f64>().unwrap() - 2.0).to_string();
-                                    }
-                                    self.state.solves[0].plus2 = false;
-                                    self.state.solves[0].dnf = false;
-                                    self.state.scramble_text = format!("{} @ {}", round(self.state.solves[0].time.parse().unwrap(), self.state.solves_prec), self.state.solves[0].scramble);
-                                    self.refresh_averages();
-                                }
-                                if ui.button("DEL").clicked() {
-                                    self.state.solves.remove(0);
-                                    self.state.time = round(self.state.solves[0].time.parse().unwrap(), self.state.prec).to_string();
-                                    self.state.show_solve = false;
-                                    self.refresh_averages();
-                                    self.redraw_solves();
-                                }
-                            });
-                            // Time
-                            ui.horizontal(|ui| {
-                                ui.label("Copyable:  ");
-                                ui.text_edit_singleline(&mut self.state.scramble_text.as_str());
-                            });
                         });
-                    }
+                        // Time
+                        ui.horizontal(|ui| {
+                            ui.label("Copyable:  ");
+                            ui.text_edit_singleline(&mut self.state.scramble_text.as_str());
+                        });
+                    });
                 }
-                if self.state.solve_info == true {
+                if self.state.solve_info {
                     egui::Window::new("Solve Info").show(ctx, |ui| {
-                        ui.heading(format!("{}", round(self.state.solves[self.state.solve_index].time.parse().unwrap(), self.state.solves_prec)));
-                        if self.state.solves[self.state.solve_index].dnf == true {
+                        ui.heading(format!("{}", round(self.state.solves[self.state.solve_index].time.parse().unwrap(), self.settings.solves_prec)));
+                        if self.state.solves[self.state.solve_index].dnf {
                             ui.label("Did Not Finish");
                         }
-                        if self.state.solves[self.state.solve_index].plus2 == true {
+                        if self.state.solves[self.state.solve_index].plus2 {
                             ui.label("+2 Penalty");
                         }
-                        let portion = self.state.solves.get(self.state.solve_index..self.state.solves.len());
-                        match portion {
-                            Some(data) => {
-                                if data.len() > 2 {
-                                    ui.label(format!("Mo3: {}", mean(&data.to_vec(), 2, self.state.ao5_prec)));
-                                }
-                            },
-                            None => {
-                            }
-                        };
-                        for gap in [4, 11, 24, 49, 99, 499, 999, 1999, 4999] {
-                            let portion = self.state.solves.get(self.state.solve_index..self.state.solves.len());
-                            match portion {
-                                Some(data) => {
-                                    let portion = data.to_vec();
-                                    if portion.len() > gap {
-                                        let average = average(&portion, gap, self.state.ao5_prec);
-                                        ui.label(format!("Ao{}: {}", gap+1, average));
-                                    }
-                                },
-                                None => {},
+                        // Averages ending with this solve, taken the same way as in Statistics
+                        let times = stats::times_ending_with(&self.state.solves, self.state.solve_index);
+                        for size in [3, 5, 12, 25, 50, 100, 500, 1000, 2000, 5000] {
+                            if let Some(window) = times.get(0..size) {
+                                let name = if size == 3 { "Mo3".to_string() } else { format!("Ao{}", size) };
+                                ui.label(format!("{}: {}", name, format_average(stats::average_of(window), self.settings.ao5_prec)));
                             }
                         }
                         let mut dont_redraw = false;
                         ui.horizontal(|ui| {
+                            let index = self.state.solve_index;
                             if ui.button("+2").clicked() {
-                                if !self.state.solves[self.state.solve_index].plus2 {
-                                    self.state.solves[self.state.solve_index].plus2 = true;
-                                    self.state.solves[self.state.solve_index].dnf = false;
-                                    self.state.solves[self.state.solve_index].time = (self.state.solves[self.state.solve_index].time.parse::<f64>().unwrap() + 2.0).to_string();
-                                    self.state.solve_info_copy = format!("{}+2 @ {}", round(self.state.solves[self.state.solve_index].clone().time.parse::<f64>().unwrap() - 2.0, self.state.solves_prec), self.state.solves[self.state.solve_index].scramble);
-                                    self.refresh_averages();
-                                }
+                                self.set_penalty(index, true, false);
                             }
                             if ui.button("DNF").clicked() {
-                                if !self.state.solves[self.state.solve_index].dnf {
-                                    if self.state.solves[self.state.solve_index].plus2 {
-                                        self.state.solves[self.state.solve_index].time = (self.state.solves[self.state.solve_index].time.parse::<f64>().unwrap() - 2.0).to_string();
-                                    }
-                                    self.state.solves[self.state.solve_index].plus2 = false;
-                                    self.state.solves[self.state.solve_index].dnf = true;
-                                    self.state.solve_info_copy = format!("DNF [{}] @ {}", round(self.state.solves[self.state.solve_index].time.parse().unwrap(), self.state.solves_prec), self.state.solves[self.state.solve_index].scramble);
-                                    self.refresh_averages();
-                                }
+                                self.set_penalty(index, false, true);
                             }
                             if ui.button("OK").clicked() {
-                                if self.state.solves[self.state.solve_index].plus2 {
-                                    self.state.solves[self.state.solve_index].time = (self.state.solves[self.state.solve_index].time.parse::<f64>().unwrap() - 2.0).to_string();
-                                }
-                                self.state.solves[self.state.solve_index].plus2 = false;
-                                self.state.solves[self.state.solve_index].dnf = false;
-                                self.state.solve_info_copy = format!("{} @ {}", round(self.state.solves[self.state.solve_index].time.parse().unwrap(), self.state.solves_prec), self.state.solves[self.state.solve_index].scramble);
-                                self.refresh_averages();
+                                self.set_penalty(index, false, false);
                             }
                             if ui.button("DEL").clicked() {
-                                self.state.solves.remove(self.state.solve_index);
+                                self.perform(Operation::Delete {
+                                    index,
+                                    solve: self.state.solves[index].clone(),
+                                });
                                 dont_redraw = true;
-                                // self.state.time = round(self.state.solves[self.state.solve_index].time.parse().unwrap(), self.state.prec).to_string();
                                 self.state.solve_info = false;
-                                if self.state.solves.len() == 0 {
-                                    self.state.show_solve = false;
-                                }
-                                self.refresh_averages();
-                                self.redraw_solves();
                             }
                         });
-                        if dont_redraw == false {
+                        if !dont_redraw {
                         ui.label(format!("Scramble: {}", self.state.solves[self.state.solve_index].scramble));
+                        let solve = &self.state.solves[self.state.solve_index];
+                        ui.label(format!("Event: {}", solve.event()));
+                        let splits: Vec<String> = solve
+                            .relay
+                            .iter()
+                            .filter_map(|part| Some(format!("{}: {}", part.puzzle, round(part.split?, self.settings.solves_prec))))
+                            .collect();
+                        if !splits.is_empty() {
+                            ui.label(format!("Splits: {}", splits.join("  ")));
+                        }
+                        if let (Some(memo), Ok(time)) = (solve.memo, solve.time.parse::<f64>()) {
+                            ui.label(format!("Memo: {}  Execution: {}", round(memo, self.settings.solves_prec), round(time - memo, self.settings.solves_prec)));
+                        }
                         ui.horizontal(|ui| {
                             ui.label("Comment: ");
-                            ui.text_edit_singleline(&mut self.state.solves[self.state.solve_index].comment);
+                            let index = self.state.solve_index;
+                            let mut comment = self.state.solves[index].comment.clone();
+                            if ui.text_edit_singleline(&mut comment).changed() {
+                                self.state.history.perform(
+                                    Operation::Comment {
+                                        index,
+                                        before: self.state.solves[index].comment.clone(),
+                                        after: comment,
+                                    },
+                                    &mut self.state.solves,
+                                );
+                            }
                         });
+                        self.tag_editor(ui);
                         ui.horizontal(|ui| {
                             ui.label("Copyable:  ");
                             ui.text_edit_singleline(&mut self.state.solve_info_copy.as_str());
                         });
-                        if ui.button("Close").clicked() == true {
+                        if ui.button("Close").clicked() {
                             self.state.solve_info_copy = "".to_string();
                             self.state.solve_info = false;
                         }
                         }
                     });
                 }
-                if self.state.show_tools == true {
+                if self.settings.show_tools {
                     egui::Window::new("Tools").show(ctx, |ui| {
                         ui.horizontal(|ui| {
                             egui::containers::ComboBox::from_label("")
-                                .selected_text(&self.state.current_tool)
+                                .selected_text(&self.scratch.current_tool)
                                 .show_ui(ui, |ui| {
-                                    ui.selectable_value(&mut self.state.current_tool, "Plot Times".to_string(), "Plot Times");
-                                    ui.selectable_value(&mut self.state.current_tool, "Solve".to_string(), "Solve");
-                                    ui.selectable_value(&mut self.state.current_tool, "Custom Solve".to_string(), "Custom Solve");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Plot Times".to_string(), "Plot Times");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Progress".to_string(), "Progress");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Personal Bests".to_string(), "Personal Bests");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Tags".to_string(), "Tags");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Step Solver".to_string(), "Step Solver");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Solve".to_string(), "Solve");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Custom Solve".to_string(), "Custom Solve");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Algorithm Tools".to_string(), "Algorithm Tools");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "LL Trainer".to_string(), "LL Trainer");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Algorithm Library".to_string(), "Algorithm Library");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Multi-BLD".to_string(), "Multi-BLD");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "FMC".to_string(), "FMC");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Competition".to_string(), "Competition");
+                                    ui.selectable_value(&mut self.scratch.current_tool, "Scramble Sheets".to_string(), "Scramble Sheets");
                                 });
                         });
-                        if self.state.current_tool == "Plot Times".to_string() {
+                        if self.scratch.current_tool == "Plot Times" {
                             ui.separator();
                             ui.heading("Plot Times");
-                            let line = egui_plot::Line::new(self.state.plottable.clone());
-                            egui_plot::Plot::new("Time Distribution").view_aspect(self.state.plot_aspect_ratio).show(ui, |plot_ui| plot_ui.line(line));
+                            self.plot_times(ui);
                             ui.horizontal(|ui| {
                                 ui.label("Width: ");
-                                ui.add(egui::Slider::new(&mut self.state.plot_aspect_ratio, 1.0..=6.0));
+                                ui.add(egui::Slider::new(&mut self.settings.plot_aspect_ratio, 1.0..=6.0));
                             });
                         }
-                        if self.state.current_tool == "Solve".to_string() {
+                        if self.scratch.current_tool == "Tags" {
+                            ui.separator();
+                            ui.heading("Tags");
+                            self.tag_stats(ui);
+                        }
+                        if self.scratch.current_tool == "Personal Bests" {
+                            ui.separator();
+                            ui.heading("Personal Bests");
+                            self.personal_bests(ui);
+                        }
+                        if self.scratch.current_tool == "Progress" {
+                            ui.separator();
+                            ui.heading("Progress");
+                            self.progress(ui);
+                        }
+                        if self.scratch.current_tool == "Step Solver" {
+                            ui.separator();
+                            ui.heading("Step Solver");
+                            ui.label("Optimal solutions for a step of the current scramble on each colour");
+                            self.step_solver(ui);
+                        }
+                        if self.scratch.current_tool == "Solve" {
                             ui.separator();
                             ui.heading("Solve");
                             ui.label("Solve the current Rubik's cube scramble in a low amount of moves");
-                            if ui.button("Solve").clicked() {
-                                self.state.solution = solve(self.state.scramble.clone());
-                            }
-                            ui.label(format!("Solution: {}", self.state.solution));
+                            self.solver(ui, false);
                         }
-                        if self.state.current_tool == "Custom Solve".to_string() {
+                        if self.scratch.current_tool == "Custom Solve" {
                             ui.separator();
                             ui.heading("Custom Solve");
                             ui.label("Input a scramble and the solver will propose a solution!");
-                            ui.text_edit_singleline(&mut self.state.c_scramble);
-                            if ui.button("Solve").clicked() {
-                                self.state.c_solution = solve(self.state.c_scramble.clone());
-                            }
-                            ui.label(format!("Solution: {}", self.state.c_solution));
+                            let cube = self.cube_type();
+                            scramble_edit(ui, &mut self.scratch.c_scramble, cube);
+                            self.solver(ui, true);
+                        }
+                        if self.scratch.current_tool == "Algorithm Tools" {
+                            ui.separator();
+                            ui.heading("Algorithm Tools");
+                            ui.label("Invert, simplify or mirror an algorithm, and count its moves");
+                            self.algorithm_tools(ui);
+                        }
+                        if self.scratch.current_tool == "LL Trainer" {
+                            ui.separator();
+                            ui.heading("LL Trainer");
+                            ui.label("Practice OLL and PLL cases, with statistics for each case");
+                            self.trainer(ui);
+                        }
+                        if self.scratch.current_tool == "Algorithm Library" {
+                            ui.separator();
+                            ui.heading("Algorithm Library");
+                            ui.label("Keep your algorithms and notes for each case, and drill the cases that are due");
+                            self.algorithm_library(ui);
+                        }
+                        if self.scratch.current_tool == "Multi-BLD" {
+                            ui.separator();
+                            ui.heading("Multi-BLD");
+                            ui.label("Solve several cubes blindfolded in one attempt, scored as solved minus unsolved cubes");
+                            self.multi_bld(ui);
+                        }
+                        if self.scratch.current_tool == "FMC" {
+                            ui.separator();
+                            ui.heading("FMC");
+                            ui.label("Find the shortest solution you can to a scramble within an hour, scored by its move count");
+                            self.fmc(ui);
+                        }
+                        if self.scratch.current_tool == "Competition" {
+                            ui.separator();
+                            ui.heading("Competition");
+                            ui.label("Simulate a WCA round with fixed scrambles, inspection, a cutoff and a time limit");
+                            self.competition(ui);
+                        }
+                        if self.scratch.current_tool == "Scramble Sheets" {
+                            ui.separator();
+                            ui.heading("Scramble Sheets");
+                            ui.label("Generate printable competition scrambles for each round of some events, the same for the same seed");
+                            self.scramble_sheets(ui);
                         }
                     });
                 }
-                if self.state.stats_open == true {
+                if self.settings.stats_open {
                     egui::Window::new("Statistics").show(ctx, |ui| {
-                    ui.label(format!("Solves: {}", self.state.solves.len()));
+                    ui.label(format!("{} Solves: {}", self.event(), self.event_solves().len()));
                     if self.state.mean.as_str() != "" {
                         ui.label(format!("Mean: {}", self.state.mean));
                     }
                     ui.separator();
+                    if self.event().is_bld() {
+                        // Blindfolded events are ranked by single and mean of 3, so those come first
+                        let bld = stats::bld_summary(&self.event_solves());
+                        let prec = self.settings.ao5_prec;
+                        let format = |time: Option<f64>| match time {
+                            Some(time) => round(time, prec).to_string(),
+                            None => "-".to_string(),
+                        };
+                        let mo3 = match bld.mo3 {
+                            _ if bld.attempts < 3 => "-".to_string(),
+                            Some(mo3) => round(mo3, prec).to_string(),
+                            None => "DNF".to_string(),
+                        };
+                        egui::Grid::new("blindfolded").show(ui, |ui| {
+                            for (name, value) in [
+                                ("Best Single", format(bld.best)),
+                                ("Mo3", mo3),
+                                ("Best Mo3", format(bld.best_mo3)),
+                                ("Success Rate", format!("{}% ({}/{})", round(bld.success_rate() * 100.0, 1), bld.successes, bld.attempts)),
+                                ("Mean Memo", format(bld.memo)),
+                                ("Mean Execution", format(bld.execution)),
+                            ] {
+                                ui.label(name);
+                                ui.label(value);
+                                ui.end_row();
+                            }
+                        });
+                        ui.separator();
+                    }
+                    if self.event().is_relay() {
+                        let splits = stats::mean_splits(&self.event_solves(), &self.event().puzzles());
+                        egui::Grid::new("relay_splits").show(ui, |ui| {
+                            for (puzzle, split) in splits {
+                                ui.label(format!("Mean {}", puzzle));
+                                ui.label(split.map_or("-".to_string(), |split| round(split, self.settings.ao5_prec).to_string()));
+                                ui.end_row();
+                            }
+                        });
+                        ui.separator();
+                    }
                     if self.state.mo3.as_str() != "" {
                         ui.label(format!("Mo3: {}", self.state.mo3))
-                            .on_hover_text(format!("Best Mo3: {}", self.state.best_mo3));
+                            .on_hover_text(format!("Best Mo3: {}\n{}", self.state.best_mo3, self.window_extremes(3)));
                     }
                     if self.state.ao5.as_str() != "" {
                         ui.label(format!("Ao5: {}", self.state.ao5))
-                            .on_hover_text(format!("Best Ao5: {}", self.state.best_ao5));
+                            .on_hover_text(format!("Best Ao5: {}\n{}", self.state.best_ao5, self.window_extremes(5)));
                     }
                     if self.state.ao12.as_str() != "" {
                         ui.label(format!("Ao12: {}", self.state.ao12))
-                            .on_hover_text(format!("Best Ao12: {}", self.state.best_ao12));
+                            .on_hover_text(format!("Best Ao12: {}\n{}", self.state.best_ao12, self.window_extremes(12)));
                     }
                     if self.state.ao25.as_str() != "" {
                         ui.label(format!("Ao25: {}", self.state.ao25))
-                            .on_hover_text(format!("Best Ao25: {}", self.state.best_ao25));
+                            .on_hover_text(format!("Best Ao25: {}\n{}", self.state.best_ao25, self.window_extremes(25)));
                     }
                     if self.state.ao50.as_str() != "" {
                         ui.label(format!("Ao50: {}", self.state.ao50))
-                            .on_hover_text(format!("Best Ao50: {}", self.state.best_ao50));
+                            .on_hover_text(format!("Best Ao50: {}\n{}", self.state.best_ao50, self.window_extremes(50)));
                     }
                     if self.state.ao100.as_str() != "" {
                         ui.label(format!("Ao100: {}", self.state.ao100))
-                            .on_hover_text(format!("Best Ao100: {}", self.state.best_ao100));
+                            .on_hover_text(format!("Best Ao100: {}\n{}", self.state.best_ao100, self.window_extremes(100)));
                     }
                     if self.state.ao500.as_str() != "" {
                         ui.label(format!("Ao500: {}", self.state.ao500))
-                            .on_hover_text(format!("Best Ao500: {}", self.state.best_ao500));
+                            .on_hover_text(format!("Best Ao500: {}\n{}", self.state.best_ao500, self.window_extremes(500)));
                     }
                     if self.state.ao1000.as_str() != "" {
                         ui.label(format!("Ao1000: {}", self.state.ao1000))
-                            .on_hover_text(format!("Best Ao1000: {}", self.state.best_ao1000));
+                            .on_hover_text(format!("Best Ao1000: {}\n{}", self.state.best_ao1000, self.window_extremes(1000)));
                     }
                     if self.state.ao2000.as_str() != "" {
                         ui.label(format!("Ao2000: {}", self.state.ao2000))
-                            .on_hover_text(format!("Best Ao2000: {}", self.state.best_ao2000));
+                            .on_hover_text(format!("Best Ao2000: {}\n{}", self.state.best_ao2000, self.window_extremes(2000)));
                     }
                     if self.state.ao5000.as_str() != "" {
-                        ui.label(format!("Ao5000: {}", self.state.ao2000))
-                            .on_hover_text(format!("Best Ao5000: {}", self.state.best_ao5000));
+                        ui.label(format!("Ao5000: {}", self.state.ao5000))
+                            .on_hover_text(format!("Best Ao5000: {}\n{}", self.state.best_ao5000, self.window_extremes(5000)));
+                    }
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.heading("Distribution");
+                        ui.checkbox(&mut self.settings.stats_all_sessions, "All Sessions");
+                    });
+                    if self.settings.filter.is_active() {
+                        ui.label("Only solves matching the filter are counted");
                     }
+                    let summary = stats::summarize(&self.filtered_solves(self.settings.stats_all_sessions), self.settings.threshold);
+                    let prec = self.settings.ao5_prec;
+                    let format = |time: Option<f64>| match time {
+                        Some(time) => round(time, prec).to_string(),
+                        None => "-".to_string(),
+                    };
+                    egui::Grid::new("distribution").show(ui, |ui| {
+                        for (name, value) in [
+                            ("Best", format(summary.best)),
+                            ("Worst", format(summary.worst)),
+                            ("Standard Deviation", format(summary.std_dev)),
+                            ("Median", format(summary.median)),
+                            ("Quartiles", format!("{} / {}", format(summary.q1), format(summary.q3))),
+                            ("10th / 90th Percentile", format!("{} / {}", format(summary.p10), format(summary.p90))),
+                            ("DNF Rate", format!("{}% ({})", round(summary.dnf_rate() * 100.0, 1), summary.dnfs)),
+                            ("+2 Rate", format!("{}% ({})", round(summary.plus2_rate() * 100.0, 1), summary.plus2s)),
+                        ] {
+                            ui.label(name);
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label("Sub");
+                            ui.add(egui::DragValue::new(&mut self.settings.threshold).speed(0.1).clamp_range(0.0..=3600.0).suffix("s"));
+                        });
+                        ui.label(summary.sub_threshold.to_string());
+                        ui.end_row();
+                    });
+                    });
+                }
+                if !self.new_pbs.is_empty() {
+                    egui::Area::new("new_pbs")
+                        .anchor(egui::Align2::CENTER_CENTER, [0.0, 60.0])
+                        .interactable(false)
+                        .show(ctx, |ui| {
+                            for message in &self.new_pbs {
+                                ui.colored_label(egui::Color32::from_rgb(212, 160, 23), egui::RichText::new(message).heading());
+                            }
+                        });
+                }
+                if self.confirm_reset {
+                    egui::Window::new("Reset App").collapsible(false).resizable(false).show(ctx, |ui| {
+                        ui.label("This deletes every session and setting, and cannot be undone.");
+                        ui.horizontal(|ui| {
+                            if ui.button("Reset").clicked() {
+                                self.settings = Settings::default();
+                                self.state = State::default();
+                                self.sessions = HashMap::new();
+                                self.library = Library::default();
+                                self.trainer = Trainer::default();
+                                self.multi_bld = MultiBld::default();
+                                self.fmc = Fmc::default();
+                                self.competition = Competition::default();
+                                self.scratch = Scratch::default();
+                                self.toast = None;
+                                self.confirm_reset = false;
+                            }
+                            if ui.button("Cancel").clicked() {
+                                self.confirm_reset = false;
+                            }
+                        });
                     });
                 }
-                if self.state.show_left_bar == false {
+                if let Some((error, data)) = self.load_error.clone() {
+                    egui::Window::new("Saved Data").collapsible(false).resizable(false).show(ctx, |ui| {
+                        ui.label("Your saved sessions and settings couldn't be read, so Cubism started empty. Nothing is saved over them until you start fresh.");
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                        ui.horizontal(|ui| {
+                            if ui.button("Copy Saved Data").on_hover_text("Keep a copy to restore or report").clicked() {
+                                ui.output_mut(|o| o.copied_text = data);
+                            }
+                            if ui.button("Start Fresh").clicked() {
+                                self.load_error = None;
+                            }
+                        });
+                    });
+                }
+                if let Some((message, shown)) = self.toast.clone() {
+                    if Local::now().signed_duration_since(shown) > TimeDelta::try_seconds(TOAST_SECONDS).unwrap() {
+                        self.toast = None;
+                    } else {
+                        egui::Window::new("Undo")
+                            .title_bar(false)
+                            .resizable(false)
+                            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -40.0])
+                            .show(ctx, |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label(message);
+                                    if ui.add_enabled(self.state.history.can_undo(), egui::Button::new("Undo")).on_hover_text(self.settings.keys.describe(Action::Undo)).clicked() {
+                                        self.undo();
+                                    }
+                                    if ui.add_enabled(self.state.history.can_redo(), egui::Button::new("Redo")).on_hover_text(self.settings.keys.describe(Action::Redo)).clicked() {
+                                        self.redo();
+                                    }
+                                    if ui.button("x").clicked() {
+                                        self.toast = None;
+                                    }
+                                });
+                            });
+                        ctx.request_repaint_after(std::time::Duration::from_secs(1));
+                    }
+                }
+                if !self.settings.show_left_bar {
                     egui::Window::new("Show Left Bar").show(ctx, |ui| {
                         if ui.button("Show Left Bar").clicked() {
-                            self.state.show_left_bar = true;
+                            self.settings.show_left_bar = true;
                         }
                     });
                 }
             }
-            if self.state.timeron == true {
+            if self.timing() {
                 ctx.request_repaint();
             }
-            if ctx.wants_keyboard_input() == false {
+            // Attempts of a competition round stop at its time limit, as a DNF
+            if let Some(time_limit) = self.competition.running().map(|round| round.time_limit) {
+                let elapsed = seconds_since(self.state.starttime);
+                if self.state.timeron && elapsed >= time_limit {
+                    self.state.timeron = false;
+                    self.finish_round_attempt(elapsed);
+                }
+            }
+            if !ctx.wants_keyboard_input() {
                 ctx.input(|i| {
                     for event in i.clone().events {
-                        match event {
-                            egui::Event::Key { key, .. } => {
-                                let delta = Local::now().signed_duration_since(self.state.debounce);
-                                if delta > TimeDelta::try_milliseconds(250).unwrap() {
-                                    self.state.debounce = Local::now();
-                                    if key == egui::Key::Space {
-                                        if self.state.timeron == false {
-                                            self.state.timeron = true;
-                                            self.state.starttime = Local::now();
+                        if let egui::Event::Key { key, pressed, modifiers, .. } = event {
+                            if pressed {
+                                if let Some(action) = self.rebinding.take() {
+                                    // The key pressed after clicking Add is bound, unless it's kept for the timer
+                                    if !keys::TIMER_KEYS.contains(&key) {
+                                        self.settings.keys.add(action, keys::binding(key, modifiers));
+                                    }
+                                    continue;
+                                }
+                                if !self.timing() {
+                                    if let Some(action) = self.settings.keys.action(key, modifiers) {
+                                        self.run_action(action);
+                                        continue;
+                                    }
+                                }
+                            }
+                            let delta = Local::now().signed_duration_since(self.state.debounce);
+                            if delta > TimeDelta::try_milliseconds(250).unwrap() {
+                                self.state.debounce = Local::now();
+                                // Solves don't start until the scramble being made is ready
+                                if key == egui::Key::Space && (self.state.timeron || self.scramble_job.is_none()) {
+                                    if !self.state.timeron && !self.state.inspecting && self.inspection() {
+                                        self.state.inspecting = true;
+                                        self.state.inspection_start = Local::now();
+                                    } else if !self.state.timeron {
+                                        if self.state.inspecting {
+                                            self.state.inspecting = false;
+                                            self.state.inspection_time = seconds_since(self.state.inspection_start);
                                         } else {
-                                            self.state.timeron = false;
-                                            let rawtime: f64 = Local::now().signed_duration_since(self.state.starttime).num_microseconds().unwrap() as f64 / 1000000 as f64;
-                                            let timertime = round(rawtime,
-                                                self.state.prec
-                                            );
-                                            let solvetime = round(rawtime,
-                                                self.state.solves_prec
-                                            );
-                                            let solve_: SolveStats;
-                                                solve_ = SolveStats {
-                                                    time: rawtime.to_string(),
-                                                    scramble: self.state.scramble.clone(),
-                                                    timestamp: timestamp(),
-                                                    cube_type: self.state.cube_type,
-                                                    ..SolveStats::default()
-                                                };
-                                            self.state.solves.insert(0, solve_);
-
+                                            self.state.inspection_time = 0.0;
+                                        }
+                                        self.state.timeron = true;
+                                        self.state.starttime = Local::now();
+                                        self.state.memo = None;
+                                        self.state.splits.clear();
+                                        self.new_pbs.clear();
+                                    } else if self.event().is_bld() && self.state.memo.is_none() {
+                                        // The first press ends memorisation, the second stops the timer
+                                        self.state.memo = Some(seconds_since(self.state.starttime));
+                                    } else if self.settings.relay_splits && self.state.splits.len() + 1 < self.state.relay.len() {
+                                        // Every puzzle but the last is marked as finished, the last one stops the timer
+                                        self.state.splits.push(seconds_since(self.state.starttime));
+                                    } else {
+                                        self.state.timeron = false;
+                                        let rawtime: f64 = Local::now().signed_duration_since(self.state.starttime).num_microseconds().unwrap() as f64 / 1000000.0;
+                                        let timertime = round(rawtime,
+                                            self.settings.prec
+                                        );
+                                        let solvetime = round(rawtime,
+                                            self.settings.solves_prec
+                                        );
+                                        if self.competition.running().is_some() {
+                                            self.finish_round_attempt(rawtime);
+                                            continue;
+                                        }
+                                        if self.trainer.active && self.trainer.case.is_some() {
+                                            // Trainer attempts go to the case statistics instead of the session
+                                            let scramble = self.state.scramble.clone();
+                                            self.trainer.finish(rawtime, &scramble);
                                             self.state.time = timertime.to_string();
-                                            self.state.fmt_solves.insert(0, solvetime.to_string());
-                                            if self.state.solves.len() > 2 {
-                                                self.state.mo3 = mean(&self.state.solves.get(0..=2).unwrap().to_vec(), 2, self.state.ao5_prec);
-                                            }
-                                            if self.state.solves.len() != 0 {
-                                                self.state.mean = mean(&self.state.solves, self.state.solves.len() - 1, self.state.ao5_prec);
-                                            }
-                                            self.refresh_averages();
                                             self.state.scramble = self.make_scramble();
-                                            self.state.scramble_text = format!("{} @ {}", round(self.state.solves[0].time.parse().unwrap(), self.state.prec), self.state.solves[0].scramble);
-                                            self.state.show_solve = true;
-                                            self.calculate_plottable();
+                                            continue;
+                                        }
+                                        if self.library.drilling && self.library.case.is_some() {
+                                            // Drills wait in the library to be marked as passed or failed
+                                            self.library.pending = Some(rawtime);
+                                            self.state.time = timertime.to_string();
+                                            continue;
+                                        }
+                                        let (plus2, dnf) = inspection_penalty(self.state.inspection_time);
+                                        let solve_ = SolveStats {
+                                            time: if plus2 { rawtime + 2.0 } else { rawtime }.to_string(),
+                                            scramble: self.state.scramble.clone(),
+                                            timestamp: timestamp(),
+                                            cube_type: self.cube_type(),
+                                            variant: self.event().variant,
+                                            plus2,
+                                            dnf,
+                                            memo: self.state.memo.take(),
+                                            relay: relay::with_splits(&self.state.relay, &self.state.splits, rawtime),
+                                            ..SolveStats::default()
+                                        };
+                                        let event = self.event();
+                                        let bests: Vec<Option<stats::Record>> = stats::RECORD_SIZES.iter().map(|size| self.records(event, *size).last().copied()).collect();
+                                        self.state.history.perform(Operation::Add { index: 0, solve: solve_ }, &mut self.state.solves);
+                                        for (size, best) in stats::RECORD_SIZES.iter().zip(bests) {
+                                            if let (Some(best), Some(record)) = (best, self.records(event, *size).last()) {
+                                                if record.value < best.value {
+                                                    self.new_pbs.push(format!("New {} PB: {}", stats::record_name(*size), round(record.value, self.settings.ao5_prec)));
+                                                }
+                                            }
+                                        }
+
+                                        self.state.time = timertime.to_string();
+                                        self.state.fmt_solves.insert(0, solvetime.to_string());
+                                        let solves = self.event_solves();
+                                        if !solves.is_empty() {
+                                            self.state.mean = mean(&solves, solves.len() - 1, self.settings.ao5_prec);
                                         }
-                                    } else if key == egui::Key::Escape {
-                                        self.reset_time();
+                                        self.refresh_averages();
+                                        self.state.scramble = self.make_scramble();
+                                        self.state.scramble_text = format!("{} @ {}", round(self.state.solves[0].time.parse().unwrap(), self.settings.prec), self.state.solves[0].scramble);
+                                        self.state.show_solve = true;
+                                        self.calculate_plottable();
                                     }
+                                } else if key == egui::Key::Escape {
+                                    self.state.inspecting = false;
+                                    self.state.memo = None;
+                                    self.state.splits.clear();
+                                    self.reset_time();
                                 }
                             }
-                            _ => {}
                         }
                     }
                 });
@@ -1343,21 +3233,29 @@ impl eframe::App for Cubism {
                 egui::Layout::centered_and_justified(egui::Direction::LeftToRight),
                 |ui| {
                     ui.style_mut().text_styles.get_mut(&egui::TextStyle::Heading).unwrap().size = 64.0;
-                    if self.state.timeron == true {
+                    if self.state.inspecting {
+                        let elapsed = seconds_since(self.state.inspection_start);
+                        let text = match inspection_penalty(elapsed) {
+                            (_, true) => "DNF".to_string(),
+                            (true, _) => "+2".to_string(),
+                            _ => format!("{}", (INSPECTION_SECONDS - elapsed).ceil()),
+                        };
+                        ui.heading(text);
+                    } else if self.state.timeron {
                         let secs = {
-                            let duration: f64 = Local::now().signed_duration_since(self.state.starttime).num_microseconds().unwrap() as f64 / 1000000 as f64;
+                            let duration: f64 = Local::now().signed_duration_since(self.state.starttime).num_microseconds().unwrap() as f64 / 1000000.0;
 
-                            let duration = round(duration, self.state.prec);
+                            let duration = round(duration, self.settings.prec);
                             let duration = duration.to_string();
-                            let parts: Vec<&str> = duration.split(".").collect();
+                            let parts: Vec<&str> = duration.split('.').collect();
                             if parts.len() == 1 {
-                                if self.state.prec > 0 {
+                                if self.settings.prec > 0 {
                                     if parts[0] == "0" {
                                         "0".to_string()
                                     } else {
                                         let mut zeros = "".to_string();
-                                        for _ in 0..self.state.prec {
-                                            zeros.push_str("0");
+                                        for _ in 0..self.settings.prec {
+                                            zeros.push('0');
                                         }
                                         format!("{}.{}", parts[0], zeros)
                                     }
@@ -1367,10 +3265,10 @@ impl eframe::App for Cubism {
                             } else {
                                 let main = parts[0].to_string();
                                 let mut dec = parts[1].to_string();
-                                if dec.len() < self.state.prec {
+                                if dec.len() < self.settings.prec {
                                     loop {
-                                        if dec.len() != self.state.prec {
-                                            dec.push_str("0");
+                                        if dec.len() != self.settings.prec {
+                                            dec.push('0');
                                         } else {
                                             break;
                                         }
@@ -1380,11 +3278,20 @@ impl eframe::App for Cubism {
                             }
                         };
 
-                        ui.heading(format!(
-                            "{}", secs
-                        ));
+                        match self.state.memo {
+                            Some(memo) => ui.heading(format!("{}\nMemo: {}", secs, round(memo, self.settings.prec))),
+                            None if !self.state.splits.is_empty() => {
+                                let splits: Vec<String> = relay::splits(&self.state.splits)
+                                    .iter()
+                                    .zip(&self.state.relay)
+                                    .map(|(split, part)| format!("{}: {}", part.puzzle, round(*split, self.settings.prec)))
+                                    .collect();
+                                ui.heading(format!("{}\n{}", secs, splits.join("  ")))
+                            }
+                            None => ui.heading(secs.to_string()),
+                        };
                     } else {
-                        ui.heading(format!("{}", self.state.time));
+                        ui.heading(&self.state.time);
                     }
                     ui.style_mut().text_styles.get_mut(&egui::TextStyle::Heading).unwrap().size = 30.0;
                 },
@@ -0,0 +1,97 @@
+// Cache
+
+/// Start of every cache file, followed by the version of its tables and a checksum of them.
+const MAGIC: &[u8; 4] = b"CBSM";
+
+/// Length of the header before the tables.
+const HEADER: usize = MAGIC.len() + 4 + 8;
+
+/// FNV-1a hash of the tables, so a file that was cut short or changed isn't trusted.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The tables with a header saying what they are.
+fn with_header(version: u32, bytes: &[u8]) -> Vec<u8> {
+    let mut file = Vec::with_capacity(HEADER + bytes.len());
+    file.extend_from_slice(MAGIC);
+    file.extend_from_slice(&version.to_le_bytes());
+    file.extend_from_slice(&checksum(bytes).to_le_bytes());
+    file.extend_from_slice(bytes);
+    file
+}
+
+/// The tables in a file, if it was written for `version` and hasn't been damaged since.
+fn without_header(version: u32, mut file: Vec<u8>) -> Option<Vec<u8>> {
+    if file.len() < HEADER || &file[..4] != MAGIC || file[4..8] != version.to_le_bytes() {
+        return None;
+    }
+    let sum = u64::from_le_bytes(file[8..HEADER].try_into().ok()?);
+    file.drain(..HEADER);
+    (checksum(&file) == sum).then_some(file)
+}
+
+/// Tables that are slow to generate are saved next to the app data, so they are only made once.
+#[cfg(not(target_arch = "wasm32"))]
+fn path(name: &str) -> std::path::PathBuf {
+    eframe::storage_dir("Cubism")
+        .unwrap_or_else(std::env::temp_dir)
+        .join(name)
+}
+
+/// Reads tables saved by `write`, ignoring ones saved for another version of them.
+pub fn read(name: &str, version: u32) -> Option<Vec<u8>> {
+    without_header(version, read_file(name)?)
+}
+
+pub fn write(name: &str, version: u32, bytes: &[u8]) {
+    write_file(name, &with_header(version, bytes));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_file(name: &str) -> Option<Vec<u8>> {
+    std::fs::read(path(name)).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_file(name: &str, file: &[u8]) {
+    let path = path(name);
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    // The cache only saves time, so failing to write it is fine
+    let _ = std::fs::write(path, file);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_file(_name: &str) -> Option<Vec<u8>> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_file(_name: &str, _file: &[u8]) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_what_it_wrote() {
+        let file = with_header(2, &[1, 2, 3]);
+        assert_eq!(without_header(2, file), Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn ignores_other_versions_and_damaged_files() {
+        let file = with_header(2, &[1, 2, 3]);
+        assert_eq!(without_header(1, file.clone()), None);
+        let mut damaged = file.clone();
+        *damaged.last_mut().unwrap() = 4;
+        assert_eq!(without_header(2, damaged), None);
+        assert_eq!(without_header(2, file[..file.len() - 1].to_vec()), None);
+        // Caches from before the header was added
+        assert_eq!(without_header(2, vec![1, 2, 3]), None);
+    }
+}
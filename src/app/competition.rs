@@ -0,0 +1,409 @@
+use chrono::{DateTime, Local};
+
+use super::event::Event;
+use super::scramble::{Cubes, Scrambler};
+use super::stats;
+
+// Competition
+
+/// How a round is ranked, like the WCA does for each event.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    /// Five attempts, ranked by the average of the middle three.
+    AverageOf5,
+    /// Three attempts, ranked by the best, as for blindfolded events.
+    BestOf3,
+}
+
+impl Format {
+    pub fn of(event: Event) -> Format {
+        if event.is_bld() {
+            Format::BestOf3
+        } else {
+            Format::AverageOf5
+        }
+    }
+
+    /// Number of attempts in a round.
+    pub fn attempts(&self) -> usize {
+        match self {
+            Format::AverageOf5 => 5,
+            Format::BestOf3 => 3,
+        }
+    }
+
+    /// Attempts that have to beat the cutoff for the rest to be done.
+    pub fn cutoff_attempts(&self) -> usize {
+        match self {
+            Format::AverageOf5 => 2,
+            Format::BestOf3 => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::AverageOf5 => write!(f, "Average of 5"),
+            Format::BestOf3 => write!(f, "Best of 3"),
+        }
+    }
+}
+
+/// Typical cutoff and time limit of an event in seconds, with no cutoff for events that rarely have one.
+pub fn default_limits(event: Event) -> (Option<f64>, f64) {
+    if event.is_bld() {
+        return (None, 600.0 * (event.puzzle.size() - 2) as f64);
+    }
+    if event.is_relay() {
+        return (None, 600.0);
+    }
+    match (event.puzzle, event.variant) {
+        (Cubes::OneByOne | Cubes::TwoByTwo, _) => (None, 60.0),
+        (Cubes::ThreeByThree, super::event::Variant::Standard) => (None, 600.0),
+        (Cubes::ThreeByThree, _) => (Some(60.0), 300.0),
+        (Cubes::FourByFour, _) => (Some(120.0), 300.0),
+        (Cubes::FiveByFive, _) => (Some(180.0), 420.0),
+    }
+}
+
+/// Writes a result as WCA Live does, such as "12.34", "1:02.50" or "DNF".
+pub fn format_result(result: Option<f64>) -> String {
+    let Some(seconds) = result else {
+        return "DNF".to_string();
+    };
+    let centiseconds = (seconds * 100.0).round() as u64;
+    let minutes = centiseconds / 6000;
+    let rest = centiseconds % 6000;
+    if minutes > 0 {
+        format!("{}:{:02}.{:02}", minutes, rest / 100, rest % 100)
+    } else {
+        format!("{}.{:02}", rest / 100, rest % 100)
+    }
+}
+
+/// An attempt of a round, its time including any +2.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Debug)]
+pub struct Attempt {
+    pub time: f64,
+    pub plus2: bool,
+    pub dnf: bool,
+}
+
+impl Attempt {
+    pub fn result(&self) -> Option<f64> {
+        if self.dnf {
+            None
+        } else {
+            Some(self.time)
+        }
+    }
+}
+
+/// A simulated round: a fixed set of scrambles and the attempts done on them.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+pub struct Round {
+    pub date: DateTime<Local>,  // When the round started
+    pub event: Event,           // Event of the round
+    pub format: Format,         // How the round is ranked
+    pub scrambles: Vec<String>, // Scramble of each attempt
+    pub cutoff: Option<f64>,    // Time the first attempts have to beat for the rest to be done
+    pub time_limit: f64,        // Time after which an attempt is a DNF
+    pub attempts: Vec<Attempt>, // Attempts done so far
+}
+
+impl Round {
+    /// Starts a round, generating every scramble up front like at a competition.
+    pub fn new(event: Event, cutoff: Option<f64>, time_limit: f64) -> Self {
+        let format = Format::of(event);
+        let scrambler = Scrambler::from(event);
+        Self {
+            date: Local::now(),
+            event,
+            format,
+            scrambles: (0..format.attempts())
+                .map(|_| scrambler.scramble())
+                .collect(),
+            cutoff,
+            time_limit,
+            attempts: vec![],
+        }
+    }
+
+    /// Whether the attempts needed to make the cutoff beat it, or would still be able to.
+    pub fn made_cutoff(&self) -> bool {
+        let Some(cutoff) = self.cutoff else {
+            return true;
+        };
+        let needed = self.format.cutoff_attempts();
+        self.attempts.len() < needed
+            || self.attempts[..needed]
+                .iter()
+                .any(|attempt| attempt.result().map_or(false, |time| time < cutoff))
+    }
+
+    /// Whether every attempt is done, or the cutoff was missed.
+    pub fn is_finished(&self) -> bool {
+        self.attempts.len() >= self.format.attempts() || !self.made_cutoff()
+    }
+
+    /// Scramble of the next attempt, if the round isn't over.
+    pub fn scramble(&self) -> Option<&String> {
+        if self.is_finished() {
+            None
+        } else {
+            self.scrambles.get(self.attempts.len())
+        }
+    }
+
+    /// Records an attempt, over the time limit being a DNF.
+    ///
+    /// Times are truncated to hundredths, as the WCA does.
+    pub fn record(&mut self, time: f64, plus2: bool, dnf: bool) {
+        self.attempts.push(Attempt {
+            time: (time * 100.0).floor() / 100.0,
+            plus2,
+            dnf: dnf || time >= self.time_limit,
+        });
+    }
+
+    pub fn best(&self) -> Option<f64> {
+        self.attempts
+            .iter()
+            .filter_map(Attempt::result)
+            .reduce(f64::min)
+    }
+
+    /// The average or mean of the round, `None` if it doesn't have one and `Some(None)` for a DNF.
+    ///
+    /// A round cut off before all its attempts has none, like at a competition.
+    pub fn average(&self) -> Option<Option<f64>> {
+        if self.attempts.len() < self.format.attempts() {
+            return None;
+        }
+        let results: Vec<Option<f64>> = self.attempts.iter().map(Attempt::result).collect();
+        // Nothing is trimmed from a mean of 3, so this is the mean of a best of 3
+        Some(stats::average_of(&results).map(|average| (average * 100.0).round() / 100.0))
+    }
+
+    /// Results of each attempt as on a result sheet, the best and worst of an average in brackets.
+    pub fn results(&self) -> Vec<String> {
+        let mut results: Vec<String> = self
+            .attempts
+            .iter()
+            .map(|attempt| format_result(attempt.result()))
+            .collect();
+        if self.format == Format::AverageOf5 && self.average().is_some() {
+            let key = |i: &usize| self.attempts[*i].result().unwrap_or(f64::INFINITY);
+            let indices: Vec<usize> = (0..self.attempts.len()).collect();
+            let best = indices.iter().min_by(|a, b| key(a).total_cmp(&key(b)));
+            let worst = indices.iter().max_by(|a, b| key(a).total_cmp(&key(b)));
+            for i in [best, worst].into_iter().flatten() {
+                results[*i] = format!("({})", results[*i]);
+            }
+        }
+        // Attempts skipped after missing the cutoff are left empty
+        results.resize(self.format.attempts(), "".to_string());
+        results
+    }
+}
+
+/// Settings of the next round, the round being done and past rounds.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Competition {
+    pub event: Event,         // Event the cutoff and time limit are set for
+    pub cutoff: Option<f64>,  // Cutoff of the next round, in seconds
+    pub time_limit: f64,      // Time limit of the next round, in seconds
+    pub round: Option<Round>, // Round being done
+    pub rounds: Vec<Round>,   // Every finished round, oldest first
+}
+
+impl Default for Competition {
+    fn default() -> Self {
+        let event = Event::default();
+        let (cutoff, time_limit) = default_limits(event);
+        Self {
+            event,
+            cutoff,
+            time_limit,
+            round: None,
+            rounds: vec![],
+        }
+    }
+}
+
+impl Competition {
+    /// Uses the usual cutoff and time limit of `event`, if they were set for another event.
+    pub fn set_event(&mut self, event: Event) {
+        if self.event != event {
+            self.event = event;
+            (self.cutoff, self.time_limit) = default_limits(event);
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.round = Some(Round::new(self.event, self.cutoff, self.time_limit));
+    }
+
+    /// The round being done, while it still has attempts left.
+    pub fn running(&self) -> Option<&Round> {
+        self.round.as_ref().filter(|round| !round.is_finished())
+    }
+
+    /// Records an attempt of the running round, moving it to the past rounds once it's over.
+    pub fn record(&mut self, time: f64, plus2: bool, dnf: bool) {
+        if let Some(round) = self.round.as_mut() {
+            round.record(time, plus2, dnf);
+            if round.is_finished() {
+                self.rounds.push(round.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::event::{Variant, EVENTS};
+
+    fn round(format: Format, cutoff: Option<f64>) -> Round {
+        Round {
+            date: Local::now(),
+            event: Event::default(),
+            format,
+            scrambles: vec!["R U R'".to_string(); format.attempts()],
+            cutoff,
+            time_limit: 600.0,
+            attempts: vec![],
+        }
+    }
+
+    fn recorded(format: Format, cutoff: Option<f64>, times: &[Option<f64>]) -> Round {
+        let mut round = round(format, cutoff);
+        for time in times {
+            round.record(time.unwrap_or(0.0), false, time.is_none());
+        }
+        round
+    }
+
+    #[test]
+    fn one_of_the_first_attempts_has_to_beat_the_cutoff() {
+        let ao5 = |times: &[Option<f64>]| recorded(Format::AverageOf5, Some(60.0), times);
+        assert!(ao5(&[]).made_cutoff());
+        assert!(ao5(&[Some(61.0)]).made_cutoff());
+        assert!(ao5(&[Some(61.0), Some(59.99)]).made_cutoff());
+        assert!(ao5(&[None, Some(50.0)]).made_cutoff());
+        // Equalling the cutoff doesn't beat it
+        let missed = ao5(&[Some(60.0), None]);
+        assert!(!missed.made_cutoff());
+        assert!(missed.is_finished());
+        assert_eq!(missed.scramble(), None);
+
+        let bo3 = |times: &[Option<f64>]| recorded(Format::BestOf3, Some(60.0), times);
+        assert!(!bo3(&[Some(70.0)]).made_cutoff());
+        assert!(bo3(&[Some(50.0), Some(70.0)]).made_cutoff());
+        assert!(recorded(Format::AverageOf5, None, &[None, None]).made_cutoff());
+    }
+
+    #[test]
+    fn attempts_are_truncated_and_over_the_limit_are_dnfs() {
+        let mut round = round(Format::AverageOf5, None);
+        round.record(12.349, false, false);
+        round.record(599.999, false, false);
+        round.record(600.0, false, false);
+        round.record(8.5, true, false);
+        let results: Vec<Option<f64>> = round.attempts.iter().map(Attempt::result).collect();
+        assert_eq!(results, vec![Some(12.34), Some(599.99), None, Some(8.5)]);
+        assert!(round.attempts[3].plus2);
+        assert_eq!(round.best(), Some(8.5));
+        assert_eq!(round.scramble(), Some(&"R U R'".to_string()));
+    }
+
+    #[test]
+    fn averages_of_5_trim_and_means_of_3_do_not() {
+        let ao5 = |times: &[Option<f64>]| recorded(Format::AverageOf5, None, times).average();
+        assert_eq!(ao5(&[Some(10.0), Some(12.0), Some(11.0), Some(13.0)]), None);
+        assert_eq!(
+            ao5(&[Some(10.0), Some(12.0), Some(11.0), None, Some(13.0)]),
+            Some(Some(12.0))
+        );
+        assert_eq!(
+            ao5(&[Some(10.0), None, Some(11.0), None, Some(13.0)]),
+            Some(None)
+        );
+
+        let mo3 = |times: &[Option<f64>]| recorded(Format::BestOf3, None, times).average();
+        assert_eq!(
+            mo3(&[Some(30.0), Some(31.0), Some(32.01)]),
+            Some(Some(31.0))
+        );
+        assert_eq!(mo3(&[Some(30.0), None, Some(29.0)]), Some(None));
+        assert_eq!(
+            recorded(Format::BestOf3, None, &[Some(30.0), None, Some(29.0)]).best(),
+            Some(29.0)
+        );
+    }
+
+    #[test]
+    fn results_bracket_the_best_and_worst_of_an_average() {
+        let results =
+            |format, cutoff, times: &[Option<f64>]| recorded(format, cutoff, times).results();
+        assert_eq!(
+            results(
+                Format::AverageOf5,
+                None,
+                &[Some(10.0), None, Some(62.5), Some(9.0), Some(11.0)]
+            ),
+            ["10.00", "(DNF)", "1:02.50", "(9.00)", "11.00"]
+        );
+        // Of equal results, the first is the best and the last the worst
+        assert_eq!(
+            results(Format::AverageOf5, None, &[Some(10.0); 5]),
+            ["(10.00)", "10.00", "10.00", "10.00", "(10.00)"]
+        );
+        assert_eq!(
+            results(Format::AverageOf5, Some(60.0), &[Some(70.0), None]),
+            ["1:10.00", "DNF", "", "", ""]
+        );
+        assert_eq!(
+            results(Format::BestOf3, None, &[Some(70.0), None, Some(65.0)]),
+            ["1:10.00", "DNF", "1:05.00"]
+        );
+    }
+
+    #[test]
+    fn limits_follow_the_event() {
+        let limits = |puzzle, variant| default_limits(Event::new(puzzle, variant));
+        assert_eq!(limits(Cubes::TwoByTwo, Variant::Standard), (None, 60.0));
+        assert_eq!(
+            limits(Cubes::ThreeByThree, Variant::Standard),
+            (None, 600.0)
+        );
+        assert_eq!(
+            limits(Cubes::ThreeByThree, Variant::OneHanded),
+            (Some(60.0), 300.0)
+        );
+        assert_eq!(
+            limits(Cubes::FourByFour, Variant::Standard),
+            (Some(120.0), 300.0)
+        );
+        assert_eq!(
+            limits(Cubes::FiveByFive, Variant::Standard),
+            (Some(180.0), 420.0)
+        );
+        assert_eq!(
+            limits(Cubes::ThreeByThree, Variant::Blindfolded),
+            (None, 600.0)
+        );
+        assert_eq!(
+            limits(Cubes::FiveByFive, Variant::Blindfolded),
+            (None, 1800.0)
+        );
+        assert_eq!(default_limits(EVENTS[9]), (None, 600.0));
+        assert_eq!(
+            Format::of(Event::new(Cubes::FourByFour, Variant::Blindfolded)),
+            Format::BestOf3
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use cubesim::{Cube, Face, FaceletCube, Move};
+
+// Cubie Cube
+
+/// Facelets of each corner, starting with its U or D sticker and going clockwise.
+pub const CORNER_FACELETS: [[usize; 3]; 8] = [
+    [8, 9, 20],
+    [6, 18, 38],
+    [0, 36, 47],
+    [2, 45, 11],
+    [29, 26, 15],
+    [27, 44, 24],
+    [33, 53, 42],
+    [35, 17, 51],
+];
+
+/// Facelets of each edge, starting with its U, D, F or B sticker.
+const EDGE_FACELETS: [[usize; 2]; 12] = [
+    [5, 10],
+    [7, 19],
+    [3, 37],
+    [1, 46],
+    [32, 16],
+    [28, 25],
+    [30, 43],
+    [34, 52],
+    [23, 12],
+    [21, 41],
+    [50, 39],
+    [48, 14],
+];
+
+/// Faces each corner belongs to, in the same order as its facelets.
+const CORNER_COLOURS: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [0, 2, 4],
+    [0, 4, 5],
+    [0, 5, 1],
+    [3, 2, 1],
+    [3, 4, 2],
+    [3, 5, 4],
+    [3, 1, 5],
+];
+
+/// Faces each edge belongs to, in the same order as its facelets.
+const EDGE_COLOURS: [[usize; 2]; 12] = [
+    [0, 1],
+    [0, 2],
+    [0, 4],
+    [0, 5],
+    [3, 1],
+    [3, 2],
+    [3, 4],
+    [3, 5],
+    [2, 1],
+    [2, 4],
+    [5, 4],
+    [5, 1],
+];
+
+/// A 3x3 cube described by where each piece is and how it is twisted.
+///
+/// `cp[i]` is the corner sitting in position `i`, and `co[i]` its twist, with
+/// the same for edges. Centers are fixed, so rotations are not represented.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CubieCube {
+    pub cp: [u8; 8],
+    pub co: [u8; 8],
+    pub ep: [u8; 12],
+    pub eo: [u8; 12],
+}
+
+impl Default for CubieCube {
+    fn default() -> Self {
+        Self {
+            cp: [0, 1, 2, 3, 4, 5, 6, 7],
+            co: [0; 8],
+            ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            eo: [0; 12],
+        }
+    }
+}
+
+/// Quarter turns of each face, clockwise, in the order U, R, F, D, L, B.
+const FACE_TURNS: [CubieCube; 6] = [
+    CubieCube {
+        cp: [3, 0, 1, 2, 4, 5, 6, 7],
+        co: [0, 0, 0, 0, 0, 0, 0, 0],
+        ep: [3, 0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [4, 1, 2, 0, 7, 5, 6, 3],
+        co: [2, 0, 0, 1, 1, 0, 0, 2],
+        ep: [8, 1, 2, 3, 11, 5, 6, 7, 4, 9, 10, 0],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [1, 5, 2, 3, 0, 4, 6, 7],
+        co: [1, 2, 0, 0, 2, 1, 0, 0],
+        ep: [0, 9, 2, 3, 4, 8, 6, 7, 1, 5, 10, 11],
+        eo: [0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0],
+    },
+    CubieCube {
+        cp: [0, 1, 2, 3, 5, 6, 7, 4],
+        co: [0, 0, 0, 0, 0, 0, 0, 0],
+        ep: [0, 1, 2, 3, 5, 6, 7, 4, 8, 9, 10, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [0, 2, 6, 3, 4, 1, 5, 7],
+        co: [0, 1, 2, 0, 0, 2, 1, 0],
+        ep: [0, 1, 10, 3, 4, 5, 9, 7, 8, 2, 6, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [0, 1, 3, 7, 4, 5, 2, 6],
+        co: [0, 0, 1, 2, 0, 0, 2, 1],
+        ep: [0, 1, 2, 11, 4, 5, 6, 10, 8, 9, 3, 7],
+        eo: [0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 1, 1],
+    },
+];
+
+/// A face turn, `face` being 0 to 5 for U, R, F, D, L, B and `power` the number of clockwise quarter turns (1 to 3).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Turn {
+    pub face: u8,
+    pub power: u8,
+}
+
+impl Turn {
+    /// All 18 face turns, indexed by `face * 3 + power - 1`.
+    pub fn all() -> impl Iterator<Item = Turn> {
+        (0..18).map(Turn::from_index)
+    }
+
+    pub fn from_index(index: usize) -> Turn {
+        Turn {
+            face: (index / 3) as u8,
+            power: (index % 3) as u8 + 1,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.face as usize * 3 + self.power as usize - 1
+    }
+}
+
+impl CubieCube {
+    /// The cube after doing `other` to this one.
+    pub fn multiply(&self, other: &CubieCube) -> CubieCube {
+        let mut result = CubieCube::default();
+        for i in 0..8 {
+            let from = other.cp[i] as usize;
+            result.cp[i] = self.cp[from];
+            result.co[i] = (self.co[from] + other.co[i]) % 3;
+        }
+        for i in 0..12 {
+            let from = other.ep[i] as usize;
+            result.ep[i] = self.ep[from];
+            result.eo[i] = (self.eo[from] + other.eo[i]) % 2;
+        }
+        result
+    }
+
+    pub fn turn(&self, turn: Turn) -> CubieCube {
+        let mut cube = *self;
+        for _ in 0..turn.power {
+            cube = cube.multiply(&FACE_TURNS[turn.face as usize]);
+        }
+        cube
+    }
+
+    pub fn turns(&self, turns: &[Turn]) -> CubieCube {
+        turns.iter().fold(*self, |cube, turn| cube.turn(*turn))
+    }
+
+    /// Reads the pieces off a 3x3 `cubesim` cube, using its centers as the reference.
+    ///
+    /// Any moves, including rotations and wide turns, may have been applied to it.
+    /// Returns `None` if the stickers don't make up a real cube.
+    pub fn from_facelets(cube: &FaceletCube) -> Option<CubieCube> {
+        if cube.size() != 3 {
+            return None;
+        }
+        let state = cube.state();
+        // Stickers are named by the face whose center has the same colour
+        let centers: Vec<Face> = (0..6).map(|face| state[face * 9 + 4]).collect();
+        let mut facelets = [0usize; 54];
+        for (i, sticker) in state.iter().enumerate() {
+            facelets[i] = centers.iter().position(|center| center == sticker)?;
+        }
+        CubieCube::from_facelet_faces(&facelets)
+    }
+
+    /// Reads only the corners off the stickers, leaving the edges solved, as for a 2x2.
+    ///
+    /// Only the corner stickers need to be filled in.
+    pub fn from_corner_faces(facelets: &[usize; 54]) -> Option<CubieCube> {
+        let mut cube = CubieCube::default();
+        for (i, positions) in CORNER_FACELETS.iter().enumerate() {
+            let twist =
+                (0..3).find(|&o| facelets[positions[o]] == 0 || facelets[positions[o]] == 3)?;
+            let first = facelets[positions[(twist + 1) % 3]];
+            let second = facelets[positions[(twist + 2) % 3]];
+            let piece = CORNER_COLOURS
+                .iter()
+                .position(|colours| colours[1] == first && colours[2] == second)?;
+            cube.cp[i] = piece as u8;
+            cube.co[i] = twist as u8;
+        }
+        let mut corners = cube.cp;
+        corners.sort();
+        if corners == [0, 1, 2, 3, 4, 5, 6, 7]
+            && cube.co.iter().map(|&o| o as u32).sum::<u32>() % 3 == 0
+        {
+            Some(cube)
+        } else {
+            None
+        }
+    }
+
+    /// Reads the pieces off 54 stickers, each given as the index of its face in the order U, R, F, D, L, B.
+    pub fn from_facelet_faces(facelets: &[usize; 54]) -> Option<CubieCube> {
+        let mut cube = CubieCube::from_corner_faces(facelets)?;
+        for (i, positions) in EDGE_FACELETS.iter().enumerate() {
+            let stickers = [facelets[positions[0]], facelets[positions[1]]];
+            let (piece, flip) = EDGE_COLOURS.iter().enumerate().find_map(|(j, colours)| {
+                if *colours == stickers {
+                    Some((j, 0))
+                } else if [colours[1], colours[0]] == stickers {
+                    Some((j, 1))
+                } else {
+                    None
+                }
+            })?;
+            cube.ep[i] = piece as u8;
+            cube.eo[i] = flip;
+        }
+        if cube.is_valid() {
+            Some(cube)
+        } else {
+            None
+        }
+    }
+
+    /// Whether every piece appears once, and the twists, flips and parities are solvable.
+    pub fn is_valid(&self) -> bool {
+        let mut corners = self.cp;
+        corners.sort();
+        let mut edges = self.ep;
+        edges.sort();
+        corners == [0, 1, 2, 3, 4, 5, 6, 7]
+            && edges == [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]
+            && self.co.iter().map(|&o| o as u32).sum::<u32>() % 3 == 0
+            && self.eo.iter().map(|&o| o as u32).sum::<u32>() % 2 == 0
+            && parity(&self.cp) == parity(&self.ep)
+    }
+}
+
+/// Whether a permutation is odd.
+pub fn parity(permutation: &[u8]) -> bool {
+    let mut odd = false;
+    for i in 0..permutation.len() {
+        for j in i + 1..permutation.len() {
+            if permutation[j] < permutation[i] {
+                odd = !odd;
+            }
+        }
+    }
+    odd
+}
+
+/// Whether `turn` may follow `last`, skipping turns of the same face and
+/// allowing opposite faces in one order only.
+pub fn allowed(last: Option<Turn>, turn: Turn) -> bool {
+    match last {
+        Some(last) => turn.face != last.face && turn.face + 3 != last.face,
+        None => true,
+    }
+}
+
+/// Applies a scramble to a solved cube, returning `None` if it can't be read as 3x3 moves.
+pub fn from_moves(moves: &[Move]) -> Option<CubieCube> {
+    CubieCube::from_facelets(&FaceletCube::new(3).apply_moves(moves))
+}
@@ -0,0 +1,168 @@
+use std::fmt::Display;
+
+use super::scramble::Cubes;
+
+// Events
+
+/// How a puzzle is solved, making it an event of its own with separate statistics.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub enum Variant {
+    Standard,
+    OneHanded,
+    Feet,
+    Blindfolded,
+    /// Several puzzles solved one after another as a single solve, bit `n` being set for the n x n.
+    Relay(u8),
+}
+
+/// Puzzles a relay can be made of, in the order they are solved.
+pub const RELAY_PUZZLES: [Cubes; 4] = [
+    Cubes::TwoByTwo,
+    Cubes::ThreeByThree,
+    Cubes::FourByFour,
+    Cubes::FiveByFive,
+];
+
+/// A puzzle and the way it is solved, such as 3x3 OH or 4BLD.
+///
+/// Solves are attributed to an event, so that averages and personal bests of
+/// different events are never mixed. The puzzle of a relay is its biggest one.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct Event {
+    pub puzzle: Cubes,
+    pub variant: Variant,
+}
+
+/// Every event a session can be set to.
+pub const EVENTS: [Event; 10] = [
+    Event::new(Cubes::TwoByTwo, Variant::Standard),
+    Event::new(Cubes::ThreeByThree, Variant::Standard),
+    Event::new(Cubes::FourByFour, Variant::Standard),
+    Event::new(Cubes::FiveByFive, Variant::Standard),
+    Event::new(Cubes::ThreeByThree, Variant::OneHanded),
+    Event::new(Cubes::ThreeByThree, Variant::Feet),
+    Event::new(Cubes::ThreeByThree, Variant::Blindfolded),
+    Event::new(Cubes::FourByFour, Variant::Blindfolded),
+    Event::new(Cubes::FiveByFive, Variant::Blindfolded),
+    // 2x2, 3x3 and 4x4
+    Event::new(Cubes::FourByFour, Variant::Relay(0b11100)),
+];
+
+impl Default for Event {
+    fn default() -> Self {
+        Event::new(Cubes::ThreeByThree, Variant::Standard)
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.variant {
+            Variant::Standard => write!(f, "{}", self.puzzle),
+            Variant::OneHanded => write!(f, "{} OH", self.puzzle),
+            Variant::Feet => write!(f, "{} Feet", self.puzzle),
+            // Named like the WCA does, such as "3BLD"
+            Variant::Blindfolded => write!(f, "{}BLD", self.puzzle.size()),
+            Variant::Relay(_) => {
+                let sizes: Vec<String> = self
+                    .puzzles()
+                    .iter()
+                    .map(|puzzle| puzzle.size().to_string())
+                    .collect();
+                write!(f, "{} Relay", sizes.join("-"))
+            }
+        }
+    }
+}
+
+impl Event {
+    pub const fn new(puzzle: Cubes, variant: Variant) -> Self {
+        Self { puzzle, variant }
+    }
+
+    /// A relay of the given puzzles, `None` if there are fewer than two of them.
+    pub fn relay(puzzles: &[Cubes]) -> Option<Self> {
+        if puzzles.len() < 2 {
+            return None;
+        }
+        let biggest = *puzzles.iter().max_by_key(|puzzle| puzzle.size())?;
+        let mask = puzzles
+            .iter()
+            .fold(0, |mask, puzzle| mask | (1 << puzzle.size()));
+        Some(Event::new(biggest, Variant::Relay(mask)))
+    }
+
+    /// Puzzles solved in the event, several for a relay.
+    pub fn puzzles(&self) -> Vec<Cubes> {
+        match self.variant {
+            Variant::Relay(mask) => RELAY_PUZZLES
+                .into_iter()
+                .filter(|puzzle| mask & (1 << puzzle.size()) != 0)
+                .collect(),
+            _ => vec![self.puzzle],
+        }
+    }
+
+    pub fn is_relay(&self) -> bool {
+        matches!(self.variant, Variant::Relay(_))
+    }
+
+    /// Whether the event is solved blindfolded, timing memorisation and execution.
+    pub fn is_bld(&self) -> bool {
+        self.variant == Variant::Blindfolded
+    }
+
+    /// Whether solves of the event start with inspection, which blindfolded solves don't
+    /// have since memorisation is part of the time.
+    pub fn inspection(&self) -> bool {
+        !self.is_bld()
+    }
+
+    /// Whether 3x3 scrambles of the event can leave part of the cube solved for practice.
+    pub fn has_subsets(&self) -> bool {
+        self.puzzle == Cubes::ThreeByThree && !self.is_bld() && !self.is_relay()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relays_set_a_bit_for_each_size() {
+        let relay = Event::relay(&[Cubes::FourByFour, Cubes::TwoByTwo, Cubes::ThreeByThree]);
+        assert_eq!(relay, Some(EVENTS[9]));
+        assert_eq!(relay.unwrap().variant, Variant::Relay(0b11100));
+        assert_eq!(relay.unwrap().puzzle, Cubes::FourByFour);
+        assert_eq!(
+            Event::relay(&[Cubes::FiveByFive, Cubes::TwoByTwo])
+                .unwrap()
+                .variant,
+            Variant::Relay(0b100100)
+        );
+        assert_eq!(Event::relay(&[Cubes::ThreeByThree]), None);
+    }
+
+    #[test]
+    fn relays_are_solved_smallest_first() {
+        let relay = Event::new(Cubes::FiveByFive, Variant::Relay(0b101100));
+        assert_eq!(
+            relay.puzzles(),
+            vec![Cubes::TwoByTwo, Cubes::ThreeByThree, Cubes::FiveByFive]
+        );
+        assert_eq!(relay.to_string(), "2-3-5 Relay");
+        assert!(relay.is_relay() && relay.inspection() && !relay.has_subsets());
+        assert_eq!(
+            Event::new(Cubes::FourByFour, Variant::Blindfolded).puzzles(),
+            vec![Cubes::FourByFour]
+        );
+    }
+
+    #[test]
+    fn events_with_the_same_puzzle_differ() {
+        let bld = Event::new(Cubes::ThreeByThree, Variant::Blindfolded);
+        assert_ne!(bld, Event::default());
+        assert_eq!(bld.to_string(), "3BLD");
+        assert!(bld.is_bld() && !bld.inspection() && !bld.has_subsets());
+        assert!(Event::new(Cubes::ThreeByThree, Variant::OneHanded).has_subsets());
+    }
+}
@@ -0,0 +1,125 @@
+use chrono::NaiveDate;
+
+use super::event::Event;
+use super::stats;
+use super::SolveStats;
+
+// Filter
+
+/// Which penalties a filtered solve may have.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Debug)]
+pub enum Penalty {
+    Any,
+    NoPenalty,
+    Plus2,
+    Dnf,
+}
+
+/// Narrows down the solves shown in the solve list and used for statistics.
+///
+/// Empty text fields don't filter anything, so the default filter matches every solve.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct SolveFilter {
+    pub search: String,       // Text the scramble has to contain
+    pub comment: String,      // Text the comment has to contain
+    pub tag: String,          // Tag the solve has to have
+    pub from: String,         // First date, as YYYY-MM-DD
+    pub to: String,           // Last date, as YYYY-MM-DD
+    pub min_time: String,     // Fastest time in seconds
+    pub max_time: String,     // Slowest time in seconds
+    pub event: Option<Event>, // Event the solve was done in
+    pub penalty: Penalty,     // Penalty the solve has
+}
+
+impl Default for SolveFilter {
+    fn default() -> Self {
+        Self {
+            search: "".to_string(),
+            comment: "".to_string(),
+            tag: "".to_string(),
+            from: "".to_string(),
+            to: "".to_string(),
+            min_time: "".to_string(),
+            max_time: "".to_string(),
+            event: None,
+            penalty: Penalty::Any,
+        }
+    }
+}
+
+/// Parses a date as typed in the filter, `None` when it is empty.
+pub fn parse_date(text: &str) -> Result<Option<NaiveDate>, chrono::ParseError> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").map(Some)
+}
+
+/// Parses a time in seconds as typed in the filter, `None` when it is empty.
+pub fn parse_time(text: &str) -> Result<Option<f64>, std::num::ParseFloatError> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    text.trim().parse().map(Some)
+}
+
+impl SolveFilter {
+    /// Whether the filter would hide anything at all.
+    pub fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+
+    pub fn matches(&self, solve: &SolveStats) -> bool {
+        let contains = |text: &str, part: &str| {
+            part.trim().is_empty() || text.to_lowercase().contains(&part.trim().to_lowercase())
+        };
+        if !contains(&solve.scramble, &self.search) || !contains(&solve.comment, &self.comment) {
+            return false;
+        }
+        if !self.tag.trim().is_empty()
+            && !solve
+                .tags
+                .iter()
+                .any(|tag| tag.eq_ignore_ascii_case(self.tag.trim()))
+        {
+            return false;
+        }
+        if self.event.map_or(false, |event| event != solve.event()) {
+            return false;
+        }
+        let penalty = match self.penalty {
+            Penalty::Any => true,
+            Penalty::NoPenalty => !solve.plus2 && !solve.dnf,
+            Penalty::Plus2 => solve.plus2,
+            Penalty::Dnf => solve.dnf,
+        };
+        if !penalty {
+            return false;
+        }
+        // Fields that don't parse are shown as errors, and ignored here
+        let date = stats::solve_date(solve);
+        if let Ok(Some(from)) = parse_date(&self.from) {
+            if date.map_or(true, |date| date < from) {
+                return false;
+            }
+        }
+        if let Ok(Some(to)) = parse_date(&self.to) {
+            if date.map_or(true, |date| date > to) {
+                return false;
+            }
+        }
+        let time: f64 = solve.time.parse().unwrap_or(0.0);
+        if let Ok(Some(min)) = parse_time(&self.min_time) {
+            if solve.dnf || time < min {
+                return false;
+            }
+        }
+        if let Ok(Some(max)) = parse_time(&self.max_time) {
+            if solve.dnf || time > max {
+                return false;
+            }
+        }
+        true
+    }
+}
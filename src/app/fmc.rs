@@ -0,0 +1,260 @@
+use chrono::{DateTime, Local};
+use cubesim::{Cube, FaceletCube};
+
+use super::notation::{self, Kind, Metric};
+use super::stats;
+
+// Fewest Moves
+
+/// Most moves a solution may have, and seconds allowed to find it.
+pub const MAX_MOVES: usize = 80;
+pub const TIME_LIMIT: f64 = 3600.0;
+
+/// Checks that `solution` solves `scramble`, returning its length in HTM.
+///
+/// Like the WCA rules, rotations are free and wide turns count as one move,
+/// while slices and inner layer turns aren't allowed.
+pub fn verify(scramble: &str, solution: &str) -> Result<usize, String> {
+    let turns = notation::parse(solution).map_err(|error| error.to_string())?;
+    if turns.is_empty() {
+        return Err("The solution is empty".to_string());
+    }
+    if let Some(turn) = turns.iter().find(|turn| match turn.kind {
+        Kind::Layers { first, last, .. } => first > 1 || last > 2,
+        Kind::Slice(_) => true,
+        Kind::Rotation(_) => false,
+    }) {
+        return Err(format!(
+            "\"{}\" isn't allowed, only face turns, wide turns and rotations are",
+            turn
+        ));
+    }
+    let moves = notation::count(&turns, Metric::Htm);
+    if moves > MAX_MOVES {
+        return Err(format!(
+            "The solution has {} moves, more than the {} allowed",
+            moves, MAX_MOVES
+        ));
+    }
+    let scrambled = notation::parse_moves(scramble, 3).map_err(|error| error.to_string())?;
+    let solution = notation::moves(&turns, 3).map_err(|error| error.to_string())?;
+    let cube = FaceletCube::new(3)
+        .apply_moves(&scrambled)
+        .apply_moves(&solution);
+    if cube.is_solved() {
+        Ok(moves)
+    } else {
+        Err(format!(
+            "The solution ({} moves) doesn't solve the cube",
+            moves
+        ))
+    }
+}
+
+/// A finished FMC attempt.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+pub struct FmcAttempt {
+    pub date: DateTime<Local>, // When the attempt was finished
+    pub scramble: String,      // Scramble of the attempt
+    pub solution: String,      // Solution submitted, empty if none was
+    pub moves: Option<usize>,  // Length of the solution, `None` for a DNF
+}
+
+impl std::fmt::Display for FmcAttempt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.moves {
+            Some(moves) => write!(f, "{}", moves),
+            None => write!(f, "DNF"),
+        }
+    }
+}
+
+/// The FMC attempt in progress and the history of attempts.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct Fmc {
+    pub scramble: String,      // Scramble being solved, empty between attempts
+    pub solution: String,      // Solution being written
+    pub error: Option<String>, // Why the last submitted solution was rejected
+    pub start: Option<DateTime<Local>>, // When the running attempt started
+    pub attempts: Vec<FmcAttempt>, // Every finished attempt, oldest first
+}
+
+impl Fmc {
+    /// Starts an attempt on a new scramble, made by `scramble::fmc_scramble`.
+    pub fn new_attempt(&mut self, scramble: String) {
+        self.scramble = scramble;
+        self.start = Some(Local::now());
+        self.solution.clear();
+        self.error = None;
+    }
+
+    /// Seconds left before the time limit, if an attempt is running.
+    pub fn remaining(&self) -> Option<f64> {
+        let start = self.start?;
+        let elapsed = Local::now().signed_duration_since(start).num_milliseconds() as f64 / 1000.0;
+        Some(TIME_LIMIT - elapsed)
+    }
+
+    /// Submits the solution, recording it if it is valid.
+    ///
+    /// Once time is up an invalid solution is recorded as a DNF, before that it can still be fixed.
+    pub fn submit(&mut self) {
+        match verify(&self.scramble, &self.solution) {
+            Ok(moves) => self.finish(Some(moves)),
+            Err(error) if self.remaining().map_or(true, |remaining| remaining <= 0.0) => {
+                self.finish(None);
+                self.error = Some(error);
+            }
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    /// Records the attempt as a DNF.
+    pub fn give_up(&mut self) {
+        self.finish(None);
+    }
+
+    fn finish(&mut self, moves: Option<usize>) {
+        self.attempts.push(FmcAttempt {
+            date: Local::now(),
+            scramble: std::mem::take(&mut self.scramble),
+            solution: self.solution.trim().to_string(),
+            moves,
+        });
+        self.start = None;
+        self.error = None;
+    }
+
+    /// Move counts of every attempt, oldest first, with `None` for DNFs.
+    fn results(&self) -> Vec<Option<f64>> {
+        self.attempts
+            .iter()
+            .map(|attempt| attempt.moves.map(|moves| moves as f64))
+            .collect()
+    }
+
+    /// Mean of the last 3 attempts, like the FMC format at competitions.
+    pub fn mean_of_3(&self) -> Option<f64> {
+        let results = self.results();
+        if results.len() < 3 {
+            return None;
+        }
+        stats::average_of(&results[results.len() - 3..])
+    }
+
+    pub fn best_mean_of_3(&self) -> Option<f64> {
+        stats::best_average(&self.results(), 3)
+    }
+
+    pub fn best(&self) -> Option<usize> {
+        self.attempts
+            .iter()
+            .filter_map(|attempt| attempt.moves)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::notation::Face;
+    use crate::app::scramble;
+    use chrono::TimeDelta;
+
+    fn attempt(moves: Option<usize>) -> FmcAttempt {
+        FmcAttempt {
+            date: Local::now(),
+            scramble: "".to_string(),
+            solution: "".to_string(),
+            moves,
+        }
+    }
+
+    #[test]
+    fn rotations_are_free_and_wide_turns_count_once() {
+        assert_eq!(verify("R U", "U' R'"), Ok(2));
+        assert_eq!(verify("R U", "y U' y' R'"), Ok(2));
+        assert_eq!(verify("Rw U", "U' Rw'"), Ok(2));
+        assert_eq!(verify("Rw U", "U' r'"), Ok(2));
+    }
+
+    #[test]
+    fn slices_and_inner_layers_are_rejected() {
+        for solution in ["M'", "R 2R'", "3Rw"] {
+            let error = verify("M", solution).unwrap_err();
+            assert!(error.contains("isn't allowed"), "{}", error);
+        }
+        assert!(verify("R", "").is_err());
+    }
+
+    #[test]
+    fn a_solution_that_does_not_solve_is_rejected() {
+        let error = verify("R U", "U R'").unwrap_err();
+        assert_eq!(error, "The solution (2 moves) doesn't solve the cube");
+    }
+
+    #[test]
+    fn bad_solutions_are_kept_until_time_is_up() {
+        let mut fmc = Fmc::default();
+        fmc.new_attempt("R U".to_string());
+        fmc.solution = "U R'".to_string();
+        fmc.submit();
+        assert!(fmc.attempts.is_empty());
+        assert!(fmc.error.is_some());
+
+        fmc.start = Some(Local::now() - TimeDelta::try_hours(1).unwrap());
+        fmc.submit();
+        assert_eq!(fmc.attempts[0].moves, None);
+        assert_eq!(fmc.attempts[0].to_string(), "DNF");
+        assert_eq!(fmc.start, None);
+
+        fmc.new_attempt("R U".to_string());
+        fmc.solution = " U' R' ".to_string();
+        fmc.submit();
+        assert_eq!(fmc.attempts[1].moves, Some(2));
+        assert_eq!(fmc.attempts[1].solution, "U' R'");
+    }
+
+    #[test]
+    fn mean_of_3_counts_a_dnf_as_a_dnf() {
+        let mut fmc = Fmc {
+            attempts: [Some(25), Some(27), Some(29), None, Some(30), Some(31)]
+                .into_iter()
+                .map(attempt)
+                .collect(),
+            ..Default::default()
+        };
+        assert_eq!(fmc.mean_of_3(), None);
+        assert_eq!(fmc.best_mean_of_3(), Some(27.0));
+        assert_eq!(fmc.best(), Some(25));
+        fmc.attempts.push(attempt(Some(32)));
+        assert_eq!(fmc.mean_of_3(), Some(31.0));
+    }
+
+    #[test]
+    fn scrambles_do_not_merge_into_their_padding() {
+        let face = |turn: &notation::Move| match turn.kind {
+            Kind::Layers { face, .. } => face,
+            _ => panic!("{} isn't a face turn", turn),
+        };
+        for _ in 0..5 {
+            let scramble = scramble::fmc_scramble();
+            let turns = notation::parse(&scramble).unwrap();
+            let inner = &turns[3..turns.len() - 3];
+            assert_eq!(notation::turns_string(&turns[..3]), "R' U' F");
+            assert_eq!(notation::turns_string(&turns[turns.len() - 3..]), "R' U' F");
+            // The F before the scramble and the R' after it would cancel with turns on their axis
+            assert!(
+                ![Face::F, Face::B].contains(&face(&inner[0])),
+                "{}",
+                scramble
+            );
+            assert!(
+                ![Face::R, Face::L].contains(&face(&inner[inner.len() - 1])),
+                "{}",
+                scramble
+            );
+        }
+    }
+}
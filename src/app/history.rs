@@ -0,0 +1,255 @@
+use super::SolveStats;
+
+// History
+
+/// A single change to the solves of a session, holding enough to apply it both ways.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Operation {
+    Add {
+        index: usize,
+        solve: SolveStats,
+    },
+    Delete {
+        index: usize,
+        solve: SolveStats,
+    },
+    Edit {
+        index: usize,
+        before: SolveStats,
+        after: SolveStats,
+    },
+    Comment {
+        index: usize,
+        before: String,
+        after: String,
+    },
+    Tags {
+        index: usize,
+        before: Vec<String>,
+        after: Vec<String>,
+    },
+    Replace {
+        label: String,
+        before: Vec<SolveStats>,
+        after: Vec<SolveStats>,
+    },
+}
+
+impl Operation {
+    /// Short description, used for the undo toast.
+    pub fn describe(&self) -> String {
+        match self {
+            Operation::Add { .. } => "Added solve".to_string(),
+            Operation::Delete { .. } => "Deleted solve".to_string(),
+            Operation::Edit { after, .. } => {
+                if after.dnf {
+                    "Marked solve as DNF".to_string()
+                } else if after.plus2 {
+                    "Added +2 to solve".to_string()
+                } else {
+                    "Removed penalty from solve".to_string()
+                }
+            }
+            Operation::Comment { .. } => "Edited comment".to_string(),
+            Operation::Tags { .. } => "Edited tags".to_string(),
+            Operation::Replace { label, .. } => label.clone(),
+        }
+    }
+
+    /// Whether the operation loses data, and should offer an undo straight away.
+    pub fn destructive(&self) -> bool {
+        !matches!(
+            self,
+            Operation::Add { .. } | Operation::Comment { .. } | Operation::Tags { .. }
+        )
+    }
+
+    pub fn apply(&self, solves: &mut Vec<SolveStats>) {
+        match self {
+            Operation::Add { index, solve } => solves.insert(*index, solve.clone()),
+            Operation::Delete { index, .. } => {
+                solves.remove(*index);
+            }
+            Operation::Edit { index, after, .. } => solves[*index] = after.clone(),
+            Operation::Comment { index, after, .. } => solves[*index].comment = after.clone(),
+            Operation::Tags { index, after, .. } => solves[*index].tags = after.clone(),
+            Operation::Replace { after, .. } => *solves = after.clone(),
+        }
+    }
+
+    pub fn revert(&self, solves: &mut Vec<SolveStats>) {
+        match self {
+            Operation::Add { index, .. } => {
+                solves.remove(*index);
+            }
+            Operation::Delete { index, solve } => solves.insert(*index, solve.clone()),
+            Operation::Edit { index, before, .. } => solves[*index] = before.clone(),
+            Operation::Comment { index, before, .. } => solves[*index].comment = before.clone(),
+            Operation::Tags { index, before, .. } => solves[*index].tags = before.clone(),
+            Operation::Replace { before, .. } => *solves = before.clone(),
+        }
+    }
+}
+
+/// Undo and redo stacks of the operations done to a session.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct History {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+}
+
+/// Most operations kept, the oldest are dropped past this
+const HISTORY_LIMIT: usize = 100;
+
+impl History {
+    /// Applies an operation to the solves and records it, clearing anything to redo.
+    pub fn perform(&mut self, operation: Operation, solves: &mut Vec<SolveStats>) {
+        operation.apply(solves);
+        self.record(operation);
+    }
+
+    /// Records an operation that has already been applied.
+    pub fn record(&mut self, operation: Operation) {
+        self.redo.clear();
+        // Typing a comment edits it once per keystroke, so merge those together
+        if let Operation::Comment { index, after, .. } = &operation {
+            if let Some(Operation::Comment {
+                index: last_index,
+                after: last_after,
+                ..
+            }) = self.undo.last_mut()
+            {
+                if last_index == index {
+                    *last_after = after.clone();
+                    return;
+                }
+            }
+        }
+        self.undo.push(operation);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn undo(&mut self, solves: &mut Vec<SolveStats>) -> Option<Operation> {
+        let operation = self.undo.pop()?;
+        operation.revert(solves);
+        self.redo.push(operation.clone());
+        Some(operation)
+    }
+
+    pub fn redo(&mut self, solves: &mut Vec<SolveStats>) -> Option<Operation> {
+        let operation = self.redo.pop()?;
+        operation.apply(solves);
+        self.undo.push(operation.clone());
+        Some(operation)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(time: &str) -> SolveStats {
+        SolveStats {
+            time: time.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn times(solves: &[SolveStats]) -> Vec<&str> {
+        solves.iter().map(|solve| solve.time.as_str()).collect()
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let mut history = History::default();
+        let mut solves = vec![solve("10")];
+        let edit = Operation::Edit {
+            index: 0,
+            before: solve("10"),
+            after: SolveStats {
+                dnf: true,
+                ..solve("10")
+            },
+        };
+        history.perform(edit.clone(), &mut solves);
+        assert!(solves[0].dnf);
+        assert_eq!(history.undo(&mut solves), Some(edit.clone()));
+        assert_eq!(solves, vec![solve("10")]);
+        assert!(!history.can_undo() && history.can_redo());
+        assert_eq!(history.redo(&mut solves), Some(edit));
+        assert!(solves[0].dnf);
+        assert!(history.can_undo() && !history.can_redo());
+        assert_eq!(history.redo(&mut solves), None);
+    }
+
+    #[test]
+    fn new_operations_clear_the_redo_stack() {
+        let mut history = History::default();
+        let mut solves = vec![];
+        history.perform(
+            Operation::Add {
+                index: 0,
+                solve: solve("10"),
+            },
+            &mut solves,
+        );
+        history.undo(&mut solves);
+        assert!(history.can_redo());
+        history.perform(
+            Operation::Add {
+                index: 0,
+                solve: solve("12"),
+            },
+            &mut solves,
+        );
+        assert!(!history.can_redo());
+        assert_eq!(history.redo(&mut solves), None);
+        assert_eq!(times(&solves), vec!["12"]);
+    }
+
+    #[test]
+    fn undoing_a_delete_puts_the_solve_back_in_place() {
+        let mut history = History::default();
+        let mut solves = vec![solve("9"), solve("10"), solve("11")];
+        history.perform(
+            Operation::Delete {
+                index: 1,
+                solve: solve("10"),
+            },
+            &mut solves,
+        );
+        assert_eq!(times(&solves), vec!["9", "11"]);
+        history.undo(&mut solves);
+        assert_eq!(times(&solves), vec!["9", "10", "11"]);
+    }
+
+    #[test]
+    fn comments_typed_in_a_row_undo_together() {
+        let mut history = History::default();
+        let mut solves = vec![solve("10")];
+        for (before, after) in [("", "g"), ("g", "go"), ("go", "good")] {
+            history.perform(
+                Operation::Comment {
+                    index: 0,
+                    before: before.to_string(),
+                    after: after.to_string(),
+                },
+                &mut solves,
+            );
+        }
+        assert_eq!(solves[0].comment, "good");
+        history.undo(&mut solves);
+        assert_eq!(solves[0].comment, "");
+        assert!(!history.can_undo());
+    }
+}
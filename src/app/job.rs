@@ -0,0 +1,56 @@
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+
+// Background Jobs
+
+/// Whether jobs stop the app responding while they run, as the web has no threads to run them on.
+pub const BLOCKS: bool = cfg!(target_arch = "wasm32");
+
+/// Work done off the UI thread, polled once per frame until it finishes.
+///
+/// The web has no threads, so there the work is done on the second poll instead,
+/// giving the frame in between a chance to show that the app is busy.
+pub struct Job<T> {
+    receiver: Receiver<T>,
+    #[cfg(target_arch = "wasm32")]
+    work: std::cell::Cell<Option<Box<dyn FnOnce()>>>,
+    #[cfg(target_arch = "wasm32")]
+    polled: std::cell::Cell<bool>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    pub fn spawn(work: impl FnOnce() -> T + Send + 'static) -> Self {
+        let (sender, receiver) = channel();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            std::thread::spawn(move || {
+                let _ = sender.send(work());
+            });
+            Self { receiver }
+        }
+        #[cfg(target_arch = "wasm32")]
+        Self {
+            receiver,
+            work: std::cell::Cell::new(Some(Box::new(move || {
+                let _ = sender.send(work());
+            }))),
+            polled: std::cell::Cell::new(false),
+        }
+    }
+
+    /// The result once the work is done, or an error if it panicked.
+    pub fn poll(&self) -> Option<Result<T, String>> {
+        #[cfg(target_arch = "wasm32")]
+        if self.polled.replace(true) {
+            if let Some(work) = self.work.take() {
+                work();
+            }
+        }
+        match self.receiver.try_recv() {
+            Ok(result) => Some(Ok(result)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err("The job stopped before finishing".to_string()))
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use egui::{Key, KeyboardShortcut, Modifiers};
+
+// Keybindings
+
+/// Something that can be done from the keyboard as well as with the mouse.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub enum Action {
+    Ok,
+    PlusTwo,
+    Dnf,
+    Delete,
+    Undo,
+    Redo,
+    NewScramble,
+    ToggleSettings,
+    ToggleStatistics,
+    ToggleTools,
+    ToggleLeftBar,
+}
+
+/// Every action, in the order they are listed in the settings.
+pub const ACTIONS: [Action; 11] = [
+    Action::Ok,
+    Action::PlusTwo,
+    Action::Dnf,
+    Action::Delete,
+    Action::Undo,
+    Action::Redo,
+    Action::NewScramble,
+    Action::ToggleSettings,
+    Action::ToggleStatistics,
+    Action::ToggleTools,
+    Action::ToggleLeftBar,
+];
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Action::Ok => "Mark Last Solve OK",
+            Action::PlusTwo => "Mark Last Solve +2",
+            Action::Dnf => "Mark Last Solve DNF",
+            Action::Delete => "Delete Last Solve",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::NewScramble => "New Scramble",
+            Action::ToggleSettings => "Toggle Settings",
+            Action::ToggleStatistics => "Toggle Statistics",
+            Action::ToggleTools => "Toggle Tools",
+            Action::ToggleLeftBar => "Toggle Left Bar",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Action {
+    /// The keys the action is bound to until they are changed.
+    ///
+    /// Ctrl is Cmd on a Mac.
+    pub fn default_bindings(&self) -> Vec<KeyboardShortcut> {
+        let command = |key| vec![KeyboardShortcut::new(Modifiers::COMMAND, key)];
+        match self {
+            Action::Ok => command(Key::Num1),
+            Action::PlusTwo => command(Key::Num2),
+            Action::Dnf => command(Key::Num3),
+            Action::Undo => command(Key::Z),
+            Action::Redo => command(Key::Y),
+            // Either arrow, so that it's found whichever one is tried
+            Action::NewScramble => vec![
+                KeyboardShortcut::new(Modifiers::NONE, Key::ArrowRight),
+                KeyboardShortcut::new(Modifiers::NONE, Key::ArrowLeft),
+            ],
+            _ => vec![],
+        }
+    }
+}
+
+/// Keys reserved for the timer, which can't be bound to anything else.
+pub const TIMER_KEYS: [Key; 2] = [Key::Space, Key::Escape];
+
+/// The keys bound to each action.
+///
+/// Only bindings changed from the defaults are kept, so that new actions get their default.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct Keybindings {
+    bindings: HashMap<Action, Vec<KeyboardShortcut>>, // Keys set in the settings, empty for unbound actions
+}
+
+impl Keybindings {
+    pub fn get(&self, action: Action) -> Vec<KeyboardShortcut> {
+        match self.bindings.get(&action) {
+            Some(bindings) => bindings.clone(),
+            None => action.default_bindings(),
+        }
+    }
+
+    /// Binds `action` to exactly `bindings`, leaving other actions as they are.
+    pub fn set(&mut self, action: Action, bindings: Vec<KeyboardShortcut>) {
+        self.bindings.insert(action, bindings);
+    }
+
+    /// Binds `action` to `binding` as well as its other keys, taking it away from any other action bound to it.
+    pub fn add(&mut self, action: Action, binding: KeyboardShortcut) {
+        for other in ACTIONS {
+            let mut bindings = self.get(other);
+            if other != action && bindings.contains(&binding) {
+                bindings.retain(|other| *other != binding);
+                self.set(other, bindings);
+            }
+        }
+        let mut bindings = self.get(action);
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self.set(action, bindings);
+    }
+
+    /// Puts every action back on its default keys.
+    pub fn reset(&mut self) {
+        self.bindings.clear();
+    }
+
+    /// The action bound to `key` pressed with `modifiers`, if any.
+    pub fn action(&self, key: Key, modifiers: Modifiers) -> Option<Action> {
+        ACTIONS.into_iter().find(|action| {
+            self.get(*action).iter().any(|binding| {
+                binding.logical_key == key && modifiers.matches_exact(binding.modifiers)
+            })
+        })
+    }
+
+    /// How the bindings of `action` are shown, such as "Ctrl+Z" or "Right, Left".
+    pub fn describe(&self, action: Action) -> String {
+        let bindings = self.get(action);
+        if bindings.is_empty() {
+            return "Unbound".to_string();
+        }
+        bindings
+            .iter()
+            .map(describe)
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// The binding for `key` pressed with `modifiers`, Ctrl and Cmd both being the command key of the platform.
+pub fn binding(key: Key, modifiers: Modifiers) -> KeyboardShortcut {
+    let modifiers = Modifiers {
+        alt: modifiers.alt,
+        shift: modifiers.shift,
+        command: modifiers.command,
+        ..Modifiers::NONE
+    };
+    KeyboardShortcut::new(modifiers, key)
+}
+
+/// How a binding is shown, with Mac modifier names on a Mac.
+pub fn describe(binding: &KeyboardShortcut) -> String {
+    binding.format(&egui::ModifierNames::NAMES, cfg!(target_os = "macos"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn either_arrow_makes_a_new_scramble() {
+        let keys = Keybindings::default();
+        for key in [Key::ArrowRight, Key::ArrowLeft] {
+            assert_eq!(keys.action(key, Modifiers::NONE), Some(Action::NewScramble));
+        }
+        assert_eq!(keys.action(Key::ArrowLeft, Modifiers::SHIFT), None);
+    }
+
+    #[test]
+    fn adding_a_key_takes_it_from_other_actions() {
+        let mut keys = Keybindings::default();
+        let left = KeyboardShortcut::new(Modifiers::NONE, Key::ArrowLeft);
+        keys.add(Action::Undo, left);
+        assert_eq!(
+            keys.action(Key::ArrowLeft, Modifiers::NONE),
+            Some(Action::Undo)
+        );
+        assert_eq!(keys.get(Action::Undo).len(), 2);
+        assert_eq!(
+            keys.get(Action::NewScramble),
+            vec![KeyboardShortcut::new(Modifiers::NONE, Key::ArrowRight)]
+        );
+        keys.reset();
+        assert_eq!(keys, Keybindings::default());
+    }
+}
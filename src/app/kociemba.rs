@@ -0,0 +1,513 @@
+use chrono::{DateTime, Local, TimeDelta};
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+use super::cache;
+use super::cubie::{allowed, CubieCube, Turn};
+
+// Two-Phase Solver
+
+/// Number of positions of each coordinate.
+const TWISTS: usize = 2187; // Corner orientations, 3^7
+pub const FLIPS: usize = 2048; // Edge orientations, 2^11
+const SLICES: usize = 495; // Positions of the four E slice edges, 12 choose 4
+const CORNER_PERMS: usize = 40320; // Corner permutations, 8!
+const EDGE_PERMS: usize = 40320; // Permutations of the U and D edges, 8!
+const SLICE_PERMS: usize = 24; // Permutations of the E slice edges, 4!
+
+/// Turns that keep a cube in the phase 2 group <U, D, R2, L2, F2, B2>.
+const PHASE2_TURNS: [usize; 10] = [0, 1, 2, 4, 7, 9, 10, 11, 13, 16];
+
+/// Pruning table value of positions the search hasn't reached yet.
+pub const UNKNOWN: u8 = 255;
+
+/// File the pruning tables are cached in.
+const CACHE_FILE: &str = "kociemba.bin";
+
+/// Changed whenever the tables are laid out differently, so older caches are generated again.
+const CACHE_VERSION: u32 = 1;
+
+/// How many nodes are searched between checks of the time budget.
+const CHECK_INTERVAL: usize = 4096;
+
+/// Limits for a solve, as set in the Solve tools.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SolverOptions {
+    pub max_length: usize, // Longest solution accepted
+    pub time: f64,         // Seconds to spend looking for shorter solutions
+    pub count: usize,      // Number of solutions returned
+}
+
+/// Move and pruning tables, generated once and shared by every solve.
+pub struct Tables {
+    twist_move: Vec<[u16; 18]>,
+    flip_move: Vec<[u16; 18]>,
+    slice_move: Vec<[u16; 18]>,
+    corner_move: Vec<[u16; 18]>,
+    edge_move: Vec<[u16; 18]>,       // Only valid for phase 2 turns
+    slice_perm_move: Vec<[u16; 18]>, // Only valid for phase 2 turns
+    twist_slice: Vec<u8>,
+    flip_slice: Vec<u8>,
+    corner_slice: Vec<u8>,
+    edge_slice: Vec<u8>,
+}
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+/// The tables, read from the cache or generated on first use.
+pub fn tables() -> &'static Tables {
+    TABLES.get_or_init(Tables::load)
+}
+
+/// Whether the tables are ready, so a solve won't have to wait for them.
+pub fn tables_ready() -> bool {
+    TABLES.get().is_some()
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |result, i| result * (n - i) / (i + 1))
+}
+
+/// Position of a permutation of `0..n` in lexicographic order.
+pub fn perm_rank(permutation: &[u8]) -> usize {
+    let n = permutation.len();
+    let mut rank = 0;
+    for i in 0..n {
+        let smaller = permutation[i + 1..]
+            .iter()
+            .filter(|&&p| p < permutation[i])
+            .count();
+        rank = rank * (n - i) + smaller;
+    }
+    rank
+}
+
+/// The permutation of `0..n` at a position in lexicographic order.
+pub fn perm_unrank(mut rank: usize, n: usize) -> Vec<u8> {
+    let mut digits = vec![0; n];
+    for i in (0..n).rev() {
+        digits[i] = rank % (n - i);
+        rank /= n - i;
+    }
+    let mut available: Vec<u8> = (0..n as u8).collect();
+    digits
+        .into_iter()
+        .map(|digit| available.remove(digit))
+        .collect()
+}
+
+fn twist(cube: &CubieCube) -> usize {
+    cube.co[..7].iter().fold(0, |t, &o| t * 3 + o as usize)
+}
+
+fn set_twist(cube: &mut CubieCube, mut twist: usize) {
+    let mut sum = 0;
+    for i in (0..7).rev() {
+        cube.co[i] = (twist % 3) as u8;
+        sum += cube.co[i];
+        twist /= 3;
+    }
+    cube.co[7] = (3 - sum % 3) % 3;
+}
+
+pub fn flip(cube: &CubieCube) -> usize {
+    cube.eo[..11].iter().fold(0, |f, &o| f * 2 + o as usize)
+}
+
+pub fn set_flip(cube: &mut CubieCube, mut flip: usize) {
+    let mut sum = 0;
+    for i in (0..11).rev() {
+        cube.eo[i] = (flip % 2) as u8;
+        sum += cube.eo[i];
+        flip /= 2;
+    }
+    cube.eo[11] = sum % 2;
+}
+
+/// Rank of the positions holding E slice edges, 0 when they are all in the slice.
+fn slice(cube: &CubieCube) -> usize {
+    let mut rank = 0;
+    let mut found = 0;
+    for j in (0..12).rev() {
+        if cube.ep[j] >= 8 {
+            found += 1;
+            rank += binomial(11 - j, found);
+        }
+    }
+    rank
+}
+
+fn set_slice(cube: &mut CubieCube, slice: usize) {
+    // Ranks aren't easily reversed, so look for the set of positions with this one
+    for mask in 0u32..1 << 12 {
+        if mask.count_ones() != 4 {
+            continue;
+        }
+        let mut other = 0;
+        let mut slice_edge = 8;
+        for j in 0..12 {
+            if mask & (1 << j) != 0 {
+                cube.ep[j] = slice_edge;
+                slice_edge += 1;
+            } else {
+                cube.ep[j] = other;
+                other += 1;
+            }
+        }
+        if self::slice(cube) == slice {
+            return;
+        }
+    }
+}
+
+fn corner_perm(cube: &CubieCube) -> usize {
+    perm_rank(&cube.cp)
+}
+
+fn edge_perm(cube: &CubieCube) -> usize {
+    perm_rank(&cube.ep[..8])
+}
+
+fn slice_perm(cube: &CubieCube) -> usize {
+    let slice: Vec<u8> = cube.ep[8..].iter().map(|&e| e - 8).collect();
+    perm_rank(&slice)
+}
+
+/// Fills a move table, `set` building a cube with a coordinate and `get` reading it back.
+pub fn move_table(
+    size: usize,
+    turns: &[usize],
+    set: impl Fn(&mut CubieCube, usize),
+    get: impl Fn(&CubieCube) -> usize,
+) -> Vec<[u16; 18]> {
+    (0..size)
+        .map(|coordinate| {
+            let mut cube = CubieCube::default();
+            set(&mut cube, coordinate);
+            let mut row = [0u16; 18];
+            for &turn in turns {
+                row[turn] = get(&cube.turn(Turn::from_index(turn))) as u16;
+            }
+            row
+        })
+        .collect()
+}
+
+/// Distances from solved of every pair of coordinates, found by a breadth first search.
+fn pruning_table(first: &[[u16; 18]], second: &[[u16; 18]], turns: &[usize]) -> Vec<u8> {
+    let width = second.len();
+    let mut table = vec![UNKNOWN; first.len() * width];
+    let mut queue = VecDeque::new();
+    table[0] = 0;
+    queue.push_back(0usize);
+    while let Some(index) = queue.pop_front() {
+        let (a, b) = (index / width, index % width);
+        for &turn in turns {
+            let next = first[a][turn] as usize * width + second[b][turn] as usize;
+            if table[next] == UNKNOWN {
+                table[next] = table[index] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    table
+}
+
+impl Tables {
+    fn generate_moves() -> Self {
+        let all: Vec<usize> = (0..18).collect();
+        Self {
+            twist_move: move_table(TWISTS, &all, set_twist, twist),
+            flip_move: move_table(FLIPS, &all, set_flip, flip),
+            slice_move: move_table(SLICES, &all, set_slice, slice),
+            corner_move: move_table(
+                CORNER_PERMS,
+                &all,
+                |cube, rank| cube.cp.copy_from_slice(&perm_unrank(rank, 8)),
+                corner_perm,
+            ),
+            edge_move: move_table(
+                EDGE_PERMS,
+                &PHASE2_TURNS,
+                |cube, rank| cube.ep[..8].copy_from_slice(&perm_unrank(rank, 8)),
+                edge_perm,
+            ),
+            slice_perm_move: move_table(
+                SLICE_PERMS,
+                &PHASE2_TURNS,
+                |cube, rank| {
+                    for (i, e) in perm_unrank(rank, 4).into_iter().enumerate() {
+                        cube.ep[8 + i] = e + 8;
+                    }
+                },
+                slice_perm,
+            ),
+            twist_slice: vec![],
+            flip_slice: vec![],
+            corner_slice: vec![],
+            edge_slice: vec![],
+        }
+    }
+
+    fn generate_pruning(&mut self) {
+        let all: Vec<usize> = (0..18).collect();
+        self.twist_slice = pruning_table(&self.twist_move, &self.slice_move, &all);
+        self.flip_slice = pruning_table(&self.flip_move, &self.slice_move, &all);
+        self.corner_slice = pruning_table(&self.corner_move, &self.slice_perm_move, &PHASE2_TURNS);
+        self.edge_slice = pruning_table(&self.edge_move, &self.slice_perm_move, &PHASE2_TURNS);
+    }
+
+    fn pruning_len() -> usize {
+        TWISTS * SLICES + FLIPS * SLICES + (CORNER_PERMS + EDGE_PERMS) * SLICE_PERMS
+    }
+
+    /// Builds the move tables, taking the slower pruning tables from the cache when it holds them.
+    fn load() -> Self {
+        let mut tables = Tables::generate_moves();
+        match cache::read(CACHE_FILE, CACHE_VERSION) {
+            Some(bytes) if bytes.len() == Tables::pruning_len() => {
+                let (twist_slice, rest) = bytes.split_at(TWISTS * SLICES);
+                let (flip_slice, rest) = rest.split_at(FLIPS * SLICES);
+                let (corner_slice, edge_slice) = rest.split_at(CORNER_PERMS * SLICE_PERMS);
+                tables.twist_slice = twist_slice.to_vec();
+                tables.flip_slice = flip_slice.to_vec();
+                tables.corner_slice = corner_slice.to_vec();
+                tables.edge_slice = edge_slice.to_vec();
+            }
+            _ => {
+                tables.generate_pruning();
+                let mut bytes = Vec::with_capacity(Tables::pruning_len());
+                bytes.extend_from_slice(&tables.twist_slice);
+                bytes.extend_from_slice(&tables.flip_slice);
+                bytes.extend_from_slice(&tables.corner_slice);
+                bytes.extend_from_slice(&tables.edge_slice);
+                cache::write(CACHE_FILE, CACHE_VERSION, &bytes);
+            }
+        }
+        tables
+    }
+}
+
+struct Search<'a> {
+    tables: &'a Tables,
+    cube: CubieCube,
+    options: SolverOptions,
+    deadline: DateTime<Local>,
+    nodes: usize,
+    timed_out: bool,
+    solutions: Vec<Vec<Turn>>,
+}
+
+impl Search<'_> {
+    /// Longest solution still worth finding.
+    fn limit(&self) -> usize {
+        if self.solutions.len() < self.options.count {
+            self.options.max_length
+        } else {
+            // Full, so only a shorter solution can replace the longest kept
+            self.solutions
+                .last()
+                .map_or(0, |longest| longest.len().saturating_sub(1))
+        }
+    }
+
+    fn out_of_time(&mut self) -> bool {
+        self.nodes += 1;
+        if self.nodes % CHECK_INTERVAL == 0 && Local::now() > self.deadline {
+            self.timed_out = true;
+        }
+        self.timed_out
+    }
+
+    fn phase1(
+        &mut self,
+        twist: usize,
+        flip: usize,
+        slice: usize,
+        depth: usize,
+        turns: &mut Vec<Turn>,
+    ) {
+        if self.out_of_time() {
+            return;
+        }
+        if depth == 0 {
+            // A phase 2 turn last means a shorter phase 1 was already tried
+            let last_phase2 = turns
+                .last()
+                .map_or(false, |turn| PHASE2_TURNS.contains(&turn.index()));
+            if twist == 0 && flip == 0 && slice == 0 && !last_phase2 {
+                self.start_phase2(turns);
+            }
+            return;
+        }
+        for turn in Turn::all() {
+            if !allowed(turns.last().copied(), turn) {
+                continue;
+            }
+            let m = turn.index();
+            let twist = self.tables.twist_move[twist][m] as usize;
+            let flip = self.tables.flip_move[flip][m] as usize;
+            let slice = self.tables.slice_move[slice][m] as usize;
+            let distance = self.tables.twist_slice[twist * SLICES + slice]
+                .max(self.tables.flip_slice[flip * SLICES + slice])
+                as usize;
+            if distance >= depth {
+                continue;
+            }
+            turns.push(turn);
+            self.phase1(twist, flip, slice, depth - 1, turns);
+            turns.pop();
+            if self.timed_out || turns.len() + depth > self.limit() {
+                return;
+            }
+        }
+    }
+
+    fn start_phase2(&mut self, phase1: &[Turn]) {
+        let cube = self.cube.turns(phase1);
+        let corner = corner_perm(&cube);
+        let edge = edge_perm(&cube);
+        let slice = slice_perm(&cube);
+        let limit = self.limit();
+        if phase1.len() > limit {
+            return;
+        }
+        let mut turns = phase1.to_vec();
+        for depth in 0..=limit - phase1.len() {
+            if self.phase2(corner, edge, slice, depth, &mut turns) {
+                if !self.solutions.contains(&turns) {
+                    let position = self
+                        .solutions
+                        .partition_point(|kept| kept.len() <= turns.len());
+                    self.solutions.insert(position, turns);
+                    self.solutions.truncate(self.options.count);
+                }
+                return;
+            }
+            if self.timed_out {
+                return;
+            }
+        }
+    }
+
+    fn phase2(
+        &mut self,
+        corner: usize,
+        edge: usize,
+        slice: usize,
+        depth: usize,
+        turns: &mut Vec<Turn>,
+    ) -> bool {
+        if self.out_of_time() {
+            return false;
+        }
+        if depth == 0 {
+            return corner == 0 && edge == 0 && slice == 0;
+        }
+        for m in PHASE2_TURNS {
+            let turn = Turn::from_index(m);
+            if !allowed(turns.last().copied(), turn) {
+                continue;
+            }
+            let corner = self.tables.corner_move[corner][m] as usize;
+            let edge = self.tables.edge_move[edge][m] as usize;
+            let slice = self.tables.slice_perm_move[slice][m] as usize;
+            let distance = self.tables.corner_slice[corner * SLICE_PERMS + slice]
+                .max(self.tables.edge_slice[edge * SLICE_PERMS + slice])
+                as usize;
+            if distance >= depth {
+                continue;
+            }
+            turns.push(turn);
+            if self.phase2(corner, edge, slice, depth - 1, turns) {
+                return true;
+            }
+            turns.pop();
+        }
+        false
+    }
+}
+
+/// Finds up to `options.count` solutions of the cube, shortest first.
+///
+/// Keeps looking for shorter solutions until the time budget runs out or none
+/// can exist, and fails if nothing within the maximum length was found by then.
+pub fn solve(cube: &CubieCube, options: SolverOptions) -> Result<Vec<Vec<Turn>>, String> {
+    if !cube.is_valid() {
+        return Err("The cube can't be solved, a piece may be twisted or swapped".to_string());
+    }
+    let tables = tables();
+    let start = Local::now();
+    let mut search = Search {
+        tables,
+        cube: *cube,
+        options: SolverOptions {
+            count: options.count.max(1),
+            ..options
+        },
+        deadline: start
+            + TimeDelta::try_milliseconds((options.time * 1000.0) as i64)
+                .unwrap_or(TimeDelta::zero()),
+        nodes: 0,
+        timed_out: false,
+        solutions: vec![],
+    };
+    let (twist, flip, slice) = (twist(cube), flip(cube), slice(cube));
+    let mut depth = 0;
+    while depth <= search.limit() && !search.timed_out {
+        search.phase1(twist, flip, slice, depth, &mut vec![]);
+        depth += 1;
+    }
+    if search.solutions.is_empty() {
+        Err(format!(
+            "No solution of {} moves or fewer was found in {} seconds",
+            options.max_length, options.time
+        ))
+    } else {
+        Ok(search.solutions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::cubie;
+
+    const OPTIONS: SolverOptions = SolverOptions {
+        max_length: 30,
+        time: 0.1,
+        count: 3,
+    };
+
+    #[test]
+    fn solutions_solve_the_cube() {
+        let moves = cubesim::parse_scramble("R U R' U' F2 D L' B2 U' R2 F D2 L".to_string());
+        let cube = cubie::from_moves(&moves).unwrap();
+        let solutions = solve(&cube, OPTIONS).unwrap();
+        assert!(!solutions.is_empty() && solutions.len() <= OPTIONS.count);
+        for solution in &solutions {
+            assert!(solution.len() <= OPTIONS.max_length);
+            assert_eq!(cube.turns(solution), CubieCube::default());
+        }
+        // Shortest first
+        assert!(solutions
+            .windows(2)
+            .all(|pair| pair[0].len() <= pair[1].len()));
+    }
+
+    #[test]
+    fn finds_short_solutions_optimally() {
+        let cube = cubie::from_moves(&cubesim::parse_scramble("R U F'".to_string())).unwrap();
+        assert_eq!(solve(&cube, OPTIONS).unwrap()[0].len(), 3);
+    }
+
+    #[test]
+    fn rejects_impossible_cubes() {
+        let mut cube = CubieCube::default();
+        cube.co[0] = 1;
+        assert!(solve(&cube, OPTIONS).is_err());
+    }
+}
@@ -0,0 +1,336 @@
+use chrono::{DateTime, Local, TimeDelta};
+
+use super::trainer::{self, CaseSet};
+
+/// Number and algorithm of every CMLL case, named by corner orientation and then by how the colours line up.
+const CMLL: [(&str, &str); 42] = [
+    ("O Adjacent", "R U R' F' R U R' U' R' F R2 U' R'"),
+    ("O Diagonal", "F R U' R' U' R U R' F' R U R' U' R' F R F'"),
+    ("H Columns", "R U2 R' U' R U R' U' R U' R'"),
+    ("H Rows", "F R U R' U' R U R' U' R U R' U' F'"),
+    ("H Column", "U R U2 R2 F R F' U2 R' F R F'"),
+    ("H Row", "r U' r2 D' r U' r' D r2 U r'"),
+    ("Pi Right Bar", "F R U R' U' R U R' U' F'"),
+    ("Pi Back Slash", "F R' F' R U2 R U' R' U R U2 R'"),
+    ("Pi X Checkerboard", "R' F R U F U' R U R' U' F'"),
+    ("Pi Forward Slash", "R U2 R' U' R U R' U2 R' F R F'"),
+    ("Pi Columns", "r U' r2 D' r U r' D r2 U r'"),
+    ("Pi Left Bar", "R' U' R' F R F' R U' R' U2 R"),
+    ("U Forward Slash", "R2 D R' U2 R D' R' U2 R'"),
+    ("U Back Slash", "R2 D' R U2 R' D R U2 R"),
+    ("U Front Row", "R2 F U' F U F2 R2 U' R' F R"),
+    ("U Rows", "F R2 D R' U R D' R2 U' F'"),
+    ("U X Checkerboard", "r U' r' U r' D' r U' r' D r"),
+    ("U Back Row", "F R U R' U' F'"),
+    ("T Left Bar", "R U R' U' R' F R F'"),
+    ("T Right Bar", "L' U' L U L F' L' F"),
+    ("T Rows", "F R' F R2 U' R' U' R U R' F2"),
+    ("T Front Row", "r' U r U2 R2 F R F' R"),
+    ("T Back Row", "r' D' r U r' D r U' r U r'"),
+    ("T Columns", "r2 D' r U r' D r2 U' r' U' r"),
+    ("S Left Bar", "R U R' U R U2 R'"),
+    ("S X Checkerboard", "L' U2 L U2 L F' L' F"),
+    ("S Forward Slash", "F R' F' R U2 R U2 R'"),
+    ("S Columns", "F R' U2 R F' R' F U2 F' R"),
+    ("S Right Bar", "R U R' U R' F R F' R U2 R'"),
+    ("S Back Slash", "R U' L' U R' U' L"),
+    ("AS Right Bar", "R' U' R U' R' U2 R"),
+    ("AS Columns", "R' F U2 F' R F R' U2 R F'"),
+    ("AS Back Slash", "F' L F L' U2 L' U2 L"),
+    ("AS X Checkerboard", "R U2 R' U2 R' F R F'"),
+    ("AS Forward Slash", "L' U R U' L U R'"),
+    ("AS Left Bar", "R' U' R U' R' U R' F R F' U R"),
+    ("L Mirror", "F R U' R' U' R U2 R' U' F'"),
+    ("L Inverse", "F R' F' R U R U' R'"),
+    ("L Pure", "R U2 R' U' R U R' U' R U R' U' R U' R'"),
+    ("L Front Commutator", "R U2 R D R' U2 R D' R2"),
+    ("L Diagonal", "R U2 R2 F R F' R U2 R'"),
+    ("L Back Commutator", "R' U2 R' D' R U2 R' D R2"),
+];
+
+/// Ease a case starts with, and the range it is kept in.
+const START_EASE: f64 = 2.5;
+const MIN_EASE: f64 = 1.3;
+const MAX_EASE: f64 = 3.0;
+
+/// Minutes until a failed case comes back.
+const RELEARN_MINUTES: i64 = 10;
+
+/// One attempt at a case.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+pub struct Drill {
+    pub date: DateTime<Local>, // When the case was drilled
+    pub passed: bool,          // Whether it was executed correctly
+    pub time: Option<f64>,     // Execution time in seconds, if it was timed
+}
+
+/// A case of an algorithm set, with the algorithm chosen for it and its drill history.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct AlgCase {
+    pub name: String,                 // Name of the case
+    pub algorithm: String,            // Algorithm the user solves it with
+    pub notes: String,                // Notes, such as fingertricks or how to recognise it
+    pub drills: Vec<Drill>,           // Every drill of the case, oldest first
+    pub interval: f64,                // Days between the last drill and the next
+    pub ease: f64,                    // Factor the interval grows by after a pass
+    pub due: Option<DateTime<Local>>, // When the case should be drilled next, `None` until it is first drilled
+}
+
+impl Default for AlgCase {
+    fn default() -> Self {
+        Self {
+            name: "".to_string(),
+            algorithm: "".to_string(),
+            notes: "".to_string(),
+            drills: vec![],
+            interval: 0.0,
+            ease: START_EASE,
+            due: None,
+        }
+    }
+}
+
+impl AlgCase {
+    fn new(name: &str, algorithm: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            algorithm: algorithm.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Records a drill and schedules the next one.
+    ///
+    /// A pass grows the interval by the ease, like SM-2, while a fail brings the
+    /// case back within minutes and makes it come back sooner from then on.
+    pub fn record(&mut self, passed: bool, time: Option<f64>, now: DateTime<Local>) {
+        self.drills.push(Drill {
+            date: now,
+            passed,
+            time,
+        });
+        if passed {
+            self.interval = if self.interval < 1.0 {
+                1.0
+            } else {
+                self.interval * self.ease
+            };
+            self.ease = (self.ease + 0.1).min(MAX_EASE);
+            let minutes = (self.interval * 24.0 * 60.0) as i64;
+            self.due = Some(now + TimeDelta::try_minutes(minutes).unwrap_or(TimeDelta::zero()));
+        } else {
+            self.interval = 0.0;
+            self.ease = (self.ease - 0.2).max(MIN_EASE);
+            self.due =
+                Some(now + TimeDelta::try_minutes(RELEARN_MINUTES).unwrap_or(TimeDelta::zero()));
+        }
+    }
+
+    /// Share of drills that passed, from 0 to 1.
+    pub fn pass_rate(&self) -> Option<f64> {
+        if self.drills.is_empty() {
+            None
+        } else {
+            let passed = self.drills.iter().filter(|drill| drill.passed).count();
+            Some(passed as f64 / self.drills.len() as f64)
+        }
+    }
+
+    /// Mean execution time of the timed drills that passed.
+    pub fn mean_time(&self) -> Option<f64> {
+        let times: Vec<f64> = self
+            .drills
+            .iter()
+            .filter(|drill| drill.passed)
+            .filter_map(|drill| drill.time)
+            .collect();
+        if times.is_empty() {
+            None
+        } else {
+            Some(times.iter().sum::<f64>() / times.len() as f64)
+        }
+    }
+}
+
+/// A named group of cases, such as OLL or a custom set.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct AlgSet {
+    pub name: String,
+    pub cases: Vec<AlgCase>,
+}
+
+impl AlgSet {
+    /// The case to drill next: the most overdue one, then cases never drilled, in order.
+    ///
+    /// Cases without an algorithm are skipped, and `None` means nothing is due yet.
+    pub fn next(&self, now: DateTime<Local>) -> Option<usize> {
+        let drillable = || {
+            self.cases
+                .iter()
+                .enumerate()
+                .filter(|(_, case)| !case.algorithm.trim().is_empty())
+        };
+        drillable()
+            .filter_map(|(i, case)| Some((i, case.due?)))
+            .filter(|&(_, due)| due <= now)
+            .min_by_key(|&(_, due)| due)
+            .map(|(i, _)| i)
+            .or_else(|| {
+                drillable()
+                    .find(|(_, case)| case.due.is_none())
+                    .map(|(i, _)| i)
+            })
+    }
+
+    /// When the first of the drilled cases becomes due.
+    pub fn next_due(&self) -> Option<DateTime<Local>> {
+        self.cases.iter().filter_map(|case| case.due).min()
+    }
+}
+
+/// Algorithm sets the user learns, drilled with spaced repetition.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Library {
+    pub sets: Vec<AlgSet>,    // Every set, starting with OLL, PLL and CMLL
+    pub set: usize,           // Index of the set shown and drilled
+    pub drilling: bool,       // Whether the timer drills cases instead of solving
+    pub case: Option<usize>,  // Case the current scramble sets up
+    pub pending: Option<f64>, // Execution time of the current case, waiting to be marked as passed or failed
+    pub new_set: String,      // Name typed for a new set
+    pub new_case: String,     // Name typed for a new case
+}
+
+impl Default for Library {
+    fn default() -> Self {
+        let from_trainer = |set: CaseSet| AlgSet {
+            name: set.to_string(),
+            cases: trainer::cases(set)
+                .iter()
+                .map(|case| AlgCase::new(case.name, case.algorithm))
+                .collect(),
+        };
+        Self {
+            sets: vec![
+                from_trainer(CaseSet::Oll),
+                from_trainer(CaseSet::Pll),
+                AlgSet {
+                    name: "CMLL".to_string(),
+                    cases: CMLL
+                        .iter()
+                        .map(|&(name, algorithm)| AlgCase::new(name, algorithm))
+                        .collect(),
+                },
+            ],
+            set: 0,
+            drilling: false,
+            case: None,
+            pending: None,
+            new_set: "".to_string(),
+            new_case: "".to_string(),
+        }
+    }
+}
+
+impl Library {
+    pub fn current(&self) -> Option<&AlgSet> {
+        self.sets.get(self.set)
+    }
+
+    /// Picks the case to drill next, returning a scramble setting it up.
+    pub fn next(&mut self) -> Option<String> {
+        self.pending = None;
+        self.case = self.current().and_then(|set| set.next(Local::now()));
+        let case = &self.sets[self.set].cases[self.case?];
+        Some(trainer::setup(&case.algorithm))
+    }
+
+    /// Marks the current case as passed or failed, with the time of the last execution if there is one.
+    pub fn grade(&mut self, passed: bool) {
+        let time = self.pending.take();
+        if let Some(case) = self
+            .case
+            .take()
+            .and_then(|case| self.sets.get_mut(self.set)?.cases.get_mut(case))
+        {
+            case.record(passed, time, Local::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days(days: f64) -> TimeDelta {
+        TimeDelta::try_minutes((days * 24.0 * 60.0) as i64).unwrap()
+    }
+
+    #[test]
+    fn passes_grow_the_interval_by_the_ease() {
+        let now = Local::now();
+        let mut case = AlgCase::new("T Perm", "R U R' U' R' F R2 U' R' U' R U R' F'");
+        case.record(true, Some(1.5), now);
+        assert_eq!(case.interval, 1.0);
+        assert_eq!(case.due, Some(now + days(1.0)));
+        case.record(true, None, now);
+        assert_eq!(case.interval, 2.6);
+        assert_eq!(case.due, Some(now + days(2.6)));
+        case.record(true, None, now);
+        assert!((case.interval - 2.6 * 2.7).abs() < 1e-9);
+        assert!((case.ease - 2.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_fail_resets_the_interval_and_lowers_the_ease() {
+        let now = Local::now();
+        let mut case = AlgCase::new("T Perm", "R U R' U' R' F R2 U' R' U' R U R' F'");
+        case.record(true, None, now);
+        case.record(true, None, now);
+        case.record(false, None, now);
+        assert_eq!(case.interval, 0.0);
+        assert!((case.ease - 2.5).abs() < 1e-9);
+        assert_eq!(
+            case.due,
+            Some(now + TimeDelta::try_minutes(RELEARN_MINUTES).unwrap())
+        );
+        for _ in 0..10 {
+            case.record(false, None, now);
+        }
+        assert_eq!(case.ease, MIN_EASE);
+        // Relearning starts from a day again
+        case.record(true, None, now);
+        assert_eq!(case.interval, 1.0);
+        assert_eq!(case.pass_rate(), Some(3.0 / 14.0));
+    }
+
+    #[test]
+    fn the_most_overdue_case_comes_first() {
+        let now = Local::now();
+        let case = |name: &str, due: Option<DateTime<Local>>| AlgCase {
+            due,
+            ..AlgCase::new(name, "R U R'")
+        };
+        let mut set = AlgSet {
+            name: "Test".to_string(),
+            cases: vec![
+                case("New", None),
+                case("Overdue", Some(now - days(1.0))),
+                case("Most overdue", Some(now - days(2.0))),
+                case("Later", Some(now + days(1.0))),
+            ],
+        };
+        assert_eq!(set.next(now), Some(2));
+        set.cases[2].due = Some(now + days(3.0));
+        assert_eq!(set.next(now), Some(1));
+        set.cases[1].due = Some(now + days(3.0));
+        assert_eq!(set.next(now), Some(0));
+        // Cases without an algorithm can't be drilled, and nothing else is due
+        set.cases[0].algorithm = " ".to_string();
+        assert_eq!(set.next(now), None);
+        assert_eq!(set.next_due(), Some(now + days(1.0)));
+        assert_eq!(set.next(now + days(1.0)), Some(3));
+    }
+}
@@ -0,0 +1,213 @@
+use chrono::{DateTime, Local};
+use std::cmp::Ordering;
+
+use super::event::{Event, Variant};
+use super::scramble::{Cubes, Scrambler};
+
+// Multi-Blind
+
+/// Seconds allowed for each cube attempted, and for the whole attempt.
+const SECONDS_PER_CUBE: f64 = 600.0;
+const MAX_SECONDS: f64 = 3600.0;
+
+/// Fewest cubes an attempt may have.
+pub const MIN_CUBES: usize = 2;
+
+/// Time limit of an attempt at `cubes` cubes, 10 minutes each up to an hour.
+pub fn time_limit(cubes: usize) -> f64 {
+    (cubes as f64 * SECONDS_PER_CUBE).min(MAX_SECONDS)
+}
+
+/// Writes seconds as minutes and seconds, such as "45:07".
+pub fn format_time(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// A finished Multi-BLD attempt.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+pub struct MultiAttempt {
+    pub date: DateTime<Local>, // When the attempt was finished
+    pub attempted: usize,      // Cubes attempted
+    pub solved: usize,         // Cubes solved
+    pub time: f64,             // Seconds taken, at most the time limit
+}
+
+impl MultiAttempt {
+    /// Points scored, one for each solved cube minus one for each unsolved one.
+    pub fn points(&self) -> i64 {
+        self.solved as i64 - (self.attempted - self.solved) as i64
+    }
+
+    /// Whether the attempt counts, needing at least two solved cubes and a point or more.
+    pub fn is_success(&self) -> bool {
+        self.points() >= 1 && self.solved >= 2
+    }
+
+    /// Orders attempts best first: successes by points, then by time, then by fewer unsolved cubes.
+    pub fn rank(&self, other: &MultiAttempt) -> Ordering {
+        other
+            .is_success()
+            .cmp(&self.is_success())
+            .then(other.points().cmp(&self.points()))
+            .then(self.time.total_cmp(&other.time))
+            .then((self.attempted - self.solved).cmp(&(other.attempted - other.solved)))
+    }
+}
+
+impl std::fmt::Display for MultiAttempt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let result = format!(
+            "{}/{} {}",
+            self.solved,
+            self.attempted,
+            format_time(self.time)
+        );
+        if self.is_success() {
+            write!(f, "{}", result)
+        } else {
+            write!(f, "DNF ({})", result)
+        }
+    }
+}
+
+/// Multi-BLD scrambles, the attempt in progress and the history of attempts.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct MultiBld {
+    pub cubes: usize,                   // Cubes to generate scrambles for
+    pub scrambles: Vec<String>,         // Scrambles of the current attempt
+    pub start: Option<DateTime<Local>>, // When the running attempt started
+    pub time: Option<f64>,              // Time of the stopped attempt, waiting for its solved count
+    pub solved: usize,                  // Solved count being entered
+    pub attempts: Vec<MultiAttempt>,    // Every finished attempt, oldest first
+}
+
+impl Default for MultiBld {
+    fn default() -> Self {
+        Self {
+            cubes: MIN_CUBES,
+            scrambles: vec![],
+            start: None,
+            time: None,
+            solved: 0,
+            attempts: vec![],
+        }
+    }
+}
+
+impl MultiBld {
+    /// Makes a new 3x3 blindfolded scramble for each cube.
+    pub fn generate(&mut self) {
+        let scrambler = Scrambler::from(Event::new(Cubes::ThreeByThree, Variant::Blindfolded));
+        self.scrambles = (0..self.cubes).map(|_| scrambler.scramble()).collect();
+        self.start = None;
+        self.time = None;
+    }
+
+    pub fn start(&mut self) {
+        self.start = Some(Local::now());
+        self.time = None;
+    }
+
+    /// Seconds since the attempt started, if it is running.
+    pub fn elapsed(&self) -> Option<f64> {
+        let start = self.start?;
+        Some(Local::now().signed_duration_since(start).num_milliseconds() as f64 / 1000.0)
+    }
+
+    /// Seconds left before the time limit, if the attempt is running.
+    pub fn remaining(&self) -> Option<f64> {
+        Some(time_limit(self.scrambles.len()) - self.elapsed()?)
+    }
+
+    /// Stops the attempt, capping its time at the limit.
+    pub fn stop(&mut self) {
+        if let Some(elapsed) = self.elapsed() {
+            self.time = Some(elapsed.min(time_limit(self.scrambles.len())));
+            self.start = None;
+            self.solved = 0;
+        }
+    }
+
+    /// Records the stopped attempt with the solved count entered, and clears its scrambles.
+    pub fn save(&mut self) {
+        if let Some(time) = self.time.take() {
+            self.attempts.push(MultiAttempt {
+                date: Local::now(),
+                attempted: self.scrambles.len(),
+                solved: self.solved.min(self.scrambles.len()),
+                time,
+            });
+            self.scrambles.clear();
+        }
+    }
+
+    /// The best attempt, by WCA ranking.
+    pub fn best(&self) -> Option<&MultiAttempt> {
+        self.attempts.iter().min_by(|a, b| a.rank(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(solved: usize, attempted: usize, time: f64) -> MultiAttempt {
+        MultiAttempt {
+            date: Local::now(),
+            attempted,
+            solved,
+            time,
+        }
+    }
+
+    #[test]
+    fn points_are_solved_minus_unsolved() {
+        assert_eq!(attempt(5, 6, 0.0).points(), 4);
+        assert_eq!(attempt(2, 4, 0.0).points(), 0);
+        assert_eq!(attempt(1, 5, 0.0).points(), -3);
+    }
+
+    #[test]
+    fn successes_need_a_point_and_two_solved_cubes() {
+        assert!(attempt(2, 2, 0.0).is_success());
+        assert!(attempt(3, 5, 0.0).is_success());
+        assert!(!attempt(2, 4, 0.0).is_success());
+        assert!(!attempt(1, 1, 0.0).is_success());
+        assert_eq!(attempt(2, 4, 1800.0).to_string(), "DNF (2/4 30:00)");
+        assert_eq!(attempt(3, 3, 1207.5).to_string(), "3/3 20:07");
+    }
+
+    #[test]
+    fn ties_in_points_go_to_the_faster_attempt() {
+        let mut multi = MultiBld {
+            attempts: vec![
+                attempt(4, 5, 2400.0),
+                attempt(3, 3, 1500.0),
+                attempt(5, 7, 1200.0),
+                attempt(9, 9, 3600.0),
+                attempt(1, 2, 60.0),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(multi.best(), Some(&multi.attempts[3]));
+        multi.attempts.remove(3);
+        // 5/7, 3/3 and 4/5 all score 3, so the fastest of them ranks first
+        assert_eq!(multi.best(), Some(&multi.attempts[2]));
+        let mut ranked = multi.attempts.clone();
+        ranked.sort_by(|a, b| a.rank(b));
+        let results: Vec<String> = ranked.iter().map(|a| a.to_string()).collect();
+        assert_eq!(
+            results,
+            ["5/7 20:00", "3/3 25:00", "4/5 40:00", "DNF (1/2 1:00)"]
+        );
+    }
+
+    #[test]
+    fn time_limit_is_ten_minutes_a_cube_up_to_an_hour() {
+        assert_eq!(time_limit(2), 1200.0);
+        assert_eq!(time_limit(6), 3600.0);
+        assert_eq!(time_limit(10), 3600.0);
+    }
+}
@@ -0,0 +1,584 @@
+use cubesim::MoveVariant;
+use std::fmt::Display;
+use std::ops::Range;
+
+use super::cubie;
+
+// Notation
+
+/// A face of the cube.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Face {
+    U,
+    R,
+    F,
+    D,
+    L,
+    B,
+}
+
+/// The middle layers, turned like L, D and F respectively.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Slice {
+    M,
+    E,
+    S,
+}
+
+/// A whole cube rotation, turned like R, U and F respectively.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// What a turn moves.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Kind {
+    /// Layers `first` to `last` counted in from `face`, the face itself being 1.
+    ///
+    /// "R" is 1 to 1, "Rw", "r" and "2Rw" are 1 to 2, "3Rw" is 1 to 3 and the inner slice "2R" is 2 to 2.
+    Layers {
+        face: Face,
+        first: u8,
+        last: u8,
+    },
+    Slice(Slice),
+    Rotation(Axis),
+}
+
+/// A move read from a scramble or algorithm, with where it was written.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Move {
+    pub kind: Kind,
+    /// Clockwise quarter turns, 1 to 3.
+    pub power: u8,
+    /// Byte range of the move in the text it was read from.
+    pub span: Range<usize>,
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self.kind {
+            Kind::Layers { face, first, last } => {
+                if first == 1 && last == 1 {
+                    format!("{:?}", face)
+                } else if first == 1 && last == 2 {
+                    format!("{:?}w", face)
+                } else if first == 1 {
+                    format!("{}{:?}w", last, face)
+                } else if first == last {
+                    format!("{}{:?}", first, face)
+                } else {
+                    format!("{}-{}{:?}w", first, last, face)
+                }
+            }
+            Kind::Slice(slice) => format!("{:?}", slice),
+            Kind::Rotation(axis) => format!("{:?}", axis).to_lowercase(),
+        };
+        write!(f, "{}", format_move(&name, variant(self.power)))
+    }
+}
+
+/// A solver's face turn, written as an outer layer turn.
+impl From<cubie::Turn> for Move {
+    fn from(turn: cubie::Turn) -> Move {
+        let face = [Face::U, Face::R, Face::F, Face::D, Face::L, Face::B][turn.face as usize];
+        Move {
+            kind: Kind::Layers {
+                face,
+                first: 1,
+                last: 1,
+            },
+            power: turn.power,
+            span: 0..0,
+        }
+    }
+}
+
+/// Adds the direction of a move to its name, as in "R2" and "R'".
+fn format_move(move_str: &str, variant: MoveVariant) -> String {
+    match variant {
+        MoveVariant::Double => format!("{}2", move_str),
+        MoveVariant::Standard => move_str.to_string(),
+        MoveVariant::Inverse => format!("{}'", move_str),
+    }
+}
+
+/// The `cubesim` direction of `power` clockwise quarter turns.
+fn variant(power: u8) -> MoveVariant {
+    [
+        MoveVariant::Standard,
+        MoveVariant::Double,
+        MoveVariant::Inverse,
+    ][power as usize - 1]
+}
+
+/// Writes moves out with single spaces between them, such as "R U R' U'".
+pub fn turns_string<T: Clone + Into<Move>>(turns: &[T]) -> String {
+    turns
+        .iter()
+        .map(|turn| turn.clone().into().to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Why some text couldn't be read as moves, and which part of it is wrong.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    pub message: String,
+    /// Byte range of the offending text.
+    pub span: Range<usize>,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at character {})", self.message, self.span.start + 1)
+    }
+}
+
+/// Reads moves written in WCA notation, also accepting slices (M, E, S), lowercase
+/// wide turns ("r"), SiGN layer counts ("3Rw", "3r") and inner slices ("2R").
+pub fn parse(text: &str) -> Result<Vec<Move>, ParseError> {
+    let mut turns = vec![];
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(from)) => {
+                turns.push(parse_turn(&text[from..i], from)?);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    Ok(turns)
+}
+
+/// Reads a single move, `offset` being where it starts in the whole text.
+fn parse_turn(token: &str, offset: usize) -> Result<Move, ParseError> {
+    let error = |message: String, span: Range<usize>| ParseError {
+        message,
+        span: offset + span.start..offset + span.end,
+    };
+    let digits = token.chars().take_while(|c| c.is_ascii_digit()).count();
+    let count: Option<u8> = match digits {
+        0 => None,
+        _ => match token[..digits].parse() {
+            Ok(count) if count > 0 => Some(count),
+            _ => {
+                return Err(error(
+                    format!("\"{}\" isn't a layer count", &token[..digits]),
+                    0..digits,
+                ))
+            }
+        },
+    };
+    let Some(letter) = token[digits..].chars().next() else {
+        return Err(error(
+            format!("\"{}\" is missing a face", token),
+            0..token.len(),
+        ));
+    };
+    let mut rest = digits + letter.len_utf8();
+    let wide = token[rest..].starts_with('w');
+    if wide {
+        rest += 1;
+    }
+    let face = match letter.to_ascii_uppercase() {
+        'U' => Some(Face::U),
+        'R' => Some(Face::R),
+        'F' => Some(Face::F),
+        'D' => Some(Face::D),
+        'L' => Some(Face::L),
+        'B' => Some(Face::B),
+        _ => None,
+    };
+    let kind = match (face, letter) {
+        (Some(_), _) if letter.is_ascii_lowercase() && wide => {
+            return Err(error(
+                format!(
+                    "\"{}\" is already a wide turn, so it can't have a \"w\"",
+                    letter
+                ),
+                digits..rest,
+            ));
+        }
+        (Some(face), _) if letter.is_ascii_lowercase() || wide => Kind::Layers {
+            face,
+            first: 1,
+            last: count.unwrap_or(2),
+        },
+        (Some(face), _) => {
+            let layer = count.unwrap_or(1);
+            Kind::Layers {
+                face,
+                first: layer,
+                last: layer,
+            }
+        }
+        (None, 'M' | 'E' | 'S' | 'x' | 'y' | 'z') => {
+            if count.is_some() || wide {
+                return Err(error(
+                    format!("\"{}\" can't have a layer count or a \"w\"", letter),
+                    0..rest,
+                ));
+            }
+            match letter {
+                'M' => Kind::Slice(Slice::M),
+                'E' => Kind::Slice(Slice::E),
+                'S' => Kind::Slice(Slice::S),
+                'x' => Kind::Rotation(Axis::X),
+                'y' => Kind::Rotation(Axis::Y),
+                _ => Kind::Rotation(Axis::Z),
+            }
+        }
+        _ => {
+            let end = digits + letter.len_utf8();
+            return Err(error(
+                format!("\"{}\" isn't a face, slice or rotation", letter),
+                digits..end,
+            ));
+        }
+    };
+    // Both ' and the ’ that gets pasted from web pages mean anticlockwise
+    let power = match &token[rest..] {
+        "" => 1,
+        "2" | "2'" | "2’" => 2,
+        "'" | "’" => 3,
+        suffix => {
+            return Err(error(
+                format!("\"{}\" isn't a turn amount, use nothing, 2 or '", suffix),
+                rest..token.len(),
+            ))
+        }
+    };
+    Ok(Move {
+        kind,
+        power,
+        span: offset..offset + token.len(),
+    })
+}
+
+/// Turns parsed moves into `cubesim` moves for a cube of `size`, checking every move fits on it.
+pub fn moves(turns: &[Move], size: i32) -> Result<Vec<cubesim::Move>, ParseError> {
+    let mut moves = vec![];
+    for turn in turns {
+        let back = variant(4 - turn.power);
+        let variant = variant(turn.power);
+        let error = |message: String| ParseError {
+            message,
+            span: turn.span.clone(),
+        };
+        let wide = |face: Face, layers: i32, variant: MoveVariant| match face {
+            Face::U => cubesim::Move::Uw(layers, variant),
+            Face::R => cubesim::Move::Rw(layers, variant),
+            Face::F => cubesim::Move::Fw(layers, variant),
+            Face::D => cubesim::Move::Dw(layers, variant),
+            Face::L => cubesim::Move::Lw(layers, variant),
+            Face::B => cubesim::Move::Bw(layers, variant),
+        };
+        // Inner layers are a wide turn with the layers outside them turned back
+        let layers = |face: Face, first: i32, last: i32| {
+            let mut moves = vec![wide(face, last, variant)];
+            if first > 1 {
+                moves.push(wide(face, first - 1, back));
+            }
+            moves
+        };
+        match turn.kind {
+            Kind::Layers { face, first, last } => {
+                if last as i32 > size {
+                    return Err(error(format!(
+                        "\"{}\" turns layer {}, but a {}x{} only has {}",
+                        turn, last, size, size, size
+                    )));
+                }
+                moves.extend(layers(face, first as i32, last as i32));
+            }
+            Kind::Slice(slice) => {
+                if size < 3 {
+                    return Err(error(format!(
+                        "A {}x{} has no middle layer to turn with {:?}",
+                        size, size, slice
+                    )));
+                }
+                let face = match slice {
+                    Slice::M => Face::L,
+                    Slice::E => Face::D,
+                    Slice::S => Face::F,
+                };
+                moves.extend(layers(face, 2, size - 1));
+            }
+            Kind::Rotation(axis) => moves.push(match axis {
+                Axis::X => cubesim::Move::X(variant),
+                Axis::Y => cubesim::Move::Y(variant),
+                Axis::Z => cubesim::Move::Z(variant),
+            }),
+        }
+    }
+    Ok(moves)
+}
+
+/// Reads text as `cubesim` moves for a cube of `size`.
+pub fn parse_moves(text: &str, size: i32) -> Result<Vec<cubesim::Move>, ParseError> {
+    moves(&parse(text)?, size)
+}
+
+// Algorithm Tools
+
+fn opposite(face: Face) -> Face {
+    match face {
+        Face::U => Face::D,
+        Face::R => Face::L,
+        Face::F => Face::B,
+        Face::D => Face::U,
+        Face::L => Face::R,
+        Face::B => Face::F,
+    }
+}
+
+/// The axis a move turns around.
+fn axis(kind: Kind) -> Axis {
+    match kind {
+        Kind::Layers {
+            face: Face::R | Face::L,
+            ..
+        }
+        | Kind::Slice(Slice::M) => Axis::X,
+        Kind::Layers {
+            face: Face::U | Face::D,
+            ..
+        }
+        | Kind::Slice(Slice::E) => Axis::Y,
+        Kind::Layers { .. } | Kind::Slice(Slice::S) => Axis::Z,
+        Kind::Rotation(axis) => axis,
+    }
+}
+
+/// The moves undoing `turns`.
+pub fn invert(turns: &[Move]) -> Vec<Move> {
+    turns
+        .iter()
+        .rev()
+        .map(|turn| Move {
+            power: 4 - turn.power,
+            ..turn.clone()
+        })
+        .collect()
+}
+
+/// Merges repeated moves and cancels ones undoing each other, such as "R R" into "R2" and "R L R'" into "L".
+///
+/// Moves around the same axis don't affect each other, so they're merged across one another.
+pub fn simplify(turns: &[Move]) -> Vec<Move> {
+    let mut result: Vec<Move> = vec![];
+    for turn in turns {
+        let same_axis = result
+            .iter()
+            .rev()
+            .take_while(|other| axis(other.kind) == axis(turn.kind))
+            .count();
+        let start = result.len() - same_axis;
+        match result[start..]
+            .iter()
+            .position(|other| other.kind == turn.kind)
+        {
+            Some(i) => {
+                let merged = &mut result[start + i];
+                merged.power = (merged.power + turn.power) % 4;
+                if merged.power == 0 {
+                    result.remove(start + i);
+                }
+            }
+            None => result.push(turn.clone()),
+        }
+    }
+    result
+}
+
+/// Mirrors `turns` across the plane of the `plane` slice, so mirroring "R U R'" across M gives "L' U' L".
+pub fn mirror(turns: &[Move], plane: Slice) -> Vec<Move> {
+    let across = axis(Kind::Slice(plane));
+    turns
+        .iter()
+        .map(|turn| {
+            let (kind, power) = match turn.kind {
+                Kind::Layers { face, first, last } if axis(turn.kind) == across => (
+                    Kind::Layers {
+                        face: opposite(face),
+                        first,
+                        last,
+                    },
+                    4 - turn.power,
+                ),
+                // Slices and rotations in the mirror's plane still turn the same way
+                kind if axis(kind) == across => (kind, turn.power),
+                kind => (kind, 4 - turn.power),
+            };
+            Move {
+                kind,
+                power,
+                span: turn.span.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Ways of counting the moves of an algorithm.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Metric {
+    /// Half turn metric, every outer or wide turn counts as 1 and slices as 2.
+    Htm,
+    /// Quarter turn metric, like HTM but with half turns counting twice.
+    Qtm,
+    /// Slice turn metric, every turn including slices counts as 1.
+    Stm,
+    /// Execution turn metric, every move including rotations counts as 1.
+    Etm,
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Metric::Htm => "HTM",
+            Metric::Qtm => "QTM",
+            Metric::Stm => "STM",
+            Metric::Etm => "ETM",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+/// Number of moves in `turns` counted in `metric`.
+pub fn count(turns: &[Move], metric: Metric) -> usize {
+    turns
+        .iter()
+        .map(|turn| {
+            let half = if turn.power == 2 { 2 } else { 1 };
+            // Layers not including a face take two outer turns
+            let outer = match turn.kind {
+                Kind::Layers { first: 1, .. } => 1,
+                Kind::Layers { .. } | Kind::Slice(_) => 2,
+                Kind::Rotation(_) => 0,
+            };
+            match metric {
+                Metric::Htm => outer,
+                Metric::Qtm => outer * half,
+                Metric::Stm => outer.min(1),
+                Metric::Etm => 1,
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(text: &str) -> String {
+        turns_string(&parse(text).unwrap())
+    }
+
+    #[test]
+    fn reads_every_kind_of_move() {
+        let turns = parse("R U2 r' 3Rw2 2R M x’").unwrap();
+        let kinds: Vec<Kind> = turns.iter().map(|turn| turn.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                Kind::Layers {
+                    face: Face::R,
+                    first: 1,
+                    last: 1
+                },
+                Kind::Layers {
+                    face: Face::U,
+                    first: 1,
+                    last: 1
+                },
+                Kind::Layers {
+                    face: Face::R,
+                    first: 1,
+                    last: 2
+                },
+                Kind::Layers {
+                    face: Face::R,
+                    first: 1,
+                    last: 3
+                },
+                Kind::Layers {
+                    face: Face::R,
+                    first: 2,
+                    last: 2
+                },
+                Kind::Slice(Slice::M),
+                Kind::Rotation(Axis::X),
+            ]
+        );
+        let powers: Vec<u8> = turns.iter().map(|turn| turn.power).collect();
+        assert_eq!(powers, vec![1, 2, 3, 2, 1, 1, 3]);
+        assert_eq!(turns[1].span, 2..4);
+    }
+
+    #[test]
+    fn writes_moves_back_out() {
+        assert_eq!(
+            written("  R  U2\tr' 3Rw2 2R M x’ "),
+            "R U2 Rw' 3Rw2 2R M x'"
+        );
+        assert_eq!(written(""), "");
+    }
+
+    #[test]
+    fn writes_solver_turns_like_parsed_ones() {
+        let turns: Vec<cubie::Turn> = [0, 4, 8, 10, 12, 17]
+            .into_iter()
+            .map(cubie::Turn::from_index)
+            .collect();
+        assert_eq!(turns_string(&turns), "U R2 F' D2 L B'");
+        let parsed = parse("U R2 F' D2 L B'").unwrap();
+        for (turn, parsed) in turns.into_iter().zip(parsed) {
+            let turn = Move::from(turn);
+            assert_eq!((turn.kind, turn.power), (parsed.kind, parsed.power));
+        }
+    }
+
+    #[test]
+    fn points_at_what_is_wrong() {
+        assert_eq!(parse("R Q U").unwrap_err().span, 2..3);
+        assert_eq!(parse("R U3").unwrap_err().span, 3..4);
+        assert_eq!(parse("rw").unwrap_err().span, 0..2);
+        assert_eq!(parse("2M").unwrap_err().span, 0..2);
+        assert_eq!(parse("0R").unwrap_err().span, 0..1);
+    }
+
+    #[test]
+    fn merges_and_cancels_moves() {
+        let simplified = |text: &str| turns_string(&simplify(&parse(text).unwrap()));
+        assert_eq!(simplified("R R"), "R2");
+        assert_eq!(simplified("R L R'"), "L");
+        assert_eq!(simplified("R U U' R'"), "");
+        assert_eq!(simplified("R2 R2 U"), "U");
+        // Moves around other axes stop the merging
+        assert_eq!(simplified("R U R"), "R U R");
+    }
+
+    #[test]
+    fn mirrors_across_a_plane() {
+        let mirrored = |text: &str, plane| turns_string(&mirror(&parse(text).unwrap(), plane));
+        assert_eq!(mirrored("R U R'", Slice::M), "L' U' L");
+        assert_eq!(mirrored("M x", Slice::M), "M x");
+        assert_eq!(mirrored("R U F", Slice::E), "R' D' F'");
+        assert_eq!(
+            mirrored(&mirrored("R U2 F' r", Slice::S), Slice::S),
+            "R U2 F' Rw"
+        );
+    }
+
+    #[test]
+    fn undoes_moves() {
+        assert_eq!(turns_string(&invert(&parse("R U2 F'").unwrap())), "F U2 R'");
+    }
+}
@@ -0,0 +1,623 @@
+use cubesim::{Cube, Face, FaceletCube, Move, MoveVariant};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use super::cubie::{self, parity, CubieCube};
+use super::kociemba::{self, SolverOptions};
+use super::notation;
+
+// NxN Reduction
+
+/// Where each sticker came from, `permutation[to] = from`, with stickers numbered as in `FaceletCube::state`.
+type Permutation = Vec<u16>;
+
+/// The permutation of doing `first`, then `second`.
+fn compose(first: &[u16], second: &[u16]) -> Permutation {
+    second.iter().map(|&from| first[from as usize]).collect()
+}
+
+fn inverse(permutation: &[u16]) -> Permutation {
+    let mut result = vec![0; permutation.len()];
+    for (to, &from) in permutation.iter().enumerate() {
+        result[from as usize] = to as u16;
+    }
+    result
+}
+
+/// The permutation of applying `moves` to a solved cube of `size`.
+fn permutation(size: usize, moves: &[Move]) -> Permutation {
+    let cube = FaceletCube::new(size as i32).apply_moves(moves);
+    let mut permutation = vec![0u16; 6 * size * size];
+    // Masking reveals one bit of where every sticker started per pass
+    for bit in 0..16 {
+        if 1 << bit >= permutation.len() {
+            break;
+        }
+        let state = cube
+            .mask(&|index, _| {
+                if index >> bit & 1 == 1 {
+                    Face::U
+                } else {
+                    Face::D
+                }
+            })
+            .state();
+        for (to, face) in state.into_iter().enumerate() {
+            if face == Face::U {
+                permutation[to] |= 1 << bit;
+            }
+        }
+    }
+    permutation
+}
+
+/// A turn of a single layer, `depth` layers in from `face` (0 to 5 for U, R, F, D, L, B),
+/// by `power` clockwise quarter turns (1 to 3).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Layer {
+    pub face: u8,
+    pub depth: u8,
+    pub power: u8,
+}
+
+impl Layer {
+    fn inverse(&self) -> Layer {
+        Layer {
+            power: 4 - self.power,
+            ..*self
+        }
+    }
+}
+
+/// A layer turn, written with the layer's number for inner layers, as in "2R".
+impl From<Layer> for notation::Move {
+    fn from(layer: Layer) -> notation::Move {
+        let mut turn = notation::Move::from(cubie::Turn {
+            face: layer.face,
+            power: layer.power,
+        });
+        if let notation::Kind::Layers { first, last, .. } = &mut turn.kind {
+            *first = layer.depth + 1;
+            *last = layer.depth + 1;
+        }
+        turn
+    }
+}
+
+/// Merges turns of the same layer that follow each other.
+fn simplify(layers: &[Layer]) -> Vec<Layer> {
+    let mut result: Vec<Layer> = vec![];
+    for &layer in layers {
+        match result.last_mut() {
+            Some(last) if last.face == layer.face && last.depth == layer.depth => {
+                last.power = (last.power + layer.power) % 4;
+                if last.power == 0 {
+                    result.pop();
+                }
+            }
+            _ => result.push(layer),
+        }
+    }
+    result
+}
+
+/// A pure 3-cycle of pieces in one orbit, with the stickers it moves as `(to, from)` pairs.
+struct Cycle {
+    stickers: Vec<(u16, u16)>,
+    layers: Vec<Layer>,
+}
+
+/// Pieces that can be swapped with each other, and the 3-cycles solving them.
+struct Orbit {
+    pieces: Vec<usize>,
+    cycles: Vec<Cycle>,
+}
+
+/// Everything the solver needs to know about one size of cube, worked out from `cubesim`.
+struct Puzzle {
+    size: usize,
+    /// Quarter turn of every layer the solver may turn, inner layers from one side only.
+    turns: Vec<(Layer, Permutation)>,
+    /// The 24 whole cube rotations.
+    rotations: Vec<Permutation>,
+    /// Piece each sticker belongs to, and the stickers of each piece.
+    piece: Vec<usize>,
+    pieces: Vec<Vec<usize>>,
+    centres: Vec<Orbit>,
+    wings: Vec<Orbit>,
+}
+
+static PUZZLES: Mutex<Vec<Arc<Puzzle>>> = Mutex::new(Vec::new());
+
+fn puzzle(size: usize) -> Arc<Puzzle> {
+    let mut puzzles = PUZZLES.lock().unwrap();
+    if let Some(puzzle) = puzzles.iter().find(|puzzle| puzzle.size == size) {
+        return puzzle.clone();
+    }
+    let puzzle = Arc::new(Puzzle::new(size));
+    puzzles.push(puzzle.clone());
+    puzzle
+}
+
+/// Whether the commutators for `size` are worked out, so solving won't have to wait for them.
+pub fn tables_ready(size: usize) -> bool {
+    PUZZLES.lock().map_or(false, |puzzles| {
+        puzzles.iter().any(|puzzle| puzzle.size == size)
+    })
+}
+
+impl Puzzle {
+    fn new(size: usize) -> Puzzle {
+        let wide = |face: u8, layers: i32| {
+            let variant = MoveVariant::Standard;
+            let turn = match face {
+                0 => Move::Uw(layers, variant),
+                1 => Move::Rw(layers, variant),
+                2 => Move::Fw(layers, variant),
+                3 => Move::Dw(layers, variant),
+                4 => Move::Lw(layers, variant),
+                _ => Move::Bw(layers, variant),
+            };
+            permutation(size, &[turn])
+        };
+        let mut turns = vec![];
+        for face in 0..6 {
+            // The middle layer of odd cubes is only turned from the U, R and F sides
+            let depths = if size % 2 == 1 && face < 3 {
+                size / 2 + 1
+            } else {
+                size / 2
+            };
+            for depth in 0..depths {
+                let layer = Layer {
+                    face,
+                    depth: depth as u8,
+                    power: 1,
+                };
+                let turn = if depth == 0 {
+                    wide(face, 1)
+                } else {
+                    compose(
+                        &wide(face, depth as i32 + 1),
+                        &inverse(&wide(face, depth as i32)),
+                    )
+                };
+                turns.push((layer, turn));
+            }
+        }
+
+        let mut rotations = vec![permutation(size, &[])];
+        let axes = [
+            permutation(size, &[Move::X(MoveVariant::Standard)]),
+            permutation(size, &[Move::Y(MoveVariant::Standard)]),
+        ];
+        let mut i = 0;
+        while i < rotations.len() {
+            for axis in &axes {
+                let rotation = compose(&rotations[i], axis);
+                if !rotations.contains(&rotation) {
+                    rotations.push(rotation);
+                }
+            }
+            i += 1;
+        }
+
+        // Stickers moved by the same turns belong to the same piece
+        let stickers = 6 * size * size;
+        let mut signatures: HashMap<Vec<bool>, usize> = HashMap::new();
+        let mut piece = vec![0; stickers];
+        let mut pieces: Vec<Vec<usize>> = vec![];
+        for sticker in 0..stickers {
+            let signature: Vec<bool> = turns
+                .iter()
+                .map(|(_, turn)| turn[sticker] as usize != sticker)
+                .collect();
+            let next = signatures.len();
+            let index = *signatures.entry(signature).or_insert(next);
+            if index == pieces.len() {
+                pieces.push(vec![]);
+            }
+            pieces[index].push(sticker);
+            piece[sticker] = index;
+        }
+
+        // Pieces that turns move into each other's places make up an orbit
+        let mut orbit: Vec<usize> = (0..pieces.len()).collect();
+        fn root(orbit: &mut [usize], piece: usize) -> usize {
+            if orbit[piece] != piece {
+                orbit[piece] = root(orbit, orbit[piece]);
+            }
+            orbit[piece]
+        }
+        for (_, turn) in &turns {
+            for (to, &from) in turn.iter().enumerate() {
+                let (a, b) = (
+                    root(&mut orbit, piece[to]),
+                    root(&mut orbit, piece[from as usize]),
+                );
+                orbit[a] = b;
+            }
+        }
+        let mut orbits: HashMap<usize, Vec<usize>> = HashMap::new();
+        for p in 0..pieces.len() {
+            let r = root(&mut orbit, p);
+            orbits.entry(r).or_default().push(p);
+        }
+        let mut orbits: Vec<Vec<usize>> = orbits.into_values().collect();
+        orbits.sort();
+
+        let mut puzzle = Puzzle {
+            size,
+            turns,
+            rotations,
+            piece,
+            pieces,
+            centres: vec![],
+            wings: vec![],
+        };
+        let commutators = puzzle.commutators();
+        for pieces in orbits {
+            // Corners, midges and the fixed centers are left to the 3x3 stage
+            let wing = match (pieces.len(), puzzle.pieces[pieces[0]].len()) {
+                (24, 1) => false,
+                (24, 2) => true,
+                _ => continue,
+            };
+            let cycles = puzzle.cycles(&pieces, &commutators);
+            let orbit = Orbit { pieces, cycles };
+            if wing {
+                puzzle.wings.push(orbit);
+            } else {
+                puzzle.centres.push(orbit);
+            }
+        }
+        puzzle
+    }
+
+    /// The permutation of a layer turn.
+    fn turn(&self, layer: Layer) -> Permutation {
+        let (_, quarter) = self
+            .turns
+            .iter()
+            .find(|(turn, _)| turn.face == layer.face && turn.depth == layer.depth)
+            .unwrap();
+        let mut permutation = quarter.clone();
+        for _ in 1..layer.power {
+            permutation = compose(&permutation, quarter);
+        }
+        permutation
+    }
+
+    /// Every turn of every layer with its permutation.
+    fn all_turns(&self) -> Vec<(Layer, Permutation)> {
+        let mut all = vec![];
+        for (layer, _) in &self.turns {
+            for power in 1..=3 {
+                let layer = Layer { power, ..*layer };
+                all.push((layer, self.turn(layer)));
+            }
+        }
+        all
+    }
+
+    /// Commutators `[A, B]` moving only 3 pieces, with `B` a single turn and `A`
+    /// either a single turn or an outer turn conjugated by one, as in `[r U r', 2R]`.
+    fn commutators(&self) -> Vec<Cycle> {
+        let all = self.all_turns();
+        let mut setups: Vec<(Vec<Layer>, Permutation)> = all
+            .iter()
+            .map(|(layer, turn)| (vec![*layer], turn.clone()))
+            .collect();
+        for (x, x_turn) in &all {
+            for (y, y_turn) in all.iter().filter(|(y, _)| y.depth == 0) {
+                let turn = compose(&compose(x_turn, y_turn), &inverse(x_turn));
+                setups.push((vec![*x, *y, x.inverse()], turn));
+            }
+        }
+        let mut commutators = vec![];
+        for (a, a_turn) in &setups {
+            let a_inverse = inverse(a_turn);
+            for (b, b_turn) in &all {
+                let turn = compose(
+                    &compose(&compose(a_turn, b_turn), &a_inverse),
+                    &inverse(b_turn),
+                );
+                let stickers: Vec<(u16, u16)> = turn
+                    .iter()
+                    .enumerate()
+                    .filter(|(to, &from)| *to != from as usize)
+                    .map(|(to, &from)| (to as u16, from))
+                    .collect();
+                let mut moved: Vec<usize> = stickers
+                    .iter()
+                    .map(|&(to, _)| self.piece[to as usize])
+                    .collect();
+                moved.sort();
+                moved.dedup();
+                if moved.len() != 3 {
+                    continue;
+                }
+                let mut layers = a.clone();
+                layers.push(*b);
+                layers.extend(a.iter().rev().map(|layer| layer.inverse()));
+                layers.push(b.inverse());
+                commutators.push(Cycle { stickers, layers });
+            }
+        }
+        commutators
+    }
+
+    /// The shortest known 3-cycle for every 3 pieces of an orbit, found by setting up commutators.
+    fn cycles(&self, pieces: &[usize], commutators: &[Cycle]) -> Vec<Cycle> {
+        let all = self.all_turns();
+        let mut cycles: Vec<Cycle> = vec![];
+        let mut seen: HashSet<Vec<(u16, u16)>> = HashSet::new();
+        let mut add =
+            |mut stickers: Vec<(u16, u16)>, layers: Vec<Layer>, cycles: &mut Vec<Cycle>| {
+                stickers.sort();
+                if seen.insert(stickers.clone()) {
+                    cycles.push(Cycle { stickers, layers });
+                }
+            };
+        let mut base: Vec<&Cycle> = commutators
+            .iter()
+            .filter(|cycle| pieces.contains(&self.piece[cycle.stickers[0].0 as usize]))
+            .collect();
+        base.sort_by_key(|cycle| cycle.layers.len());
+        for cycle in base {
+            add(cycle.stickers.clone(), cycle.layers.clone(), &mut cycles);
+        }
+        // Breadth first, so each 3-cycle keeps the shortest setup reaching it
+        let mut index = 0;
+        while index < cycles.len() {
+            for (layer, turn) in &all {
+                let stickers: Vec<(u16, u16)> = cycles[index]
+                    .stickers
+                    .iter()
+                    .map(|&(to, from)| (turn[to as usize], turn[from as usize]))
+                    .collect();
+                let mut layers = vec![*layer];
+                layers.extend(cycles[index].layers.iter().copied());
+                layers.push(layer.inverse());
+                add(stickers, layers, &mut cycles);
+            }
+            index += 1;
+        }
+        cycles
+    }
+
+    /// Whether the pieces of an orbit are in an odd permutation.
+    fn odd(&self, state: &[u16], orbit: &Orbit) -> bool {
+        let permutation: Vec<u8> = orbit
+            .pieces
+            .iter()
+            .map(|&p| {
+                let from = self.piece[state[self.pieces[p][0]] as usize];
+                orbit.pieces.iter().position(|&q| q == from).unwrap() as u8
+            })
+            .collect();
+        parity(&permutation)
+    }
+
+    /// Reads the corners, and the midges and centers of odd cubes, as a 3x3.
+    fn reduced(&self, state: &[u16]) -> Option<CubieCube> {
+        let (size, area) = (self.size, self.size * self.size);
+        let place = [0, size / 2, size - 1];
+        let mut facelets = [0usize; 54];
+        for (facelet, face) in facelets.iter_mut().enumerate() {
+            let sticker = facelet / 9 * area + place[facelet % 9 / 3] * size + place[facelet % 3];
+            *face = state[sticker] as usize / area;
+        }
+        if size % 2 == 0 {
+            CubieCube::from_corner_faces(&facelets)
+        } else {
+            CubieCube::from_facelet_faces(&facelets)
+        }
+    }
+}
+
+/// Does `layer` to `state`, noting it in `solution`.
+fn apply(puzzle: &Puzzle, state: &mut Permutation, solution: &mut Vec<Layer>, layer: Layer) {
+    *state = compose(state, &puzzle.turn(layer));
+    solution.push(layer);
+}
+
+/// Solves an orbit with 3-cycles, greedily taking whichever puts the most stickers right.
+fn reduce(
+    state: &mut Permutation,
+    orbit: &Orbit,
+    solution: &mut Vec<Layer>,
+    solved: &dyn Fn(usize, u16) -> bool,
+) {
+    loop {
+        let best = orbit
+            .cycles
+            .iter()
+            .filter_map(|cycle| {
+                let gain: i32 = cycle
+                    .stickers
+                    .iter()
+                    .map(|&(to, from)| {
+                        solved(to as usize, state[from as usize]) as i32
+                            - solved(to as usize, state[to as usize]) as i32
+                    })
+                    .sum();
+                (gain > 0).then_some((gain, cycle))
+            })
+            .max_by_key(|(gain, cycle)| (*gain, Reverse(cycle.layers.len())));
+        let Some((_, cycle)) = best else {
+            return;
+        };
+        let before = state.clone();
+        for &(to, from) in &cycle.stickers {
+            state[to as usize] = before[from as usize];
+        }
+        solution.extend(cycle.layers.iter().copied());
+    }
+}
+
+/// Solves a 4x4 or bigger by reduction: centers, then the corners and midges as a 3x3, then the wings.
+///
+/// Returns the solution, or why the cube couldn't be solved.
+pub fn solve(moves: &[Move], size: usize, options: SolverOptions) -> Result<Vec<Layer>, String> {
+    let puzzle = puzzle(size);
+    let area = size * size;
+    let scrambled = permutation(size, moves);
+
+    // Solve towards the orientation the cube is in, found from its fixed centers, or the DBL corner on even cubes
+    let reference: Vec<usize> = if size % 2 == 1 {
+        (0..6)
+            .map(|face| face * area + size / 2 * size + size / 2)
+            .collect()
+    } else {
+        vec![
+            3 * area + (size - 1) * size,
+            5 * area + area - 1,
+            4 * area + (size - 1) * size,
+        ]
+    };
+    let rotation = puzzle
+        .rotations
+        .iter()
+        .find(|rotation| {
+            reference
+                .iter()
+                .all(|&sticker| rotation[sticker] == scrambled[sticker])
+        })
+        .ok_or("Bad Scramble")?;
+    // Stickers are relabelled so that the cube counts as solved in that orientation
+    let relabel = inverse(rotation);
+    let mut state: Permutation = scrambled
+        .iter()
+        .map(|&from| relabel[from as usize])
+        .collect();
+    let mut solution = vec![];
+
+    // Wings only change parity with inner slice quarter turns, so fix that before the centers
+    for orbit in &puzzle.wings {
+        if !puzzle.odd(&state, orbit) {
+            continue;
+        }
+        let slice = puzzle
+            .turns
+            .iter()
+            .find(|(layer, turn)| {
+                layer.face == 1
+                    && layer.depth > 0
+                    && puzzle
+                        .wings
+                        .iter()
+                        .all(|other| puzzle.odd(turn, other) == std::ptr::eq(other, orbit))
+            })
+            .map(|(layer, _)| *layer)
+            .ok_or("Couldn't fix the edge parity")?;
+        apply(&puzzle, &mut state, &mut solution, slice);
+    }
+
+    for orbit in &puzzle.centres {
+        reduce(&mut state, orbit, &mut solution, &|to, from| {
+            to / area == from as usize / area
+        });
+    }
+
+    // Even cubes have no edges in the 3x3 stage, so the corners must be in an even permutation
+    if size % 2 == 0
+        && puzzle
+            .reduced(&state)
+            .map_or(false, |cube| parity(&cube.cp))
+    {
+        let turn = Layer {
+            face: 0,
+            depth: 0,
+            power: 1,
+        };
+        apply(&puzzle, &mut state, &mut solution, turn);
+    }
+    let cube = puzzle.reduced(&state).ok_or("Bad Scramble")?;
+    let options = SolverOptions {
+        count: 1,
+        ..options
+    };
+    let turns = kociemba::solve(&cube, options)?
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    for turn in turns {
+        let layer = Layer {
+            face: turn.face,
+            depth: 0,
+            power: turn.power,
+        };
+        apply(&puzzle, &mut state, &mut solution, layer);
+    }
+
+    for orbit in &puzzle.wings {
+        reduce(&mut state, orbit, &mut solution, &|to, from| {
+            to == from as usize
+        });
+    }
+
+    if state
+        .iter()
+        .enumerate()
+        .any(|(to, &from)| to / area != from as usize / area)
+    {
+        return Err("Couldn't solve the cube".to_string());
+    }
+    Ok(simplify(&solution))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: SolverOptions = SolverOptions {
+        max_length: 30,
+        time: 0.1,
+        count: 1,
+    };
+
+    /// Whether doing the solution after `scramble` leaves the cube solved in some orientation.
+    fn solves(scramble: &str, size: usize) -> bool {
+        let mut all = cubesim::parse_scramble(scramble.to_string());
+        let solution = solve(&all, size, OPTIONS).unwrap();
+        let solution: Vec<notation::Move> =
+            solution.into_iter().map(notation::Move::from).collect();
+        all.extend(notation::moves(&solution, size as i32).unwrap());
+        let state = FaceletCube::new(size as i32).apply_moves(&all).state();
+        state
+            .chunks(size * size)
+            .all(|face| face.iter().all(|&sticker| sticker == face[0]))
+    }
+
+    #[test]
+    fn solves_a_4x4() {
+        assert!(solves("Rw U2 Fw' R D2 Uw L' Rw R' B2 Rw2 F U'", 4));
+    }
+
+    #[test]
+    fn solves_a_4x4_with_parity() {
+        assert!(solves("Rw R'", 4));
+        assert!(solves("Rw U2 Rw U2", 4));
+    }
+
+    #[test]
+    fn solves_a_5x5() {
+        assert!(solves("Rw U2 3Fw' R D2 Uw L' Rw R' B2 3Rw2 F U' Dw D'", 5));
+    }
+
+    #[test]
+    fn merges_turns_of_the_same_layer() {
+        let layer = |face, power| Layer {
+            face,
+            depth: 1,
+            power,
+        };
+        assert_eq!(
+            simplify(&[layer(1, 1), layer(1, 1), layer(0, 1)]),
+            vec![layer(1, 2), layer(0, 1)]
+        );
+        assert_eq!(simplify(&[layer(1, 1), layer(1, 3)]), vec![]);
+    }
+}
@@ -0,0 +1,112 @@
+use rand::Rng;
+
+use super::event::Event;
+use super::scramble::{Cubes, Scrambler};
+
+// Relay
+
+/// One puzzle of a relay, with its scramble and how long it took.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+pub struct RelayPart {
+    pub puzzle: Cubes,      // Puzzle scrambled
+    pub scramble: String,   // Scramble of the puzzle
+    pub split: Option<f64>, // Seconds spent on this puzzle, if splits were recorded
+}
+
+/// A scramble for every puzzle of a relay, in the order they are solved.
+pub fn scrambles(event: Event, rng: &mut impl Rng) -> Vec<RelayPart> {
+    event
+        .puzzles()
+        .into_iter()
+        .map(|puzzle| RelayPart {
+            puzzle,
+            scramble: Scrambler::from(puzzle).scramble_with(rng),
+            split: None,
+        })
+        .collect()
+}
+
+/// Writes the scrambles of a relay one per line, such as "2x2: R U R'".
+pub fn scramble_text(parts: &[RelayPart]) -> String {
+    parts
+        .iter()
+        .map(|part| format!("{}: {}", part.puzzle, part.scramble))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Time spent on each puzzle, from the times they were finished at.
+pub fn splits(finished: &[f64]) -> Vec<f64> {
+    finished
+        .iter()
+        .enumerate()
+        .map(|(i, end)| end - if i == 0 { 0.0 } else { finished[i - 1] })
+        .collect()
+}
+
+/// Fills in the split of each puzzle from the times each one but the last was finished at.
+///
+/// Splits are left out unless every puzzle but the last was marked as finished.
+pub fn with_splits(parts: &[RelayPart], finished: &[f64], total: f64) -> Vec<RelayPart> {
+    let mut ends = finished.to_vec();
+    ends.push(total);
+    let splits = splits(&ends);
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| RelayPart {
+            split: if splits.len() == parts.len() {
+                Some(splits[i])
+            } else {
+                None
+            },
+            ..part.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::event::EVENTS;
+    use crate::app::notation;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn a_2_3_4_relay_has_a_scramble_per_puzzle_in_order() {
+        let relay = EVENTS[9];
+        assert_eq!(relay.to_string(), "2-3-4 Relay");
+        let parts = scrambles(relay, &mut StdRng::seed_from_u64(1));
+        let puzzles: Vec<Cubes> = parts.iter().map(|part| part.puzzle).collect();
+        assert_eq!(
+            puzzles,
+            [Cubes::TwoByTwo, Cubes::ThreeByThree, Cubes::FourByFour]
+        );
+        for part in &parts {
+            assert!(notation::parse_moves(&part.scramble, part.puzzle.size()).is_ok());
+            assert_eq!(part.split, None);
+        }
+        let text = scramble_text(&parts);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for (line, part) in lines.iter().zip(&parts) {
+            assert_eq!(*line, format!("{}: {}", part.puzzle, part.scramble));
+        }
+        assert!(lines[0].starts_with("2x2: ") && lines[2].starts_with("4x4: "));
+    }
+
+    #[test]
+    fn splits_need_every_puzzle_but_the_last_finished() {
+        assert_eq!(splits(&[10.0, 25.0, 60.0]), vec![10.0, 15.0, 35.0]);
+        let parts = scrambles(EVENTS[9], &mut StdRng::seed_from_u64(2));
+        let split: Vec<Option<f64>> = with_splits(&parts, &[10.0, 25.0], 60.0)
+            .iter()
+            .map(|part| part.split)
+            .collect();
+        assert_eq!(split, vec![Some(10.0), Some(15.0), Some(35.0)]);
+        assert!(with_splits(&parts, &[10.0], 60.0)
+            .iter()
+            .all(|part| part.split.is_none()));
+    }
+}
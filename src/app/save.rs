@@ -0,0 +1,293 @@
+use egui::KeyboardShortcut;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+
+use super::event::{Event, Variant};
+use super::keys::Action;
+use super::scramble::Cubes;
+use super::settings::Settings;
+use super::Cubism;
+
+// Saving
+
+/// Reads the saved app, moving anything older versions saved elsewhere to where it is kept now.
+///
+/// Older data is recognised by what it contains, so loading is the same whichever version saved it.
+pub fn load(text: &str) -> Result<Cubism, ron::error::SpannedError> {
+    let mut app: Cubism = ron::from_str(text)?;
+    let old: OldApp = ron::from_str(text)?;
+    // Sessions had a puzzle until they had an event, which is that puzzle solved normally
+    if let Some(event) = old.state.event() {
+        app.state.event = event;
+    }
+    for (name, session) in old.sessions {
+        if let (Some(event), Some(state)) = (session.event(), app.sessions.get_mut(&name)) {
+            state.event = event;
+        }
+    }
+    match old.settings {
+        // Settings were kept with the current session until they got their own place
+        None => app.settings = ron::from_str::<FirstVersion>(text)?.state,
+        Some(settings) => {
+            if let (None, Some(puzzle)) = (settings.event, settings.cube_type) {
+                app.settings.event = Event::new(puzzle, Variant::Standard);
+            }
+            // Actions had a single key until they could have several
+            if settings.keys.bindings.is_none() {
+                for (action, binding) in settings.keys.changed {
+                    app.settings.keys.set(action, binding.into_iter().collect());
+                }
+            }
+        }
+    }
+    Ok(app)
+}
+
+/// The parts of a save that older versions kept somewhere else.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct OldApp {
+    #[serde(deserialize_with = "present")]
+    settings: Option<OldSettings>,
+    state: OldState,
+    sessions: HashMap<String, OldState>,
+}
+
+/// A session as saved before it had an event, when it only had a puzzle.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct OldState {
+    #[serde(deserialize_with = "present")]
+    event: Option<IgnoredAny>,
+    #[serde(deserialize_with = "present")]
+    cube_type: Option<SavedPuzzle>,
+}
+
+impl OldState {
+    /// The event of the session, if it was saved with a puzzle instead.
+    fn event(&self) -> Option<Option<Event>> {
+        match (&self.event, self.cube_type) {
+            (None, Some(puzzle)) => Some(
+                puzzle
+                    .puzzle()
+                    .map(|puzzle| Event::new(puzzle, Variant::Standard)),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// The puzzle of a session, saved bare by the first versions and as an `Option` once settings had a default.
+#[derive(Deserialize, Clone, Copy)]
+enum SavedPuzzle {
+    // The same names as `Cubes`, which is how the first versions wrote them
+    ThreeByThree,
+    TwoByTwo,
+    OneByOne,
+    FourByFour,
+    FiveByFive,
+    Some(Cubes),
+    None,
+}
+
+impl SavedPuzzle {
+    fn puzzle(self) -> Option<Cubes> {
+        match self {
+            SavedPuzzle::ThreeByThree => Some(Cubes::ThreeByThree),
+            SavedPuzzle::TwoByTwo => Some(Cubes::TwoByTwo),
+            SavedPuzzle::OneByOne => Some(Cubes::OneByOne),
+            SavedPuzzle::FourByFour => Some(Cubes::FourByFour),
+            SavedPuzzle::FiveByFive => Some(Cubes::FiveByFive),
+            SavedPuzzle::Some(puzzle) => Some(puzzle),
+            SavedPuzzle::None => None,
+        }
+    }
+}
+
+/// Settings as saved before they had an event, and while every action had a single key.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct OldSettings {
+    #[serde(deserialize_with = "present")]
+    event: Option<IgnoredAny>,
+    #[serde(deserialize_with = "present")]
+    cube_type: Option<Cubes>,
+    keys: OldKeybindings,
+}
+
+/// Keybindings as saved when every action had a single key.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct OldKeybindings {
+    #[serde(deserialize_with = "present")]
+    bindings: Option<IgnoredAny>,
+    changed: HashMap<Action, Option<KeyboardShortcut>>, // Bindings changed from the defaults, `None` for unbound actions
+}
+
+/// A save from before settings were split out of the session.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct FirstVersion {
+    state: Settings, // Settings were fields of the current session, under the same names
+}
+
+/// Tells a field that was saved apart from one that was left out, without needing it to be saved as an `Option`.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The start of a save from before settings were split out of the session, as the first versions wrote it.
+    const FIRST_VERSION: &str = r#"(
+        state: (
+            time: "12.34",
+            solves: [(time: "12.34", scramble: "R U R'", timestamp: "1700000000", comment: "", plus2: false, dnf: false, cube_type: ThreeByThree)],
+            name: "Main",
+            cube_type: FourByFour,
+            cube_type_old: FourByFour,
+            prec: 3,
+            ao5_prec: 1,
+            background: (10, 20, 30),
+            footer: "Hello",
+            show_tools: false,
+        ),
+        sessions: {
+            "Other": (solves: [], name: "Other", cube_type: TwoByTwo, prec: 2),
+        },
+    )"#;
+
+    #[test]
+    fn moves_settings_out_of_the_session() {
+        let app = load(FIRST_VERSION).unwrap();
+        assert_eq!(app.settings.prec, 3);
+        assert_eq!(app.settings.ao5_prec, 1);
+        assert_eq!(app.settings.background, [10, 20, 30]);
+        assert_eq!(app.settings.footer, "Hello");
+        assert!(!app.settings.show_tools);
+        // Fields that weren't saved get their default
+        assert_eq!(app.settings.solves_prec, Settings::default().solves_prec);
+    }
+
+    #[test]
+    fn keeps_sessions_and_solves() {
+        let app = load(FIRST_VERSION).unwrap();
+        assert_eq!(app.state.name, "Main");
+        assert_eq!(app.state.solves.len(), 1);
+        assert_eq!(app.state.solves[0].scramble, "R U R'");
+        assert_eq!(app.sessions["Other"].name, "Other");
+    }
+
+    #[test]
+    fn loads_what_it_saves() {
+        let mut app = Cubism::default();
+        app.settings.prec = 1;
+        app.state.name = "Saved".to_string();
+        let text = ron::ser::to_string(&app).unwrap();
+        let loaded = load(&text).unwrap();
+        assert_eq!(loaded.settings, app.settings);
+        assert_eq!(loaded.state.name, "Saved");
+    }
+
+    #[test]
+    fn turns_puzzles_into_events() {
+        let app = load(FIRST_VERSION).unwrap();
+        assert_eq!(
+            app.state.event,
+            Some(Event::new(Cubes::FourByFour, Variant::Standard))
+        );
+        assert_eq!(
+            app.sessions["Other"].event,
+            Some(Event::new(Cubes::TwoByTwo, Variant::Standard))
+        );
+        // Later saves had puzzles that fell back to the one in settings
+        let app = load(
+            r#"(
+                settings: (cube_type: FiveByFive),
+                state: (name: "Main", cube_type: Some(OneByOne)),
+                sessions: {"Other": (name: "Other", cube_type: None)},
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(
+            app.settings.event,
+            Event::new(Cubes::FiveByFive, Variant::Standard)
+        );
+        assert_eq!(
+            app.state.event,
+            Some(Event::new(Cubes::OneByOne, Variant::Standard))
+        );
+        assert_eq!(app.sessions["Other"].event, None);
+    }
+
+    #[test]
+    fn keeps_events_over_puzzles() {
+        let event = Event::new(Cubes::TwoByTwo, Variant::OneHanded);
+        let mut app = Cubism::default();
+        app.settings.event = event;
+        app.state.event = Some(event);
+        let text = ron::ser::to_string(&app).unwrap();
+        let loaded = load(&text).unwrap();
+        assert_eq!(loaded.settings.event, event);
+        assert_eq!(loaded.state.event, Some(event));
+    }
+
+    #[test]
+    fn old_1x1_and_2x2_sessions_keep_their_events_once_saved_again() {
+        let app = load(
+            r#"(
+                state: (
+                    name: "Main",
+                    cube_type: OneByOne,
+                    solves: [(time: "1.23", scramble: "x", timestamp: "1700000000", comment: "", plus2: false, dnf: false, cube_type: OneByOne)],
+                ),
+                sessions: {
+                    "Other": (
+                        name: "Other",
+                        cube_type: TwoByTwo,
+                        solves: [(time: "4.56", scramble: "R U", timestamp: "1700000000", comment: "", plus2: false, dnf: false, cube_type: TwoByTwo)],
+                    ),
+                },
+            )"#,
+        )
+        .unwrap();
+        let one = Event::new(Cubes::OneByOne, Variant::Standard);
+        let two = Event::new(Cubes::TwoByTwo, Variant::Standard);
+        let saved = load(&ron::ser::to_string(&app).unwrap()).unwrap();
+        for app in [app, saved] {
+            assert_eq!(app.state.event, Some(one));
+            assert_eq!(app.state.solves[0].event(), one);
+            assert_eq!(app.sessions["Other"].event, Some(two));
+            assert_eq!(app.sessions["Other"].solves[0].event(), two);
+        }
+    }
+
+    #[test]
+    fn keeps_single_keybindings() {
+        let app = load(
+            r#"(settings: (keys: (changed: {
+                NewScramble: Some((modifiers: (alt: false, ctrl: false, shift: false, mac_cmd: false, command: false), logical_key: N)),
+                Undo: None,
+            })))"#,
+        )
+        .unwrap();
+        assert_eq!(app.settings.keys.describe(Action::NewScramble), "N");
+        assert_eq!(app.settings.keys.describe(Action::Undo), "Unbound");
+        assert_eq!(
+            app.settings.keys.get(Action::Redo),
+            Action::Redo.default_bindings()
+        );
+    }
+
+    #[test]
+    fn rejects_unreadable_data() {
+        assert!(load("(state: (solves: 3))").is_err());
+    }
+}
@@ -1,8 +1,17 @@
+use rand::seq::SliceRandom;
 use rand::Rng;
 use std::fmt::Display;
 
+use super::cubie::{parity, CubieCube, Turn};
+use super::event::{Event, Variant};
+use super::kociemba::{self, SolverOptions};
+use super::notation::turns_string;
+use super::relay;
+use super::steps;
+use super::two_by_two;
+
 #[allow(dead_code)]
-#[derive(PartialEq, Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub enum Cubes {
     ThreeByThree,
     TwoByTwo,
@@ -20,32 +29,256 @@ impl Display for Cubes {
             Cubes::FourByFour => "4x4",
             Cubes::FiveByFive => "5x5",
         };
-        write!(f, "{}", string.to_string())
+        write!(f, "{}", string)
+    }
+}
+
+impl Cubes {
+    /// Number of layers along each edge of the puzzle.
+    pub fn size(&self) -> i32 {
+        match self {
+            Cubes::OneByOne => 1,
+            Cubes::TwoByTwo => 2,
+            Cubes::ThreeByThree => 3,
+            Cubes::FourByFour => 4,
+            Cubes::FiveByFive => 5,
+        }
+    }
+}
+
+/// Kinds of 3x3 scrambles, either fully scrambled or leaving part of the cube solved for practice.
+///
+/// Solved parts are on the white side, so they end up on the bottom when solving white cross.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub enum Subset {
+    Full,
+    CrossSolved,
+    LastSlot,
+    LastLayer,
+    EdgesOnly,
+    CornersOnly,
+    EasyCross(usize), // Cross solvable in at most this many moves
+}
+
+impl Display for Subset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Subset::Full => "Full",
+            Subset::CrossSolved => "Cross Solved",
+            Subset::LastSlot => "Last Slot",
+            Subset::LastLayer => "Last Layer",
+            Subset::EdgesOnly => "Edges Only",
+            Subset::CornersOnly => "Corners Only",
+            Subset::EasyCross(_) => "Easy Cross",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+impl Subset {
+    /// Every subset, with a default move count for the easy cross.
+    pub fn all() -> [Subset; 7] {
+        [
+            Subset::Full,
+            Subset::CrossSolved,
+            Subset::LastSlot,
+            Subset::LastLayer,
+            Subset::EdgesOnly,
+            Subset::CornersOnly,
+            Subset::EasyCross(4),
+        ]
+    }
+
+    /// Corner and edge positions scrambled by the subset, with the solved side on D.
+    fn free_pieces(&self) -> (Vec<usize>, Vec<usize>) {
+        // Positions of the U layer, and of the DFR corner and FR edge making up the last slot
+        let (top, slot_corner, slot_edge) = (0..4, 4, 8);
+        match self {
+            Subset::Full | Subset::EasyCross(_) => ((0..8).collect(), (0..12).collect()),
+            Subset::CrossSolved => ((0..8).collect(), top.clone().chain(8..12).collect()),
+            Subset::LastSlot => (
+                top.clone().chain([slot_corner]).collect(),
+                top.chain([slot_edge]).collect(),
+            ),
+            Subset::LastLayer => (top.clone().collect(), top.collect()),
+            Subset::EdgesOnly => (vec![], (0..12).collect()),
+            Subset::CornersOnly => ((0..8).collect(), vec![]),
+        }
+    }
+
+    /// A random state of the subset, each equally likely.
+    fn random_state(&self, rng: &mut impl Rng) -> CubieCube {
+        let (corners, edges) = self.free_pieces();
+        loop {
+            let mut cube = CubieCube::default();
+            let mut shuffled = corners.clone();
+            shuffled.shuffle(rng);
+            for (&position, &piece) in corners.iter().zip(&shuffled) {
+                cube.cp[position] = piece as u8;
+                cube.co[position] = rng.gen_range(0..3);
+            }
+            let mut shuffled = edges.clone();
+            shuffled.shuffle(rng);
+            for (&position, &piece) in edges.iter().zip(&shuffled) {
+                cube.ep[position] = piece as u8;
+                cube.eo[position] = rng.gen_range(0..2);
+            }
+            // The last free piece is turned so that the twists and flips add up
+            if let Some(&last) = corners.last() {
+                let sum: u8 = cube.co.iter().sum();
+                cube.co[last] = (cube.co[last] + 3 - sum % 3) % 3;
+            }
+            if let Some(&last) = edges.last() {
+                let sum: u8 = cube.eo.iter().sum();
+                cube.eo[last] = (cube.eo[last] + sum) % 2;
+            }
+            if parity(&cube.cp) != parity(&cube.ep) {
+                if edges.len() >= 2 {
+                    cube.ep.swap(edges[0], edges[1]);
+                } else {
+                    cube.cp.swap(corners[0], corners[1]);
+                }
+            }
+            match self {
+                Subset::EasyCross(moves) if steps::cross_distance(&cube) > *moves => continue,
+                _ if cube == CubieCube::default() => continue,
+                _ => return cube,
+            }
+        }
+    }
+
+    /// A scramble leading to a random state of the subset, in WCA orientation.
+    pub fn scramble(&self, rng: &mut impl Rng) -> String {
+        turns_string(&self.turns(rng))
+    }
+
+    /// The turns of a scramble leading to a random state of the subset.
+    ///
+    /// `Full` gives a random state of the whole cube, rather than random moves.
+    fn turns(&self, rng: &mut impl Rng) -> Vec<Turn> {
+        let options = SolverOptions {
+            max_length: 25,
+            time: 0.05,
+            count: 1,
+        };
+        let solution = loop {
+            if let Ok(solutions) = kociemba::solve(&self.random_state(rng), options) {
+                break solutions[0].clone();
+            }
+        };
+        // Undoing the solution gives the state, and turning it over puts the solved side on white
+        let over = [3, 1, 5, 0, 4, 2];
+        solution
+            .iter()
+            .rev()
+            .map(|turn| Turn {
+                face: over[turn.face as usize],
+                power: 4 - turn.power,
+            })
+            .collect()
     }
 }
 
+/// A random state scramble for FMC, starting and ending with R' U' F like WCA scrambles.
+///
+/// The padding keeps the end of the scramble from being undone as part of a solution.
+pub fn fmc_scramble() -> String {
+    loop {
+        let turns = Subset::Full.turns(&mut rand::thread_rng());
+        // Turns that could merge with the padding would shorten it
+        let (first, last) = (turns[0].face, turns[turns.len() - 1].face);
+        if [2, 5].contains(&first) || [1, 4].contains(&last) {
+            continue;
+        }
+        return format!("R' U' F {} R' U' F", turns_string(&turns));
+    }
+}
+
+/// Wide turns leaving the cube in a random orientation, so that blindfolded solvers can't rely on it.
+///
+/// On bigger cubes they take three layers, so that the middle centres turn with them.
+fn orientation(size: i32, rng: &mut impl Rng) -> String {
+    let layers = if size > 3 { "3" } else { "" };
+    // A turn choosing the top face, then one choosing the front face
+    let top = ["", "Rw", "Rw2", "Rw'", "Fw", "Fw'"][rng.gen_range(0..6)];
+    let front = ["", "Uw", "Uw2", "Uw'"][rng.gen_range(0..4)];
+    [top, front]
+        .iter()
+        .filter(|turn| !turn.is_empty())
+        .map(|turn| format!("{}{}", layers, turn))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
 #[allow(dead_code)]
 pub struct Scrambler {
     cube: Cubes,
+    variant: Variant,
+    subset: Subset,
 }
 
 impl Default for Scrambler {
     fn default() -> Self {
         Self {
             cube: Cubes::ThreeByThree,
+            variant: Variant::Standard,
+            subset: Subset::Full,
         }
     }
 }
 
 impl From<Cubes> for Scrambler {
     fn from(value: Cubes) -> Self {
-        Self { cube: value }
+        Self {
+            cube: value,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<Event> for Scrambler {
+    fn from(value: Event) -> Self {
+        Self {
+            cube: value.puzzle,
+            variant: value.variant,
+            ..Self::default()
+        }
     }
 }
 
 #[allow(dead_code, unused_assignments)]
 impl Scrambler {
+    /// Scrambles only part of a 3x3, leaving the rest solved.
+    pub fn subset(self, subset: Subset) -> Self {
+        Self { subset, ..self }
+    }
+
     pub fn scramble(&self) -> String {
+        self.scramble_with(&mut rand::thread_rng())
+    }
+
+    /// Scrambles with the given random numbers, so that a seeded generator always gives the same scrambles.
+    pub fn scramble_with(&self, rng: &mut impl Rng) -> String {
+        if let Variant::Relay(_) = self.variant {
+            let parts = relay::scrambles(Event::new(self.cube, self.variant), rng);
+            return relay::scramble_text(&parts);
+        }
+        if self.variant == Variant::Blindfolded {
+            let scramble = Scrambler::from(self.cube).scramble_with(rng);
+            return format!("{} {}", scramble, orientation(self.cube.size(), rng))
+                .trim_end()
+                .to_string();
+        }
+        if self.cube == Cubes::ThreeByThree && self.subset != Subset::Full {
+            return self.subset.scramble(rng);
+        }
+        // 2x2 scrambles are random states rather than random moves, once the table for them is ready
+        if self.cube == Cubes::TwoByTwo {
+            if two_by_two::tables_ready() {
+                return two_by_two::random_state_scramble(two_by_two::MIN_DISTANCE, rng);
+            }
+            two_by_two::prepare();
+        }
         let length: i8 = match self.cube {
             Cubes::OneByOne => 8,
             Cubes::TwoByTwo => 12,
@@ -105,12 +338,11 @@ impl Scrambler {
         }
         let mut back = "".to_string();
         let mut scramble: Vec<String> = vec![];
-        let mut rng = rand::thread_rng();
         for _i in 1..=length {
             loop {
                 let option = &options[rng.gen_range(1..options.len())];
                 let mut characters: Vec<char> = option.chars().collect();
-                if characters[0].is_digit(10) {
+                if characters[0].is_ascii_digit() {
                     characters.remove(0);
                 }
                 let character = match characters[0].to_string().as_str() {
@@ -131,7 +363,7 @@ impl Scrambler {
                     "L2" => "L",
                     _ => continue,
                 };
-                if back == character.to_string() {
+                if back == character {
                     continue;
                 }
                 scramble.push(option.to_string());
@@ -0,0 +1,166 @@
+use super::event::Event;
+use super::filter::SolveFilter;
+use super::keys::Keybindings;
+use super::sheet::SheetOptions;
+use super::steps::Step;
+
+/// The ways solve times can be plotted.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Debug)]
+pub enum PlotView {
+    Times,
+    Histogram,
+    BoxPlot,
+}
+
+/// How solves are grouped into boxes in the box plot.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Debug)]
+pub enum BoxGroup {
+    Session,
+    Day,
+}
+
+/// Length of time solves are grouped by in the progress view.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Debug)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+// Settings
+
+/// Global configuration shared by every session.
+///
+/// Anything that describes how the app looks or behaves lives here, while
+/// the solves themselves live in the per-session `State`.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    // Precision
+    pub prec: usize,            // Precision for numeric display
+    pub ao5_prec: usize,        // Precision for ao5 display
+    pub solves_prec: usize,     // Precision for solves display
+    pub old_ao5_prec: usize,    // Old precision for ao5
+    pub old_solves_prec: usize, // Old precision for solves
+    pub old_prec: usize,        // Old precision
+
+    // Colours
+    pub background: [u8; 3], // Background color
+    pub window: [u8; 3],     // Window color
+    pub button: [u8; 3],     // Button color
+    pub outline: [u8; 3],    // Outline color
+    pub titlebar: [u8; 3],   // Title bar color
+    pub text: [u8; 3],       // Text color
+    pub outline_w: f32,      // Outline width
+    pub widget: [u8; 3],     // Widget color
+
+    // View toggles
+    pub settings_open: bool,      // Indicates whether settings are open
+    pub stats_all_sessions: bool, // Whether the distribution statistics include every session
+    pub threshold: f64,           // Seconds under which solves are counted in the statistics
+    pub stats_open: bool,         // Whether to have Stats menu open
+    pub show_solve_info: bool,    // Indicates whether solve info is being shown
+    pub show_tools: bool,         // Indicates whether tools are being shown
+    pub show_left_bar: bool,      // Whether to show the left (main) bar of the screen
+    pub show_scramble: bool,      // Indicates whether scramble is being shown
+    pub show_footer: bool,        // Whether to show the footer
+    pub footer: String,           // Custom footer text (for youtubers etc)
+
+    // Solve list
+    pub filter: SolveFilter, // Filter for the solve list and statistics
+
+    // Tools
+    pub plot_aspect_ratio: f32,   // Aspect ratio for plots
+    pub plot_view: PlotView,      // Which plot is shown by Plot Times
+    pub bucket_width: f64,        // Width of histogram buckets in seconds
+    pub box_group: BoxGroup,      // Whether to draw a box per session or per day
+    pub plot_ao5: bool,           // Whether to overlay the rolling ao5 on the time plot
+    pub plot_ao12: bool,          // Whether to overlay the rolling ao12 on the time plot
+    pub plot_ao100: bool,         // Whether to overlay the rolling ao100 on the time plot
+    pub period: Period,           // Period solves are grouped by in the progress view
+    pub all_sessions: bool,       // Whether the progress view includes every session
+    pub pb_size: usize,           // Kind of personal best shown in the progression chart
+    pub compare_tag: String,      // Tag compared against the other solves in the Tags tool
+    pub solver_max_length: usize, // Longest solution the solver accepts
+    pub solver_time: f64,         // Seconds the solver spends looking for shorter solutions
+    pub solver_count: usize,      // Number of solutions the solver proposes
+    pub step: Step,               // Step solved by the Step Solver
+    pub relay_splits: bool,       // Whether Space marks the end of each puzzle of a relay
+    pub sheet: SheetOptions,      // Events, rounds and seed of scramble sheets
+    pub keys: Keybindings,        // Keys bound to each action
+
+    // Session defaults, which a session may override
+    pub event: Event,     // Event of sessions without their own
+    pub inspection: bool, // Whether to use 15 second WCA inspection
+
+    // Initiation fields
+    pub download: bool, // Whether the Download the Desktop App prompt has been closed
+    pub used: bool,     // Whether the About Cubism menu has been shown
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            // Precision Settings
+            prec: 2,
+            ao5_prec: 3,
+            solves_prec: 2,
+            old_prec: 2,
+            old_ao5_prec: 3,
+            old_solves_prec: 2,
+
+            // UI Colors
+            background: [255, 255, 255],
+            window: [255, 255, 255],
+            button: [255, 255, 255],
+            titlebar: [255, 255, 255],
+            outline: [0, 0, 0],
+            text: [0, 0, 0],
+            outline_w: 0.5,
+            widget: [150, 150, 150],
+
+            // Visual Options
+            settings_open: false,
+            stats_all_sessions: false,
+            threshold: 10.0,
+            stats_open: false,
+            show_solve_info: true,
+            show_tools: true,
+            show_left_bar: true,
+            show_scramble: true,
+            show_footer: true,
+            footer: "Cubism by Aityz (This text is customizable)".to_string(),
+
+            // Solve List
+            filter: SolveFilter::default(),
+
+            // Tools
+            plot_aspect_ratio: 2.0,
+            plot_view: PlotView::Times,
+            bucket_width: 1.0,
+            box_group: BoxGroup::Session,
+            plot_ao5: true,
+            plot_ao12: true,
+            plot_ao100: false,
+            period: Period::Day,
+            all_sessions: false,
+            pb_size: 1,
+            compare_tag: "".to_string(),
+            solver_max_length: 22,
+            solver_time: 2.0,
+            solver_count: 3,
+            step: Step::Cross,
+            relay_splits: false,
+            sheet: SheetOptions::default(),
+            keys: Keybindings::default(),
+
+            // Session Defaults
+            event: Event::default(),
+            inspection: false,
+
+            // First Load
+            download: true,
+            used: false,
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use cubesim::{Cube, Face, FaceletCube};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::competition::Format;
+use super::event::Event;
+use super::notation;
+use super::scramble::{Cubes, Scrambler};
+use super::two_by_two;
+
+// Scramble Sheets
+
+/// Extra scrambles of every round, for attempts that have to be redone.
+pub const EXTRAS: usize = 2;
+
+/// Where each face is drawn in a net, in faces across and down: L, F, R and B in a row,
+/// with U above F and D below it. Faces are in the order U, R, F, D, L, B like `cubesim` stickers.
+pub const NET: [(usize, usize); 6] = [(1, 0), (2, 1), (1, 1), (1, 2), (0, 1), (3, 1)];
+
+/// Colour of a face in the WCA colour scheme, white on top and green in front.
+pub fn colour(face: Face) -> [u8; 3] {
+    match face {
+        Face::U => [255, 255, 255],
+        Face::R => [183, 18, 52],
+        Face::F => [0, 155, 72],
+        Face::D => [255, 213, 0],
+        Face::L => [255, 88, 0],
+        Face::B => [0, 70, 173],
+        Face::X => [128, 128, 128],
+    }
+}
+
+/// Top left corner of sticker `i` of a puzzle of `size` in a net, with faces `face` wide and `gap` apart.
+pub fn sticker_position(i: usize, size: usize, face: f32, gap: f32) -> (f32, f32) {
+    let (across, down) = NET[i / (size * size)];
+    let (row, column) = (i % (size * size) / size, i % size);
+    let sticker = face / size as f32;
+    (
+        across as f32 * (face + gap) + column as f32 * sticker,
+        down as f32 * (face + gap) + row as f32 * sticker,
+    )
+}
+
+/// Stickers of `puzzle` after `scramble`, face by face in the order U, R, F, D, L, B.
+pub fn stickers(puzzle: Cubes, scramble: &str) -> Option<Vec<Face>> {
+    let moves = notation::parse_moves(scramble, puzzle.size()).ok()?;
+    Some(FaceletCube::new(puzzle.size()).apply_moves(&moves).state())
+}
+
+/// The puzzles of an event with their part of `scramble`, one line per puzzle for a relay.
+pub fn parts(event: Event, scramble: &str) -> Vec<(Cubes, &str)> {
+    if !event.is_relay() {
+        return vec![(event.puzzle, scramble)];
+    }
+    event
+        .puzzles()
+        .into_iter()
+        .zip(scramble.lines())
+        .map(|(puzzle, line)| {
+            let moves = line.split_once(": ").map_or(line, |(_, moves)| moves);
+            (puzzle, moves)
+        })
+        .collect()
+}
+
+/// What goes on the next scramble sheet.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct SheetOptions {
+    pub events: Vec<Event>, // Events scrambled, in the order they are printed
+    pub rounds: usize,      // Rounds of each event
+    pub seed: u64,          // Seed the scrambles are generated from
+    pub path: String,       // File the sheet is saved to, as SVG for ".svg" files
+}
+
+impl Default for SheetOptions {
+    fn default() -> Self {
+        Self {
+            events: vec![Event::default()],
+            rounds: 1,
+            seed: 1,
+            path: "scrambles.html".to_string(),
+        }
+    }
+}
+
+/// The scrambles of one round of an event.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SheetRound {
+    pub event: Event,
+    pub round: usize,           // Number of the round, from 1
+    pub scrambles: Vec<String>, // Scramble of each attempt
+    pub extras: Vec<String>,    // Scrambles for attempts that have to be redone
+}
+
+impl SheetRound {
+    /// The scrambles with the label they are printed with, "1" to "5" then "E1" and "E2".
+    pub fn labelled(&self) -> Vec<(String, &String)> {
+        let attempts = self
+            .scrambles
+            .iter()
+            .enumerate()
+            .map(|(i, scramble)| ((i + 1).to_string(), scramble));
+        let extras = self
+            .extras
+            .iter()
+            .enumerate()
+            .map(|(i, scramble)| (format!("E{}", i + 1), scramble));
+        attempts.chain(extras).collect()
+    }
+}
+
+/// Scrambles for every round of a competition, printable for club meetups.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScrambleSheet {
+    pub seed: u64,
+    pub rounds: Vec<SheetRound>,
+}
+
+impl ScrambleSheet {
+    /// Scrambles `rounds` rounds of each event, always the same for the same seed, events and rounds.
+    pub fn generate(events: &[Event], rounds: usize, seed: u64) -> Self {
+        // Without the table 2x2 scrambles are random moves, so a seed would give different scrambles
+        if events
+            .iter()
+            .any(|event| event.puzzles().contains(&Cubes::TwoByTwo))
+        {
+            two_by_two::wait_for_tables();
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut sheet_rounds = vec![];
+        for &event in events {
+            let scrambler = Scrambler::from(event);
+            for round in 1..=rounds {
+                let mut scramble = || scrambler.scramble_with(&mut rng);
+                let scrambles = (0..Format::of(event).attempts())
+                    .map(|_| scramble())
+                    .collect();
+                let extras = (0..EXTRAS).map(|_| scramble()).collect();
+                sheet_rounds.push(SheetRound {
+                    event,
+                    round,
+                    scrambles,
+                    extras,
+                });
+            }
+        }
+        Self {
+            seed,
+            rounds: sheet_rounds,
+        }
+    }
+
+    /// A self-contained HTML document with a page per round.
+    pub fn html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Scramble Sheet</title>\n<style>\n\
+             body { font-family: sans-serif; }\n\
+             section { page-break-after: always; }\n\
+             table { border-collapse: collapse; width: 100%; }\n\
+             td { border: 1px solid black; padding: 6px; }\n\
+             td.scramble { font-family: monospace; font-size: 14px; }\n\
+             </style>\n</head>\n<body>\n",
+        );
+        for round in &self.rounds {
+            html += &format!(
+                "<section>\n<h1>{} Round {}</h1>\n<p>Seed {}</p>\n<table>\n",
+                round.event, round.round, self.seed
+            );
+            for (label, scramble) in round.labelled() {
+                let parts = parts(round.event, scramble);
+                let nets: String = parts
+                    .iter()
+                    .map(|(puzzle, moves)| {
+                        format!(
+                            "<svg width=\"{}\" height=\"{}\">{}</svg>",
+                            NET_WIDTH,
+                            NET_HEIGHT,
+                            net_svg(*puzzle, moves)
+                        )
+                    })
+                    .collect();
+                html += &format!(
+                    "<tr><td>{}</td><td class=\"scramble\">{}</td><td>{}</td></tr>\n",
+                    label,
+                    scramble.replace('\n', "<br>"),
+                    nets
+                );
+            }
+            html += "</table>\n</section>\n";
+        }
+        html += "</body>\n</html>\n";
+        html
+    }
+
+    /// A single SVG document with every round one after another.
+    pub fn svg(&self) -> String {
+        const WIDTH: f32 = 800.0;
+        const LINE: f32 = 16.0;
+        let mut body = String::new();
+        let mut y = 0.0;
+        for round in &self.rounds {
+            y += 40.0;
+            body += &format!(
+                "<text x=\"10\" y=\"{}\" font-size=\"24\">{} Round {}</text>\n",
+                y, round.event, round.round
+            );
+            y += 10.0;
+            for (label, scramble) in round.labelled() {
+                let parts = parts(round.event, scramble);
+                let nets_width = parts.len() as f32 * (NET_WIDTH + 10.0);
+                // Monospace characters are about 0.6 of the font size wide
+                let columns = ((WIDTH - nets_width - 70.0) / (0.6 * 13.0)) as usize;
+                let lines: Vec<String> = scramble
+                    .lines()
+                    .flat_map(|line| wrap(line, columns))
+                    .collect();
+                let height = (lines.len() as f32 * LINE).max(NET_HEIGHT) + 10.0;
+                body += &format!(
+                    "<rect x=\"5\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"black\"/>\n",
+                    y,
+                    WIDTH - 10.0,
+                    height
+                );
+                body += &format!(
+                    "<text x=\"12\" y=\"{}\" font-size=\"14\">{}</text>\n",
+                    y + 20.0,
+                    label
+                );
+                for (i, line) in lines.iter().enumerate() {
+                    body += &format!(
+                        "<text x=\"50\" y=\"{}\" font-family=\"monospace\" font-size=\"13\">{}</text>\n",
+                        y + 20.0 + i as f32 * LINE,
+                        line
+                    );
+                }
+                for (i, (puzzle, moves)) in parts.iter().enumerate() {
+                    let x = WIDTH - nets_width + i as f32 * (NET_WIDTH + 10.0);
+                    body += &format!(
+                        "<g transform=\"translate({} {})\">{}</g>\n",
+                        x,
+                        y + 5.0,
+                        net_svg(*puzzle, moves)
+                    );
+                }
+                y += height;
+            }
+            y += 10.0;
+            body += &format!(
+                "<text x=\"10\" y=\"{}\" font-size=\"12\">Seed {}</text>\n",
+                y, self.seed
+            );
+        }
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n{}</svg>\n",
+            WIDTH,
+            y + 20.0,
+            body
+        )
+    }
+}
+
+/// Size of a face in a net, and of the gap between faces.
+const FACE: f32 = 36.0;
+const GAP: f32 = 3.0;
+const NET_WIDTH: f32 = 4.0 * FACE + 3.0 * GAP;
+const NET_HEIGHT: f32 = 3.0 * FACE + 2.0 * GAP;
+
+/// The stickers of a scrambled puzzle as SVG squares laid out like `NET`, empty if the scramble can't be read.
+fn net_svg(puzzle: Cubes, scramble: &str) -> String {
+    let Some(stickers) = stickers(puzzle, scramble) else {
+        return String::new();
+    };
+    let size = puzzle.size() as usize;
+    let sticker = FACE / size as f32;
+    let mut svg = String::new();
+    for (i, face) in stickers.iter().enumerate() {
+        let (x, y) = sticker_position(i, size, FACE, GAP);
+        let [r, g, b] = colour(*face);
+        svg += &format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"rgb({},{},{})\" stroke=\"black\" stroke-width=\"0.5\"/>",
+            x,
+            y,
+            sticker,
+            sticker,
+            r,
+            g,
+            b
+        );
+    }
+    svg
+}
+
+/// Splits a scramble into lines of at most `columns` characters, between moves.
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in text.split_whitespace() {
+        let line = lines.last_mut().unwrap();
+        if !line.is_empty() && line.len() + 1 + word.len() > columns {
+            lines.push(word.to_string());
+        } else {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+    }
+    lines
+}
+
+/// Saves an exported sheet to `path`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(path: &str, contents: &str) -> Result<(), String> {
+    std::fs::write(path, contents).map_err(|error| error.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(_path: &str, _contents: &str) -> Result<(), String> {
+    Err("Files can't be saved on the web, copy the sheet instead".to_string())
+}
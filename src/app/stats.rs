@@ -0,0 +1,746 @@
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+
+use super::event::Event;
+use super::scramble::Cubes;
+use super::settings::Period;
+use super::SolveStats;
+
+// Statistics
+
+/// Time of a solve in seconds, or `None` for a DNF.
+pub fn solve_time(solve: &SolveStats) -> Option<f64> {
+    if solve.dnf {
+        None
+    } else {
+        solve.time.parse().ok()
+    }
+}
+
+/// Local date a solve was done on, from its timestamp.
+pub fn solve_date(solve: &SolveStats) -> Option<NaiveDate> {
+    let seconds: i64 = solve.timestamp.parse().ok()?;
+    Some(
+        DateTime::from_timestamp(seconds, 0)?
+            .with_timezone(&Local)
+            .date_naive(),
+    )
+}
+
+/// Times of the solves in the order they were done, oldest first.
+pub fn chronological_times(solves: &[SolveStats]) -> Vec<Option<f64>> {
+    solves.iter().rev().map(solve_time).collect()
+}
+
+/// Times of the solve at `index` and the solves of its event before it, newest first.
+pub fn times_ending_with(solves: &[SolveStats], index: usize) -> Vec<Option<f64>> {
+    let event = solves[index].event();
+    solves[index..]
+        .iter()
+        .filter(|solve| solve.event() == event)
+        .map(solve_time)
+        .collect()
+}
+
+/// Number of solves trimmed from each end of an average, 5% rounded up like the WCA.
+pub fn trimmed(count: usize) -> usize {
+    if count < 5 {
+        0
+    } else {
+        (count * 5 + 99) / 100
+    }
+}
+
+/// The WCA style average of the times, DNFs counting as the worst result.
+///
+/// Returns `None` when there are more DNFs than trimmed solves, or no times at all.
+pub fn average_of(times: &[Option<f64>]) -> Option<f64> {
+    if times.is_empty() {
+        return None;
+    }
+    let trim = trimmed(times.len());
+    let dnfs = times.iter().filter(|time| time.is_none()).count();
+    if dnfs > trim {
+        return None;
+    }
+    let mut finished: Vec<f64> = times.iter().flatten().copied().collect();
+    finished.sort_by(|a, b| a.total_cmp(b));
+    // DNFs are already the worst, so fewer finished times need trimming from the top
+    let counted = &finished[trim..finished.len() + dnfs - trim];
+    Some(counted.iter().sum::<f64>() / counted.len() as f64)
+}
+
+/// The average of every window of `size` solves, indexed by the last solve in the window.
+pub fn rolling_average(times: &[Option<f64>], size: usize) -> Vec<(usize, Option<f64>)> {
+    if size == 0 || times.len() < size {
+        return vec![];
+    }
+    times
+        .windows(size)
+        .enumerate()
+        .map(|(i, window)| (i + size - 1, average_of(window)))
+        .collect()
+}
+
+/// The best average of `size` consecutive times, if any of them finished.
+pub fn best_average(times: &[Option<f64>], size: usize) -> Option<f64> {
+    rolling_average(times, size)
+        .into_iter()
+        .filter_map(|(_, average)| average)
+        .reduce(f64::min)
+}
+
+/// The `p`th percentile (0 to 100) of sorted times, interpolating between the closest ranks.
+pub fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+/// Five number summary of some times, used for box plots.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Spread {
+    pub lower_whisker: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub upper_whisker: f64,
+}
+
+/// Quartiles of the times, with whiskers at the furthest times within 1.5 IQR.
+pub fn spread(times: &[f64]) -> Option<Spread> {
+    let mut sorted = times.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let q1 = percentile(&sorted, 25.0)?;
+    let median = percentile(&sorted, 50.0)?;
+    let q3 = percentile(&sorted, 75.0)?;
+    let reach = (q3 - q1) * 1.5;
+    let lower_whisker = sorted
+        .iter()
+        .copied()
+        .find(|time| *time >= q1 - reach)
+        .unwrap_or(q1);
+    let upper_whisker = sorted
+        .iter()
+        .rev()
+        .copied()
+        .find(|time| *time <= q3 + reach)
+        .unwrap_or(q3);
+    Some(Spread {
+        lower_whisker,
+        q1,
+        median,
+        q3,
+        upper_whisker,
+    })
+}
+
+/// Counts the times falling in each bucket, returned as (bucket start, count) pairs.
+pub fn histogram(times: &[f64], bucket_width: f64) -> Vec<(f64, usize)> {
+    if times.is_empty() || bucket_width <= 0.0 {
+        return vec![];
+    }
+    let min = times.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = times.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let first = (min / bucket_width).floor() as i64;
+    let last = (max / bucket_width).floor() as i64;
+    let mut counts = vec![0; (last - first + 1) as usize];
+    for time in times {
+        counts[((time / bucket_width).floor() as i64 - first) as usize] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| ((first + i as i64) as f64 * bucket_width, count))
+        .collect()
+}
+
+/// First day of the period a date falls in.
+pub fn period_start(date: NaiveDate, period: Period) -> NaiveDate {
+    match period {
+        Period::Day => date,
+        Period::Week => date - chrono::Days::new(date.weekday().num_days_from_monday() as u64),
+        Period::Month => date.with_day(1).unwrap(),
+    }
+}
+
+/// Summary of the solves done within a day, week or month.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PeriodStats {
+    pub start: NaiveDate,
+    pub count: usize,
+    pub mean: Option<f64>,
+    pub best_single: Option<f64>,
+    pub best_ao5: Option<f64>,
+}
+
+impl PeriodStats {
+    pub fn label(&self, period: Period) -> String {
+        match period {
+            Period::Day => self.start.format("%Y-%m-%d").to_string(),
+            Period::Week => format!("Week of {}", self.start.format("%Y-%m-%d")),
+            Period::Month => self.start.format("%B %Y").to_string(),
+        }
+    }
+}
+
+/// Groups solves by the period they were done in, oldest first.
+///
+/// Solves without a valid timestamp are left out.
+pub fn group_by_period(solves: &[SolveStats], period: Period) -> Vec<PeriodStats> {
+    // Solves are stored newest first, so reverse them to keep ties in order
+    let mut dated: Vec<(NaiveDate, i64, Option<f64>)> = solves
+        .iter()
+        .rev()
+        .filter_map(|solve| {
+            let date = solve_date(solve)?;
+            Some((date, solve.timestamp.parse().ok()?, solve_time(solve)))
+        })
+        .collect();
+    dated.sort_by_key(|(_, seconds, _)| *seconds);
+
+    let mut groups: Vec<(NaiveDate, Vec<Option<f64>>)> = vec![];
+    for (date, _, time) in dated {
+        let start = period_start(date, period);
+        match groups.last_mut() {
+            Some((last, times)) if *last == start => times.push(time),
+            _ => groups.push((start, vec![time])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(start, times)| {
+            let finished: Vec<f64> = times.iter().flatten().copied().collect();
+            PeriodStats {
+                start,
+                count: times.len(),
+                mean: if finished.is_empty() {
+                    None
+                } else {
+                    Some(finished.iter().sum::<f64>() / finished.len() as f64)
+                },
+                best_single: finished.iter().copied().reduce(f64::min),
+                best_ao5: best_average(&times, 5),
+            }
+        })
+        .collect()
+}
+
+/// Spread and success rate of a set of solves.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Summary {
+    pub count: usize,
+    pub dnfs: usize,
+    pub plus2s: usize,
+    pub mean: Option<f64>,
+    pub std_dev: Option<f64>,
+    pub best: Option<f64>,
+    pub worst: Option<f64>,
+    pub median: Option<f64>,
+    pub q1: Option<f64>,
+    pub q3: Option<f64>,
+    pub p10: Option<f64>,
+    pub p90: Option<f64>,
+    pub sub_threshold: usize,
+}
+
+impl Summary {
+    /// Fraction of solves that were DNFs, from 0 to 1.
+    pub fn dnf_rate(&self) -> f64 {
+        self.dnfs as f64 / self.count.max(1) as f64
+    }
+
+    /// Fraction of solves that were given a +2, from 0 to 1.
+    pub fn plus2_rate(&self) -> f64 {
+        self.plus2s as f64 / self.count.max(1) as f64
+    }
+}
+
+/// Summarises solves, counting the finished ones under `threshold` seconds.
+///
+/// DNFs are left out of everything except the counts, and the standard
+/// deviation is that of the whole population of finished solves.
+pub fn summarize(solves: &[SolveStats], threshold: f64) -> Summary {
+    let mut finished: Vec<f64> = solves.iter().filter_map(solve_time).collect();
+    finished.sort_by(|a, b| a.total_cmp(b));
+    let mean = if finished.is_empty() {
+        None
+    } else {
+        Some(finished.iter().sum::<f64>() / finished.len() as f64)
+    };
+    let std_dev = mean.map(|mean| {
+        (finished
+            .iter()
+            .map(|time| (time - mean).powi(2))
+            .sum::<f64>()
+            / finished.len() as f64)
+            .sqrt()
+    });
+    Summary {
+        count: solves.len(),
+        dnfs: solves.iter().filter(|solve| solve.dnf).count(),
+        plus2s: solves.iter().filter(|solve| solve.plus2).count(),
+        mean,
+        std_dev,
+        best: finished.first().copied(),
+        worst: finished.last().copied(),
+        median: percentile(&finished, 50.0),
+        q1: percentile(&finished, 25.0),
+        q3: percentile(&finished, 75.0),
+        p10: percentile(&finished, 10.0),
+        p90: percentile(&finished, 90.0),
+        sub_threshold: finished.iter().filter(|time| **time < threshold).count(),
+    }
+}
+
+/// Results of blindfolded solves, where the single and the mean of 3 are what count.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct BldSummary {
+    pub attempts: usize,
+    pub successes: usize,
+    pub best: Option<f64>,
+    pub mo3: Option<f64>, // Mean of the last 3 attempts, `None` if one of them was a DNF
+    pub best_mo3: Option<f64>,
+    pub memo: Option<f64>, // Mean memorisation time of the successes that were split
+    pub execution: Option<f64>, // Mean execution time of the same successes
+}
+
+impl BldSummary {
+    /// Fraction of attempts that succeeded, from 0 to 1.
+    pub fn success_rate(&self) -> f64 {
+        self.successes as f64 / self.attempts.max(1) as f64
+    }
+}
+
+/// Summarises blindfolded solves, newest first as in a session.
+pub fn bld_summary(solves: &[SolveStats]) -> BldSummary {
+    let times = chronological_times(solves);
+    let splits: Vec<(f64, f64)> = solves
+        .iter()
+        .filter_map(|solve| Some((solve.memo?, solve_time(solve)?)))
+        .collect();
+    let mean = |values: Vec<f64>| {
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.iter().sum::<f64>() / values.len() as f64)
+        }
+    };
+    BldSummary {
+        attempts: solves.len(),
+        successes: times.iter().flatten().count(),
+        best: times.iter().flatten().copied().reduce(f64::min),
+        mo3: times
+            .len()
+            .checked_sub(3)
+            .and_then(|start| average_of(&times[start..])),
+        best_mo3: best_average(&times, 3),
+        memo: mean(splits.iter().map(|(memo, _)| *memo).collect()),
+        execution: mean(splits.iter().map(|(memo, time)| time - memo).collect()),
+    }
+}
+
+/// Mean split of each puzzle of a relay, over the finished solves that recorded splits.
+pub fn mean_splits(solves: &[SolveStats], puzzles: &[Cubes]) -> Vec<(Cubes, Option<f64>)> {
+    puzzles
+        .iter()
+        .enumerate()
+        .map(|(i, puzzle)| {
+            let splits: Vec<f64> = solves
+                .iter()
+                .filter(|solve| solve_time(solve).is_some())
+                .filter_map(|solve| solve.relay.get(i)?.split)
+                .collect();
+            let mean = if splits.is_empty() {
+                None
+            } else {
+                Some(splits.iter().sum::<f64>() / splits.len() as f64)
+            };
+            (*puzzle, mean)
+        })
+        .collect()
+}
+
+/// The best and worst single of some times, where a worst of `None` is a DNF.
+pub fn extremes(times: &[Option<f64>]) -> Option<(f64, Option<f64>)> {
+    let best = times.iter().flatten().copied().reduce(f64::min)?;
+    let worst = if times.iter().any(|time| time.is_none()) {
+        None
+    } else {
+        times.iter().flatten().copied().reduce(f64::max)
+    };
+    Some((best, worst))
+}
+
+/// Kinds of personal best, a single or an average of that many solves.
+pub const RECORD_SIZES: [usize; 4] = [1, 5, 12, 100];
+
+/// Name of a kind of personal best, such as "Single" or "Ao5".
+pub fn record_name(size: usize) -> String {
+    if size == 1 {
+        "Single".to_string()
+    } else {
+        format!("Ao{}", size)
+    }
+}
+
+/// A personal best, set by the solve done at `timestamp`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Record {
+    pub timestamp: i64,
+    pub value: f64,
+}
+
+/// Every result of a session for an event, as singles or averages of `size`.
+///
+/// Averages are only taken over consecutive solves of the same event within a session.
+pub fn results(solves: &[SolveStats], event: Event, size: usize) -> Vec<Record> {
+    let timed: Vec<(i64, Option<f64>)> = solves
+        .iter()
+        .rev()
+        .filter(|solve| solve.event() == event)
+        .filter_map(|solve| Some((solve.timestamp.parse().ok()?, solve_time(solve))))
+        .collect();
+    let times: Vec<Option<f64>> = timed.iter().map(|(_, time)| *time).collect();
+    if size == 1 {
+        return timed
+            .iter()
+            .filter_map(|(timestamp, time)| {
+                Some(Record {
+                    timestamp: *timestamp,
+                    value: (*time)?,
+                })
+            })
+            .collect();
+    }
+    rolling_average(&times, size)
+        .into_iter()
+        .filter_map(|(i, average)| {
+            Some(Record {
+                timestamp: timed[i].0,
+                value: average?,
+            })
+        })
+        .collect()
+}
+
+/// The results that were personal bests when they were set, oldest first.
+pub fn progression(mut results: Vec<Record>) -> Vec<Record> {
+    results.sort_by_key(|record| record.timestamp);
+    let mut bests: Vec<Record> = vec![];
+    for record in results {
+        if bests.last().map_or(true, |best| record.value < best.value) {
+            bests.push(record);
+        }
+    }
+    bests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::event::Variant;
+    use crate::app::relay::RelayPart;
+
+    fn solve(time: &str, timestamp: i64) -> SolveStats {
+        SolveStats {
+            time: time.to_string(),
+            timestamp: timestamp.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn dnf(timestamp: i64) -> SolveStats {
+        SolveStats {
+            dnf: true,
+            ..solve("0", timestamp)
+        }
+    }
+
+    #[test]
+    fn trims_five_percent_from_each_end() {
+        assert_eq!(trimmed(3), 0);
+        assert_eq!(trimmed(5), 1);
+        assert_eq!(trimmed(12), 1);
+        assert_eq!(trimmed(50), 3);
+        assert_eq!(trimmed(100), 5);
+    }
+
+    #[test]
+    fn averages_leave_out_the_best_and_worst() {
+        let times = [Some(1.0), Some(2.0), Some(3.0), Some(4.0), Some(100.0)];
+        assert_eq!(average_of(&times), Some(3.0));
+        // Means of 3 trim nothing
+        assert_eq!(average_of(&times[..3]), Some(2.0));
+        assert_eq!(average_of(&[]), None);
+    }
+
+    #[test]
+    fn dnfs_count_as_the_worst_result() {
+        let times = [Some(1.0), None, Some(3.0), Some(4.0), Some(5.0)];
+        assert_eq!(average_of(&times), Some(4.0));
+        assert_eq!(
+            average_of(&[None, None, Some(3.0), Some(4.0), Some(5.0)]),
+            None
+        );
+        assert_eq!(average_of(&[Some(1.0), None, Some(3.0)]), None);
+    }
+
+    #[test]
+    fn rolling_averages_end_at_each_solve() {
+        let times = [Some(1.0), Some(2.0), Some(3.0), Some(4.0)];
+        assert_eq!(
+            rolling_average(&times, 3),
+            vec![(2, Some(2.0)), (3, Some(3.0))]
+        );
+        assert_eq!(rolling_average(&times, 5), vec![]);
+    }
+
+    #[test]
+    fn best_averages_skip_dnf_averages() {
+        let times = [Some(1.0), Some(2.0), Some(3.0), Some(4.0)];
+        assert_eq!(best_average(&times, 3), Some(2.0));
+        assert_eq!(best_average(&[None, Some(1.0), Some(2.0)], 3), None);
+    }
+
+    #[test]
+    fn averages_of_a_solve_skip_other_events() {
+        let two_by_two = |time| SolveStats {
+            cube_type: Cubes::TwoByTwo,
+            ..solve(time, 0)
+        };
+        // Stored newest first, like the solves of a session
+        let solves = [
+            solve("9", 0),
+            solve("8", 0),
+            two_by_two("2"),
+            solve("7", 0),
+            two_by_two("3"),
+            dnf(0),
+        ];
+        assert_eq!(
+            times_ending_with(&solves, 1),
+            vec![Some(8.0), Some(7.0), None]
+        );
+        assert_eq!(times_ending_with(&solves, 2), vec![Some(2.0), Some(3.0)]);
+        assert_eq!(average_of(&times_ending_with(&solves, 0)[..3]), Some(8.0));
+    }
+
+    #[test]
+    fn percentiles_interpolate_between_ranks() {
+        let sorted = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(percentile(&sorted, 0.0), Some(1.0));
+        assert_eq!(percentile(&sorted, 50.0), Some(2.5));
+        assert_eq!(percentile(&sorted, 100.0), Some(4.0));
+        assert_eq!(percentile(&sorted, 150.0), Some(4.0));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn whiskers_stop_before_outliers() {
+        let spread = spread(&[100.0, 2.0, 4.0, 1.0, 3.0]).unwrap();
+        assert_eq!(
+            spread,
+            Spread {
+                lower_whisker: 1.0,
+                q1: 2.0,
+                median: 3.0,
+                q3: 4.0,
+                upper_whisker: 4.0,
+            }
+        );
+        assert_eq!(super::spread(&[]), None);
+    }
+
+    #[test]
+    fn periods_start_on_the_first_day() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap(); // A Wednesday
+        assert_eq!(period_start(date, Period::Day), date);
+        assert_eq!(
+            period_start(date, Period::Week),
+            NaiveDate::from_ymd_opt(2024, 1, 8).unwrap()
+        );
+        assert_eq!(
+            period_start(date, Period::Month),
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn groups_solves_by_day_oldest_first() {
+        let day = 24 * 60 * 60;
+        let start = 1_700_000_000;
+        // Stored newest first, like the solves of a session
+        let solves = [
+            solve("8", start + 2 * day),
+            solve("bad", start),
+            SolveStats {
+                timestamp: "never".to_string(),
+                ..solve("5", start)
+            },
+            dnf(start + 60),
+            solve("10", start),
+        ];
+        let groups = group_by_period(&solves, Period::Day);
+        assert_eq!(groups.len(), 2);
+        assert!(groups[0].start < groups[1].start);
+        assert_eq!(groups[0].count, 3);
+        assert_eq!(groups[0].mean, Some(10.0));
+        assert_eq!(groups[0].best_single, Some(10.0));
+        assert_eq!(groups[1].count, 1);
+        assert_eq!(groups[1].best_single, Some(8.0));
+    }
+
+    #[test]
+    fn summaries_leave_dnfs_out_of_the_times() {
+        let solves = [
+            SolveStats {
+                plus2: true,
+                ..solve("30", 0)
+            },
+            dnf(0),
+            solve("20", 0),
+            solve("10", 0),
+        ];
+        let summary = summarize(&solves, 25.0);
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.dnfs, 1);
+        assert_eq!(summary.plus2s, 1);
+        assert_eq!(summary.mean, Some(20.0));
+        assert_eq!(summary.best, Some(10.0));
+        assert_eq!(summary.worst, Some(30.0));
+        assert_eq!(summary.median, Some(20.0));
+        assert_eq!(summary.sub_threshold, 2);
+        assert!((summary.std_dev.unwrap() - (200.0f64 / 3.0).sqrt()).abs() < 1e-9);
+        assert_eq!(summary.dnf_rate(), 0.25);
+        assert_eq!(summarize(&[], 25.0), Summary::default());
+    }
+
+    #[test]
+    fn results_are_singles_or_averages_of_the_event() {
+        let record = |timestamp, value| Record { timestamp, value };
+        // Stored newest first, like the solves of a session
+        let solves = [
+            solve("9", 6),
+            SolveStats {
+                variant: Variant::OneHanded,
+                ..solve("1", 5)
+            },
+            solve("11", 4),
+            dnf(3),
+            solve("10", 2),
+            solve("12", 1),
+        ];
+        let event = Event::default();
+        assert_eq!(
+            results(&solves, event, 1),
+            vec![
+                record(1, 12.0),
+                record(2, 10.0),
+                record(4, 11.0),
+                record(6, 9.0)
+            ]
+        );
+        // The one-handed solve doesn't break up the average, but the DNF counts in it
+        assert_eq!(results(&solves, event, 5), vec![record(6, 11.0)]);
+        assert_eq!(results(&solves, event, 3), vec![]);
+        assert_eq!(
+            results(
+                &solves,
+                Event::new(Cubes::ThreeByThree, Variant::OneHanded),
+                1
+            ),
+            vec![record(5, 1.0)]
+        );
+    }
+
+    #[test]
+    fn progression_keeps_each_improvement() {
+        let record = |timestamp, value| Record { timestamp, value };
+        let results = vec![
+            record(3, 11.0),
+            record(1, 12.0),
+            record(4, 12.0),
+            record(2, 12.0),
+            record(5, 9.0),
+        ];
+        // Equalling a personal best doesn't set a new one
+        assert_eq!(
+            progression(results),
+            vec![record(1, 12.0), record(3, 11.0), record(5, 9.0)]
+        );
+        assert_eq!(progression(vec![]), vec![]);
+    }
+
+    #[test]
+    fn bld_summary_counts_dnfs_as_failed_attempts() {
+        let split = |time: &str, timestamp: i64, memo: f64| SolveStats {
+            memo: Some(memo),
+            ..solve(time, timestamp)
+        };
+        let solves = vec![
+            split("60", 5, 20.0),
+            solve("40", 4),
+            split("50", 3, 15.0),
+            SolveStats {
+                memo: Some(30.0),
+                ..dnf(2)
+            },
+            solve("70", 1),
+        ];
+        let summary = bld_summary(&solves);
+        assert_eq!((summary.attempts, summary.successes), (5, 4));
+        assert_eq!(summary.success_rate(), 0.8);
+        assert_eq!(summary.best, Some(40.0));
+        assert_eq!(summary.mo3, Some(50.0));
+        assert_eq!(summary.best_mo3, Some(50.0));
+        // The DNF's memo isn't counted
+        assert_eq!(summary.memo, Some(17.5));
+        assert_eq!(summary.execution, Some(37.5));
+
+        let failed = bld_summary(&solves[1..4]);
+        assert_eq!(failed.mo3, None);
+        assert_eq!(bld_summary(&[]).success_rate(), 0.0);
+    }
+
+    #[test]
+    fn mean_splits_skip_dnfs_and_missing_splits() {
+        let relay = |time: &str, splits: &[Option<f64>]| SolveStats {
+            relay: splits
+                .iter()
+                .map(|&split| RelayPart {
+                    puzzle: Cubes::ThreeByThree,
+                    scramble: "".to_string(),
+                    split,
+                })
+                .collect(),
+            ..solve(time, 0)
+        };
+        let solves = vec![
+            relay("45", &[Some(5.0), Some(10.0), Some(30.0)]),
+            relay("50", &[Some(7.0), Some(12.0), None]),
+            SolveStats {
+                dnf: true,
+                ..relay("3", &[Some(1.0), Some(1.0), Some(1.0)])
+            },
+            relay("60", &[]),
+        ];
+        let puzzles = [Cubes::TwoByTwo, Cubes::ThreeByThree, Cubes::FourByFour];
+        assert_eq!(
+            mean_splits(&solves, &puzzles),
+            vec![
+                (Cubes::TwoByTwo, Some(6.0)),
+                (Cubes::ThreeByThree, Some(11.0)),
+                (Cubes::FourByFour, Some(30.0)),
+            ]
+        );
+        assert_eq!(
+            mean_splits(&solves[2..], &puzzles[..1]),
+            vec![(Cubes::TwoByTwo, None)]
+        );
+    }
+}
@@ -0,0 +1,474 @@
+use cubesim::{Cube, FaceletCube, Move};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use super::cache;
+use super::cubie::{allowed, CubieCube, Turn};
+use super::kociemba::{flip, move_table, set_flip, FLIPS, UNKNOWN};
+
+// Step Solver
+
+/// Parts of a solve that can be solved optimally on their own.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Debug)]
+pub enum Step {
+    Cross,
+    XCross,
+    EOLine,
+    EOCross,
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Step::Cross => "Cross",
+            Step::XCross => "XCross",
+            Step::EOLine => "EOLine",
+            Step::EOCross => "EOCross",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+/// Colours in WCA scrambling orientation, with the rotation that puts each on the bottom.
+const COLOURS: [(&str, &str); 6] = [
+    ("White", "x2"),
+    ("Yellow", ""),
+    ("Green", "x'"),
+    ("Blue", "x"),
+    ("Red", "z"),
+    ("Orange", "z'"),
+];
+
+/// Rotations bringing each F2L slot to the front right, for XCross.
+const SLOTS: [&str; 4] = ["", "y", "y2", "y'"];
+
+/// Rotations that orient edges on the F/B or R/L axis, for EOLine and EOCross.
+const AXES: [&str; 2] = ["", "y"];
+
+/// Most solutions kept for each colour.
+const MAX_SOLUTIONS: usize = 100;
+
+/// File the distance tables are cached in.
+const CACHE_FILE: &str = "steps.bin";
+
+/// Changed whenever the tables are laid out differently, so older caches are generated again.
+const CACHE_VERSION: u32 = 1;
+
+/// Pieces, by their position when solved.
+const CROSS: [usize; 4] = [4, 5, 6, 7]; // DR, DF, DL, DB
+const FR_EDGE: usize = 8;
+const DFR_CORNER: usize = 4;
+const LINE: [usize; 2] = [5, 7]; // DF, DB
+const SIDE_LINE: [usize; 2] = [4, 6]; // DR, DL
+
+/// Distances to solving some pieces, and optionally the orientation of every edge.
+///
+/// Each tracked piece is stored as its position and orientation, so the
+/// table covers every placement of those pieces.
+struct StepTable {
+    edges: Vec<usize>,
+    corners: Vec<usize>,
+    eo: bool,
+    distances: Vec<u8>,
+}
+
+/// Where each edge goes, as `position * 2 + orientation`, after each turn.
+fn edge_moves() -> [[u8; 18]; 24] {
+    let mut moves = [[0; 18]; 24];
+    for turn in Turn::all() {
+        let cube = CubieCube::default().turn(turn);
+        for position in 0..12 {
+            let to = cube
+                .ep
+                .iter()
+                .position(|&e| e as usize == position)
+                .unwrap();
+            for orientation in 0..2 {
+                moves[position * 2 + orientation][turn.index()] =
+                    (to * 2) as u8 + (orientation as u8 + cube.eo[to]) % 2;
+            }
+        }
+    }
+    moves
+}
+
+/// Where each corner goes, as `position * 3 + orientation`, after each turn.
+fn corner_moves() -> [[u8; 18]; 24] {
+    let mut moves = [[0; 18]; 24];
+    for turn in Turn::all() {
+        let cube = CubieCube::default().turn(turn);
+        for position in 0..8 {
+            let to = cube
+                .cp
+                .iter()
+                .position(|&c| c as usize == position)
+                .unwrap();
+            for orientation in 0..3 {
+                moves[position * 3 + orientation][turn.index()] =
+                    (to * 3) as u8 + (orientation as u8 + cube.co[to]) % 3;
+            }
+        }
+    }
+    moves
+}
+
+impl StepTable {
+    fn new(edges: &[usize], corners: &[usize], eo: bool) -> Self {
+        Self {
+            edges: edges.to_vec(),
+            corners: corners.to_vec(),
+            eo,
+            distances: vec![],
+        }
+    }
+
+    fn flips(&self) -> usize {
+        if self.eo {
+            FLIPS
+        } else {
+            1
+        }
+    }
+
+    fn size(&self) -> usize {
+        24usize.pow((self.edges.len() + self.corners.len()) as u32) * self.flips()
+    }
+
+    fn index(&self, pieces: &[u8], flip: usize) -> usize {
+        pieces
+            .iter()
+            .fold(0, |index, &piece| index * 24 + piece as usize)
+            * self.flips()
+            + flip
+    }
+
+    /// Fills the table with a breadth first search from the solved pieces.
+    fn generate(
+        &mut self,
+        edge_moves: &[[u8; 18]; 24],
+        corner_moves: &[[u8; 18]; 24],
+        flip_moves: &[[u16; 18]],
+    ) {
+        let pieces = self.edges.len() + self.corners.len();
+        let solved: Vec<u8> = self
+            .edges
+            .iter()
+            .map(|&e| (e * 2) as u8)
+            .chain(self.corners.iter().map(|&c| (c * 3) as u8))
+            .collect();
+        let mut distances = vec![UNKNOWN; self.size()];
+        let mut queue = VecDeque::new();
+        let start = self.index(&solved, 0);
+        distances[start] = 0;
+        queue.push_back(start);
+        let mut current = vec![0u8; pieces];
+        while let Some(index) = queue.pop_front() {
+            let flip = index % self.flips();
+            let mut rest = index / self.flips();
+            for piece in current.iter_mut().rev() {
+                *piece = (rest % 24) as u8;
+                rest /= 24;
+            }
+            for m in 0..18 {
+                let mut next = [0u8; 6];
+                for (i, &piece) in current.iter().enumerate() {
+                    next[i] = if i < self.edges.len() {
+                        edge_moves[piece as usize][m]
+                    } else {
+                        corner_moves[piece as usize][m]
+                    };
+                }
+                let next_flip = if self.eo {
+                    flip_moves[flip][m] as usize
+                } else {
+                    0
+                };
+                let next = self.index(&next[..pieces], next_flip);
+                if distances[next] == UNKNOWN {
+                    distances[next] = distances[index] + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+        self.distances = distances;
+    }
+
+    /// Fewest turns needed to solve the tracked pieces of a cube.
+    fn distance(&self, cube: &CubieCube) -> usize {
+        let edges = self.edges.iter().map(|&edge| {
+            let position = cube.ep.iter().position(|&e| e as usize == edge).unwrap();
+            (position * 2) as u8 + cube.eo[position]
+        });
+        let corners = self.corners.iter().map(|&corner| {
+            let position = cube.cp.iter().position(|&c| c as usize == corner).unwrap();
+            (position * 3) as u8 + cube.co[position]
+        });
+        let pieces: Vec<u8> = edges.chain(corners).collect();
+        let flip = if self.eo { flip(cube) } else { 0 };
+        self.distances[self.index(&pieces, flip)] as usize
+    }
+}
+
+/// Distance tables for each step, all solved on the bottom.
+struct StepTables {
+    cross: StepTable,
+    cross_corner: StepTable,
+    cross_edge: StepTable,
+    line: StepTable,
+    side_line: StepTable,
+}
+
+static TABLES: OnceLock<StepTables> = OnceLock::new();
+
+fn tables() -> &'static StepTables {
+    TABLES.get_or_init(StepTables::load)
+}
+
+/// Whether the tables are ready, so solving won't have to wait for them.
+pub fn tables_ready() -> bool {
+    TABLES.get().is_some()
+}
+
+impl StepTables {
+    fn load() -> Self {
+        let cross_corner: Vec<usize> = vec![DFR_CORNER];
+        let cross_edge: Vec<usize> = CROSS.iter().copied().chain([FR_EDGE]).collect();
+        let mut tables = StepTables {
+            cross: StepTable::new(&CROSS, &[], false),
+            cross_corner: StepTable::new(&CROSS, &cross_corner, false),
+            cross_edge: StepTable::new(&cross_edge, &[], false),
+            line: StepTable::new(&LINE, &[], true),
+            side_line: StepTable::new(&SIDE_LINE, &[], true),
+        };
+        let len: usize = tables.all().iter().map(|table| table.size()).sum();
+        match cache::read(CACHE_FILE, CACHE_VERSION) {
+            Some(bytes) if bytes.len() == len => {
+                let mut rest = &bytes[..];
+                for table in tables.all_mut() {
+                    let (distances, next) = rest.split_at(table.size());
+                    table.distances = distances.to_vec();
+                    rest = next;
+                }
+            }
+            _ => {
+                let edge_moves = edge_moves();
+                let corner_moves = corner_moves();
+                let all: Vec<usize> = (0..18).collect();
+                let flip_moves = move_table(FLIPS, &all, set_flip, flip);
+                let mut bytes = Vec::with_capacity(len);
+                for table in tables.all_mut() {
+                    table.generate(&edge_moves, &corner_moves, &flip_moves);
+                    bytes.extend_from_slice(&table.distances);
+                }
+                cache::write(CACHE_FILE, CACHE_VERSION, &bytes);
+            }
+        }
+        tables
+    }
+
+    fn all(&self) -> [&StepTable; 5] {
+        [
+            &self.cross,
+            &self.cross_corner,
+            &self.cross_edge,
+            &self.line,
+            &self.side_line,
+        ]
+    }
+
+    fn all_mut(&mut self) -> [&mut StepTable; 5] {
+        [
+            &mut self.cross,
+            &mut self.cross_corner,
+            &mut self.cross_edge,
+            &mut self.line,
+            &mut self.side_line,
+        ]
+    }
+
+    /// Tables whose pieces make up a step, which is solved when they all are.
+    fn for_step(&self, step: Step) -> Vec<&StepTable> {
+        match step {
+            Step::Cross => vec![&self.cross],
+            Step::XCross => vec![&self.cross_corner, &self.cross_edge],
+            Step::EOLine => vec![&self.line],
+            Step::EOCross => vec![&self.cross, &self.line, &self.side_line],
+        }
+    }
+}
+
+/// Fewest turns solving the cross on the bottom of a cube.
+pub fn cross_distance(cube: &CubieCube) -> usize {
+    tables().cross.distance(cube)
+}
+
+/// Optimal solutions of a step on one colour.
+#[derive(Clone, PartialEq, Debug)]
+pub struct StepSolutions {
+    pub colour: &'static str,
+    pub length: usize,
+    pub solutions: Vec<(String, Vec<Turn>)>, // Rotation to do first, then the turns
+}
+
+fn distance(tables: &[&StepTable], cube: &CubieCube) -> usize {
+    tables
+        .iter()
+        .map(|table| table.distance(cube))
+        .max()
+        .unwrap_or(0)
+}
+
+fn search(
+    tables: &[&StepTable],
+    cube: &CubieCube,
+    depth: usize,
+    turns: &mut Vec<Turn>,
+    solutions: &mut Vec<Vec<Turn>>,
+) {
+    if solutions.len() >= MAX_SOLUTIONS {
+        return;
+    }
+    if depth == 0 {
+        solutions.push(turns.clone());
+        return;
+    }
+    for turn in Turn::all() {
+        if !allowed(turns.last().copied(), turn) {
+            continue;
+        }
+        let next = cube.turn(turn);
+        if distance(tables, &next) < depth {
+            turns.push(turn);
+            search(tables, &next, depth - 1, turns, solutions);
+            turns.pop();
+        }
+    }
+}
+
+/// Every optimal solution of a step, or of its shortest variant when there are several
+/// rotations to try, such as the four slots of an XCross.
+fn solve_colour(
+    scrambled: &FaceletCube,
+    tables: &[&StepTable],
+    rotations: &[String],
+) -> Vec<(String, Vec<Turn>)> {
+    let cubes: Vec<(String, CubieCube)> = rotations
+        .iter()
+        .filter_map(|rotation| {
+            let moves = cubesim::parse_scramble(rotation.clone());
+            Some((
+                rotation.clone(),
+                CubieCube::from_facelets(&scrambled.apply_moves(&moves))?,
+            ))
+        })
+        .collect();
+    // The tables of XCross and EOCross only give a lower bound, so keep deepening until something is found
+    let Some(mut depth) = cubes.iter().map(|(_, cube)| distance(tables, cube)).min() else {
+        return vec![];
+    };
+    let mut found = vec![];
+    while found.is_empty() {
+        for (rotation, cube) in &cubes {
+            if distance(tables, cube) <= depth {
+                let mut solutions = vec![];
+                search(tables, cube, depth, &mut vec![], &mut solutions);
+                found.extend(
+                    solutions
+                        .into_iter()
+                        .map(|solution| (rotation.clone(), solution)),
+                );
+            }
+        }
+        depth += 1;
+    }
+    found.truncate(MAX_SOLUTIONS);
+    found
+}
+
+/// Solves a step optimally on each colour of a scrambled 3x3, with every solution of that length.
+pub fn solve(scramble: &[Move], step: Step) -> Vec<StepSolutions> {
+    let tables = tables();
+    let step_tables = tables.for_step(step);
+    let extra: &[&str] = match step {
+        Step::Cross => &[""],
+        Step::XCross => &SLOTS,
+        Step::EOLine | Step::EOCross => &AXES,
+    };
+    let scrambled = FaceletCube::new(3).apply_moves(scramble);
+    COLOURS
+        .iter()
+        .map(|(colour, bottom)| {
+            let rotations: Vec<String> = extra
+                .iter()
+                .map(|extra| format!("{} {}", bottom, extra).trim().to_string())
+                .collect();
+            let solutions = solve_colour(&scrambled, &step_tables, &rotations);
+            StepSolutions {
+                colour,
+                length: solutions.first().map_or(0, |(_, turns)| turns.len()),
+                solutions,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The cube after the scramble, the rotation and then the turns of a solution.
+    fn solved(scramble: &str, rotation: &str, turns: &[Turn]) -> CubieCube {
+        let moves = cubesim::parse_scramble(format!("{} {}", scramble, rotation));
+        let cube = FaceletCube::new(3).apply_moves(&moves);
+        CubieCube::from_facelets(&cube).unwrap().turns(turns)
+    }
+
+    fn cross_solved(cube: &CubieCube) -> bool {
+        CROSS
+            .iter()
+            .all(|&edge| cube.ep[edge] as usize == edge && cube.eo[edge] == 0)
+    }
+
+    #[test]
+    fn solutions_solve_their_step_on_every_colour() {
+        let scramble = "R U R' U' F2 D L' B2 U' R2 F D2 L";
+        let moves = cubesim::parse_scramble(scramble.to_string());
+        for step in [Step::Cross, Step::XCross, Step::EOLine, Step::EOCross] {
+            let colours = solve(&moves, step);
+            assert_eq!(colours.len(), COLOURS.len());
+            for colour in &colours {
+                assert!(!colour.solutions.is_empty());
+                for (rotation, turns) in &colour.solutions {
+                    assert_eq!(turns.len(), colour.length);
+                    let cube = solved(scramble, rotation, turns);
+                    let done = match step {
+                        Step::Cross => cross_solved(&cube),
+                        Step::XCross => {
+                            cross_solved(&cube)
+                                && cube.ep[FR_EDGE] as usize == FR_EDGE
+                                && cube.eo[FR_EDGE] == 0
+                                && cube.cp[DFR_CORNER] as usize == DFR_CORNER
+                                && cube.co[DFR_CORNER] == 0
+                        }
+                        Step::EOLine => {
+                            cube.eo == [0; 12]
+                                && LINE.iter().all(|&edge| cube.ep[edge] as usize == edge)
+                        }
+                        Step::EOCross => cube.eo == [0; 12] && cross_solved(&cube),
+                    };
+                    assert!(done, "{} {:?} on {}", step, turns, colour.colour);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn a_solved_cube_needs_no_turns() {
+        for (colour, (name, bottom)) in solve(&[], Step::Cross).iter().zip(COLOURS) {
+            assert_eq!(colour.colour, name);
+            assert_eq!(colour.length, 0);
+            assert_eq!(colour.solutions, vec![(bottom.to_string(), vec![])]);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use cubesim::{Cube, Face, FaceletCube};
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use super::notation;
+
+/// Groups of last layer cases that can be trained.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CaseSet {
+    Oll,
+    Pll,
+}
+
+impl Display for CaseSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            CaseSet::Oll => "OLL",
+            CaseSet::Pll => "PLL",
+        };
+        write!(f, "{}", string)
+    }
+}
+
+/// Number and algorithm of every OLL case.
+const OLL: [(&str, &str); 57] = [
+    ("1", "R U2 R2 F R F' U2 R' F R F'"),
+    ("2", "F R U R' U' F' f R U R' U' f'"),
+    ("3", "f R U R' U' f' U' F R U R' U' F'"),
+    ("4", "f R U R' U' f' U F R U R' U' F'"),
+    ("5", "r' U2 R U R' U r"),
+    ("6", "r U2 R' U' R U' r'"),
+    ("7", "r U R' U R U2 r'"),
+    ("8", "l' U' L U' L' U2 l"),
+    ("9", "R U R' U' R' F R2 U R' U' F'"),
+    ("10", "R U R' U R' F R F' R U2 R'"),
+    ("11", "r U R' U R' F R F' R U2 r'"),
+    ("12", "M' R' U' R U' R' U2 R U' R r'"),
+    ("13", "F U R U' R2 F' R U R U' R'"),
+    ("14", "R' F R U R' F' R F U' F'"),
+    ("15", "l' U' l L' U' L U l' U l"),
+    ("16", "r U r' R U R' U' r U' r'"),
+    ("17", "R U R' U R' F R F' U2 R' F R F'"),
+    ("18", "r U R' U R U2 r2 U' R U' R' U2 r"),
+    ("19", "r' R U R U R' U' M' R' F R F'"),
+    ("20", "r U R' U' M2 U R U' R' U' M'"),
+    ("21", "R U2 R' U' R U R' U' R U' R'"),
+    ("22", "R U2 R2 U' R2 U' R2 U2 R"),
+    ("23", "R2 D' R U2 R' D R U2 R"),
+    ("24", "r U R' U' r' F R F'"),
+    ("25", "F' r U R' U' r' F R"),
+    ("26", "R U2 R' U' R U' R'"),
+    ("27", "R U R' U R U2 R'"),
+    ("28", "r U R' U' r' R U R U' R'"),
+    ("29", "R U R' U' R U' R' F' U' F R U R'"),
+    ("30", "F R' F R2 U' R' U' R U R' F2"),
+    ("31", "R' U' F U R U' R' F' R"),
+    ("32", "L U F' U' L' U L F L'"),
+    ("33", "R U R' U' R' F R F'"),
+    ("34", "R U R2 U' R' F R U R U' F'"),
+    ("35", "R U2 R2 F R F' R U2 R'"),
+    ("36", "L' U' L U' L' U L U L F' L' F"),
+    ("37", "F R' F' R U R U' R'"),
+    ("38", "R U R' U R U' R' U' R' F R F'"),
+    ("39", "L F' L' U' L U F U' L'"),
+    ("40", "R' F R U R' U' F' U R"),
+    ("41", "R U R' U R U2 R' F R U R' U' F'"),
+    ("42", "R' U' R U' R' U2 R F R U R' U' F'"),
+    ("43", "F' U' L' U L F"),
+    ("44", "F U R U' R' F'"),
+    ("45", "F R U R' U' F'"),
+    ("46", "R' U' R' F R F' U R"),
+    ("47", "R' U' R' F R F' R' F R F' U R"),
+    ("48", "F R U R' U' R U R' U' F'"),
+    ("49", "r U' r2 U r2 U r2 U' r"),
+    ("50", "r' U r2 U' r2 U' r2 U r'"),
+    ("51", "F U R U' R' U R U' R' F'"),
+    ("52", "R U R' U R U' B U' B' R'"),
+    ("53", "l' U2 L U L' U' L U L' U l"),
+    ("54", "r U2 R' U' R U R' U' R U' r'"),
+    ("55", "R' F R U R U' R2 F' R2 U' R' U R U R'"),
+    ("56", "r' U' r U' R' U R U' R' U R r' U r"),
+    ("57", "R U R' U' M' U R U' r'"),
+];
+
+/// Name and algorithm of every PLL case.
+const PLL: [(&str, &str); 21] = [
+    ("Aa", "x R' U R' D2 R U' R' D2 R2 x'"),
+    ("Ab", "x R2 D2 R U R' D2 R U' R x'"),
+    ("E", "x' R U' R' D R U R' D' R U R' D R U' R' D' x"),
+    ("F", "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R"),
+    ("Ga", "R2 U R' U R' U' R U' R2 U' D R' U R D'"),
+    ("Gb", "R' U' R U D' R2 U R' U R U' R U' R2 D"),
+    ("Gc", "R2 U' R U' R U R' U R2 U D' R U' R' D"),
+    ("Gd", "R U R' U' D R2 U' R U' R' U R' U R2 D'"),
+    ("H", "M2 U M2 U2 M2 U M2"),
+    ("Ja", "R' U L' U2 R U' R' U2 R L"),
+    ("Jb", "R U R' F' R U R' U' R' F R2 U' R'"),
+    (
+        "Na",
+        "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'",
+    ),
+    ("Nb", "R' U R U' R' F' U' F R U R' F R' F' R U' R"),
+    ("Ra", "R U' R' U' R U R D R' U' R D' R' U2 R'"),
+    ("Rb", "R2 F R U R U' R' F' R U2 R' U2 R"),
+    ("T", "R U R' U' R' F R2 U' R' U' R U R' F'"),
+    ("Ua", "M2 U M U2 M' U M2"),
+    ("Ub", "M2 U' M U2 M' U' M2"),
+    ("V", "R U' R U R' D R D' R U' D R2 U R2 D' R2"),
+    ("Y", "F R U' R' U' R U R' F' R U R' U' R' F R F'"),
+    ("Z", "M' U M2 U M2 U M' U2 M2"),
+];
+
+/// A last layer case and an algorithm solving it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Case {
+    pub set: CaseSet,
+    pub name: &'static str,
+    pub algorithm: &'static str,
+}
+
+impl Case {
+    /// The name used to store the case, such as "OLL 21" or "PLL T".
+    pub fn id(&self) -> String {
+        format!("{} {}", self.set, self.name)
+    }
+}
+
+/// Every case of a set.
+pub fn cases(set: CaseSet) -> Vec<Case> {
+    let cases: &[(&str, &str)] = match set {
+        CaseSet::Oll => &OLL,
+        CaseSet::Pll => &PLL,
+    };
+    cases
+        .iter()
+        .map(|&(name, algorithm)| Case {
+            set,
+            name,
+            algorithm,
+        })
+        .collect()
+}
+
+/// Finds a case by its id.
+pub fn case(id: &str) -> Option<Case> {
+    [CaseSet::Oll, CaseSet::Pll]
+        .into_iter()
+        .flat_map(cases)
+        .find(|case| case.id() == id)
+}
+
+/// A scramble setting up the case `algorithm` solves: undoing it, with a random U turn before and after.
+pub fn setup(algorithm: &str) -> String {
+    let mut rng = rand::thread_rng();
+    let auf = ["", "U", "U2", "U'"];
+    let algorithm = notation::parse(algorithm).unwrap_or_default();
+    let text = format!(
+        "{} {} {}",
+        auf[rng.gen_range(0..4)],
+        notation::turns_string(&notation::invert(&algorithm)),
+        auf[rng.gen_range(0..4)]
+    );
+    let turns = notation::parse(&text).unwrap_or_default();
+    notation::turns_string(&notation::simplify(&turns))
+}
+
+/// The last layer after `setup` seen from above, with the back at the top.
+///
+/// The 3x3 in the middle is the U face, surrounded by the top row of each side.
+/// The corners of the 5x5 grid are left empty.
+pub fn last_layer(setup: &str) -> Option<[[Option<Face>; 5]; 5]> {
+    let state = FaceletCube::new(3)
+        .apply_moves(&notation::parse_moves(setup, 3).ok()?)
+        .state();
+    // Stickers are numbered face by face in the order U, R, F, D, L, B
+    let sticker = |face: usize, row: usize, column: usize| Some(state[face * 9 + row * 3 + column]);
+    let mut grid = [[None; 5]; 5];
+    for i in 1..4 {
+        grid[0][i] = sticker(5, 0, 3 - i);
+        grid[4][i] = sticker(2, 0, i - 1);
+        grid[i][0] = sticker(4, 0, i - 1);
+        grid[i][4] = sticker(1, 0, 3 - i);
+        for j in 1..4 {
+            grid[i][j] = sticker(0, i - 1, j - 1);
+        }
+    }
+    Some(grid)
+}
+
+/// Attempts at one case.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct CaseStats {
+    pub times: Vec<f64>, // Execution time of each attempt, in seconds
+    pub missed: usize,   // Attempts where the case wasn't recognised
+}
+
+impl CaseStats {
+    pub fn average(&self) -> Option<f64> {
+        if self.times.is_empty() {
+            None
+        } else {
+            Some(self.times.iter().sum::<f64>() / self.times.len() as f64)
+        }
+    }
+
+    pub fn best(&self) -> Option<f64> {
+        self.times.iter().copied().reduce(f64::min)
+    }
+
+    /// Share of attempts where the case was recognised, from 0 to 1.
+    pub fn recognition(&self) -> Option<f64> {
+        if self.times.is_empty() {
+            None
+        } else {
+            Some(1.0 - self.missed as f64 / self.times.len() as f64)
+        }
+    }
+}
+
+/// The last layer trainer, scrambling into selected cases and keeping statistics for each.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct Trainer {
+    pub active: bool,          // Whether the timer is training cases instead of solving
+    pub show_case: bool,       // Whether the case is shown before it is solved
+    pub selected: Vec<String>, // Ids of the cases being trained
+    pub stats: HashMap<String, CaseStats>, // Statistics of each case by id
+    pub case: Option<String>,  // Case the current scramble sets up
+    pub last: Option<(String, String)>, // Case and setup of the last attempt, shown once it's done
+    pub last_missed: bool,     // Whether the last attempt was marked as not recognised
+}
+
+impl Trainer {
+    /// Picks a random selected case, returning a scramble setting it up.
+    pub fn next(&mut self) -> Option<String> {
+        let cases: Vec<Case> = self.selected.iter().filter_map(|id| case(id)).collect();
+        if cases.is_empty() {
+            self.case = None;
+            return None;
+        }
+        let case = cases[rand::thread_rng().gen_range(0..cases.len())];
+        self.case = Some(case.id());
+        Some(setup(case.algorithm))
+    }
+
+    /// Records an attempt at the current case, set up by `setup`.
+    pub fn finish(&mut self, time: f64, setup: &str) {
+        if let Some(case) = self.case.take() {
+            self.stats.entry(case.clone()).or_default().times.push(time);
+            self.last = Some((case, setup.to_string()));
+            self.last_missed = false;
+        }
+    }
+
+    /// Marks the last attempt as not recognised.
+    pub fn miss(&mut self) {
+        if let Some((case, _)) = &self.last {
+            if !self.last_missed {
+                self.stats.entry(case.clone()).or_default().missed += 1;
+                self.last_missed = true;
+            }
+        }
+    }
+}
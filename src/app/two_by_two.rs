@@ -0,0 +1,287 @@
+use cubesim::{Cube, Face, FaceletCube, Move};
+use rand::Rng;
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+use super::cache;
+use super::cubie::{allowed, CubieCube, Turn, CORNER_FACELETS};
+use super::kociemba::{perm_rank, perm_unrank, UNKNOWN};
+use super::notation::turns_string;
+
+// 2x2 Solver
+
+/// The DBL corner never moves, so every state is solved with U, R and F turns.
+const TURNS: [usize; 9] = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+
+/// Positions of the corners that move, every corner but DBL.
+const MOVING: [usize; 7] = [0, 1, 2, 3, 4, 5, 7];
+
+const PERMS: usize = 5040; // Permutations of the moving corners, 7!
+const TWISTS: usize = 729; // Twists of the moving corners, 3^6
+
+/// Fewest turns a random state scramble may solve in, like the WCA scrambler.
+pub const MIN_DISTANCE: usize = 4;
+
+/// Most optimal solutions returned.
+const MAX_SOLUTIONS: usize = 20;
+
+/// File the distance table is cached in.
+const CACHE_FILE: &str = "2x2.bin";
+
+/// Changed whenever the tables are laid out differently, so older caches are generated again.
+const CACHE_VERSION: u32 = 1;
+
+fn perm(cube: &CubieCube) -> usize {
+    // DBL stays put, so the DRB corner takes its place in the ranking
+    let corners: Vec<u8> = MOVING
+        .iter()
+        .map(|&position| cube.cp[position].min(6))
+        .collect();
+    perm_rank(&corners)
+}
+
+fn set_perm(cube: &mut CubieCube, perm: usize) {
+    for (i, corner) in perm_unrank(perm, 7).into_iter().enumerate() {
+        cube.cp[MOVING[i]] = if corner == 6 { 7 } else { corner };
+    }
+    cube.cp[6] = 6;
+}
+
+fn twist(cube: &CubieCube) -> usize {
+    cube.co[..6].iter().fold(0, |t, &o| t * 3 + o as usize)
+}
+
+fn set_twist(cube: &mut CubieCube, mut twist: usize) {
+    let mut sum = 0;
+    for i in (0..6).rev() {
+        cube.co[i] = (twist % 3) as u8;
+        sum += cube.co[i];
+        twist /= 3;
+    }
+    cube.co[6] = 0;
+    cube.co[7] = (3 - sum % 3) % 3;
+}
+
+fn index(cube: &CubieCube) -> usize {
+    perm(cube) * TWISTS + twist(cube)
+}
+
+fn from_index(index: usize) -> CubieCube {
+    let mut cube = CubieCube::default();
+    set_perm(&mut cube, index / TWISTS);
+    set_twist(&mut cube, index % TWISTS);
+    cube
+}
+
+/// Distance from solved of every 2x2 state.
+static DISTANCES: OnceLock<Vec<u8>> = OnceLock::new();
+
+fn distances() -> &'static [u8] {
+    DISTANCES.get_or_init(load)
+}
+
+/// Whether the table is ready, so solving or scrambling won't have to wait for it.
+pub fn tables_ready() -> bool {
+    DISTANCES.get().is_some()
+}
+
+/// Starts making the table in the background, unless that has already started.
+///
+/// The web has no threads, so there the table is only made once something waits for it.
+pub fn prepare() {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        static STARTED: std::sync::Once = std::sync::Once::new();
+        STARTED.call_once(|| {
+            std::thread::spawn(distances);
+        });
+    }
+}
+
+/// Makes the table if it isn't ready, waiting for it if it's being made in the background.
+pub fn wait_for_tables() {
+    distances();
+}
+
+fn load() -> Vec<u8> {
+    if let Some(bytes) = cache::read(CACHE_FILE, CACHE_VERSION) {
+        if bytes.len() == PERMS * TWISTS {
+            return bytes;
+        }
+    }
+    // The permutation and twist move independently, so each gets a small move table
+    let perm_moves: Vec<[usize; 9]> = (0..PERMS)
+        .map(|p| {
+            let mut cube = CubieCube::default();
+            set_perm(&mut cube, p);
+            TURNS.map(|m| perm(&cube.turn(Turn::from_index(m))))
+        })
+        .collect();
+    let twist_moves: Vec<[usize; 9]> = (0..TWISTS)
+        .map(|t| {
+            let mut cube = CubieCube::default();
+            set_twist(&mut cube, t);
+            TURNS.map(|m| twist(&cube.turn(Turn::from_index(m))))
+        })
+        .collect();
+    let mut distances = vec![UNKNOWN; PERMS * TWISTS];
+    let mut queue = VecDeque::new();
+    distances[0] = 0;
+    queue.push_back(0);
+    while let Some(index) = queue.pop_front() {
+        let (p, t) = (index / TWISTS, index % TWISTS);
+        for m in 0..TURNS.len() {
+            let next = perm_moves[p][m] * TWISTS + twist_moves[t][m];
+            if distances[next] == UNKNOWN {
+                distances[next] = distances[index] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    cache::write(CACHE_FILE, CACHE_VERSION, &distances);
+    distances
+}
+
+pub fn distance(cube: &CubieCube) -> usize {
+    distances()[index(cube)] as usize
+}
+
+/// Reads the corners off a 2x2 `cubesim` cube, turned so that the DBL corner is solved.
+///
+/// Colours are matched to faces using the DBL corner and the WCA colour scheme,
+/// so rotations and any face turns may have been applied to it.
+pub fn from_facelets(cube: &FaceletCube) -> Option<CubieCube> {
+    if cube.size() != 2 {
+        return None;
+    }
+    let state = cube.state();
+    // Sticker of a 3x3 facelet, taken from the 2x2 corner in the same place
+    let sticker = |facelet: usize| {
+        let (face, row, column) = (facelet / 9, facelet % 9 / 3, facelet % 3);
+        state[face * 4 + row / 2 * 2 + column / 2]
+    };
+    let opposite = |face: Face| match face {
+        Face::U => Face::D,
+        Face::D => Face::U,
+        Face::R => Face::L,
+        Face::L => Face::R,
+        Face::F => Face::B,
+        Face::B => Face::F,
+        Face::X => Face::X,
+    };
+    let [d, b, l] = CORNER_FACELETS[6].map(sticker);
+    // Faces in the order U, R, F, D, L, B
+    let faces = [opposite(d), opposite(l), opposite(b), d, l, b];
+    let mut facelets = [0usize; 54];
+    for positions in CORNER_FACELETS {
+        for facelet in positions {
+            facelets[facelet] = faces.iter().position(|&face| face == sticker(facelet))?;
+        }
+    }
+    CubieCube::from_corner_faces(&facelets)
+}
+
+/// Applies a scramble to a solved 2x2, returning `None` if it can't be read as 2x2 moves.
+pub fn from_moves(moves: &[Move]) -> Option<CubieCube> {
+    from_facelets(&FaceletCube::new(2).apply_moves(moves))
+}
+
+/// Every optimal solution of a 2x2, up to a limit, using U, R and F turns.
+pub fn solve(cube: &CubieCube) -> Vec<Vec<Turn>> {
+    let mut solutions = vec![];
+    search(cube, distance(cube), &mut vec![], &mut solutions);
+    solutions
+}
+
+fn search(cube: &CubieCube, depth: usize, turns: &mut Vec<Turn>, solutions: &mut Vec<Vec<Turn>>) {
+    if solutions.len() >= MAX_SOLUTIONS {
+        return;
+    }
+    if depth == 0 {
+        solutions.push(turns.clone());
+        return;
+    }
+    for m in TURNS {
+        let turn = Turn::from_index(m);
+        if !allowed(turns.last().copied(), turn) {
+            continue;
+        }
+        let next = cube.turn(turn);
+        // The table is exact, so only turns getting one closer lead to optimal solutions
+        if distance(&next) == depth - 1 {
+            turns.push(turn);
+            search(&next, depth - 1, turns, solutions);
+            turns.pop();
+        }
+    }
+}
+
+/// A scramble leading to a uniformly random 2x2 state, at least `min_distance` turns from solved.
+pub fn random_state_scramble(min_distance: usize, rng: &mut impl Rng) -> String {
+    let cube = loop {
+        let cube = from_index(rng.gen_range(0..PERMS * TWISTS));
+        if distance(&cube) >= min_distance {
+            break cube;
+        }
+    };
+    // Undoing a solution of the state gives a scramble for it
+    let solution = &solve(&cube)[0];
+    let scramble: Vec<Turn> = solution
+        .iter()
+        .rev()
+        .map(|turn| Turn {
+            face: turn.face,
+            power: 4 - turn.power,
+        })
+        .collect();
+    turns_string(&scramble)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn cube(scramble: &str) -> Option<CubieCube> {
+        from_moves(&cubesim::parse_scramble(scramble.to_string()))
+    }
+
+    #[test]
+    fn solutions_are_optimal() {
+        let cube = cube("R U2 F' R").unwrap();
+        let solutions = solve(&cube);
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            assert_eq!(solution.len(), 4);
+            // A 2x2 has no edges, so only the corners need to be solved
+            let solved = cube.turns(solution);
+            assert_eq!((solved.cp, solved.co), ([0, 1, 2, 3, 4, 5, 6, 7], [0; 8]));
+        }
+    }
+
+    #[test]
+    fn rotations_and_other_faces_are_read_from_the_fixed_corner() {
+        let scrambled = cube("x L D' y B").unwrap();
+        assert_eq!(distance(&scrambled), solve(&scrambled)[0].len());
+        assert_eq!(cube("x y2 z'"), Some(CubieCube::default()));
+    }
+
+    #[test]
+    fn scrambles_are_far_enough_from_solved() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..20 {
+            let scramble = random_state_scramble(MIN_DISTANCE, &mut rng);
+            let cube = cube(&scramble).unwrap();
+            assert_eq!(distance(&cube), scramble.split_whitespace().count());
+            assert!(distance(&cube) >= MIN_DISTANCE);
+        }
+    }
+
+    #[test]
+    fn indexes_every_state() {
+        for index in [0, 1, TWISTS, PERMS * TWISTS - 1] {
+            assert_eq!(super::index(&from_index(index)), index);
+        }
+    }
+}